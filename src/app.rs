@@ -1,9 +1,13 @@
 //! Application layer: orchestrates domain logic through port traits.
 //!
-//! Defines the port traits (`WeatherFetcher`, `WeatherFormatter`) that
-//! infrastructure adapters implement. No `use crate::infra::` imports here.
+//! Defines the port traits (`WeatherFetcher`, `WeatherFormatter`, `AlertNotifier`,
+//! `LocationSearcher`, `LocationSource`) that infrastructure adapters implement. No `use crate::infra::` imports here.
 
-use crate::domain::WeatherData;
+use crate::domain::{
+    Coordinates, Location, LocationQuery, LocationWeather, PlaceMatch, WeatherAlert, WeatherData,
+};
+use anyhow::Context;
+use time::{Duration, OffsetDateTime};
 
 /// Port trait for fetching weather data.
 ///
@@ -11,7 +15,134 @@ use crate::domain::WeatherData;
 /// Uses `anyhow::Error` because network/HTTP errors are genuinely
 /// open-ended infrastructure concerns.
 pub trait WeatherFetcher {
-    fn fetch_weather(&self, location: &str) -> Result<WeatherData, anyhow::Error>;
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error>;
+
+    /// Human-readable name of the service behind this fetcher
+    fn name(&self) -> &str;
+}
+
+impl<F: WeatherFetcher + ?Sized> WeatherFetcher for Box<F> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+        (**self).fetch_weather(location)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// Composite fetcher that tries each wrapped fetcher in order until one succeeds.
+///
+/// The successful provider is recorded by the adapter in `WeatherData::source`;
+/// when every provider fails, the error lists each provider's failure reason.
+pub struct FallbackFetcher {
+    fetchers: Vec<Box<dyn WeatherFetcher + Send + Sync>>,
+    name: String,
+}
+
+impl FallbackFetcher {
+    /// Create a fallback chain from fetchers in order of preference
+    pub fn new(fetchers: Vec<Box<dyn WeatherFetcher + Send + Sync>>) -> Self {
+        let name = fetchers
+            .iter()
+            .map(|fetcher| fetcher.name())
+            .collect::<Vec<_>>()
+            .join(" → ");
+        Self { fetchers, name }
+    }
+}
+
+impl WeatherFetcher for FallbackFetcher {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+        let mut failures = Vec::new();
+
+        for fetcher in &self.fetchers {
+            match fetcher.fetch_weather(location) {
+                Ok(data) => return Ok(data),
+                Err(e) if self.fetchers.len() == 1 => return Err(e),
+                Err(e) => failures.push(format!("• {}: {:#}", fetcher.name(), e)),
+            }
+        }
+
+        if failures.is_empty() {
+            anyhow::bail!("No weather providers configured");
+        }
+        anyhow::bail!("All weather providers failed:\n{}", failures.join("\n"))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Decorator that shows the location under a name the user chose, e.g. one saved by
+/// the `search` command together with the place's coordinates.
+///
+/// Without a name the inner fetcher's results pass through unchanged.
+pub struct LabelledFetcher<F> {
+    inner: F,
+    name: Option<String>,
+}
+
+impl<F: WeatherFetcher> LabelledFetcher<F> {
+    /// Wrap `inner`, renaming the location it resolves to `name` if one is given
+    pub fn new(inner: F, name: Option<String>) -> Self {
+        Self { inner, name }
+    }
+}
+
+impl<F: WeatherFetcher> WeatherFetcher for LabelledFetcher<F> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+        let mut data = self.inner.fetch_weather(location)?;
+        if let Some(name) = &self.name {
+            data.location = data.location.with_name(name.clone());
+        }
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Decimal places a located position is rounded to: about a kilometre, close enough for
+/// weather, and steady enough for cached results to be reused as the fix wobbles
+const LOCATED_DECIMALS: i32 = 2;
+
+/// Decorator that fetches weather for wherever the computer is when asked for
+/// `LocationQuery::Current`, finding the position with `source` on every fetch.
+///
+/// Other queries pass through unchanged.
+pub struct LocatingFetcher<F, S> {
+    inner: F,
+    source: S,
+}
+
+impl<F: WeatherFetcher, S: LocationSource> LocatingFetcher<F, S> {
+    /// Wrap `inner`, resolving the current location with `source`
+    pub fn new(inner: F, source: S) -> Self {
+        Self { inner, source }
+    }
+}
+
+impl<F: WeatherFetcher, S: LocationSource> WeatherFetcher for LocatingFetcher<F, S> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+        if *location != LocationQuery::Current {
+            return self.inner.fetch_weather(location);
+        }
+
+        let coordinates = self
+            .source
+            .locate()
+            .map_err(|e| e.context("Failed to find the current location"))?;
+        self.inner.fetch_weather(&LocationQuery::Coordinates(
+            coordinates.rounded(LOCATED_DECIMALS),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
 }
 
 /// Port trait for formatting weather data into some output representation.
@@ -21,74 +152,376 @@ pub trait WeatherFetcher {
 pub trait WeatherFormatter {
     type Output;
     fn format(&self, data: &WeatherData) -> Result<Self::Output, anyhow::Error>;
+
+    /// Format `data` for one of several locations, summarising every location's weather
+    /// (`data`'s own included) alongside. Without a summary layout only `data` is shown.
+    fn format_with_summary(
+        &self,
+        data: &WeatherData,
+        _summary: &[LocationWeather],
+    ) -> Result<Self::Output, anyhow::Error> {
+        self.format(data)
+    }
+}
+
+/// Port trait for telling the user about a weather alert outside the bar, e.g. with a
+/// desktop notification
+pub trait AlertNotifier {
+    fn notify(&self, location: &Location, alert: &WeatherAlert) -> Result<(), anyhow::Error>;
+}
+
+/// Port trait for listing the places a location query could mean, so the user can
+/// pick between places sharing a name
+pub trait LocationSearcher {
+    /// Up to `limit` places matching `query`, best match first
+    fn search_places(&self, query: &str, limit: usize) -> Result<Vec<PlaceMatch>, anyhow::Error>;
+
+    /// Human-readable name of the service behind this searcher
+    fn name(&self) -> &str;
+}
+
+/// Port trait for finding where the computer currently is, e.g. from the system's
+/// location service
+pub trait LocationSource {
+    fn locate(&self) -> Result<Coordinates, anyhow::Error>;
+}
+
+/// Fetch weather for every location, in order.
+///
+/// Several locations are fetched concurrently, one thread each, so a slow provider
+/// response for one place does not hold up the others.
+pub fn fetch_all<F: WeatherFetcher + Sync + ?Sized>(
+    fetcher: &F,
+    locations: &[LocationQuery],
+) -> Vec<Result<WeatherData, anyhow::Error>> {
+    if let [location] = locations {
+        return vec![fetcher.fetch_weather(location)];
+    }
+
+    std::thread::scope(|scope| {
+        let handles = locations
+            .iter()
+            .map(|location| scope.spawn(move || fetcher.fetch_weather(location)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
 }
 
-/// Fetch weather data and format it for output.
+/// Summary entries for `locations`, with the weather known for each
+fn summarise(
+    locations: &[LocationQuery],
+    weather: impl IntoIterator<Item = Option<WeatherData>>,
+    shown: usize,
+) -> Vec<LocationWeather> {
+    locations
+        .iter()
+        .zip(weather)
+        .enumerate()
+        .map(|(index, (query, weather))| LocationWeather {
+            query: query.clone(),
+            weather,
+            shown: index == shown,
+        })
+        .collect()
+}
+
+/// Fetch weather data for `locations` and format it for output.
+///
+/// The output is about the location at index `shown`; with more than one location,
+/// the others are fetched alongside it and summarised. Fails when the shown location
+/// cannot be fetched, whatever happens to the others.
 ///
 /// Generic over both ports, enabling test doubles for either side.
-pub fn fetch_and_format<F: WeatherFetcher, Fmt: WeatherFormatter>(
+pub fn fetch_and_format<F: WeatherFetcher + Sync + ?Sized, Fmt: WeatherFormatter>(
     fetcher: &F,
     formatter: &Fmt,
-    location: &str,
+    locations: &[LocationQuery],
+    shown: usize,
 ) -> Result<Fmt::Output, anyhow::Error> {
-    let weather_data = fetcher.fetch_weather(location)?;
-    let output = formatter.format(&weather_data)?;
+    let mut results = fetch_all(fetcher, locations);
+    if results.len() <= 1 {
+        let weather_data = results
+            .pop()
+            .unwrap_or_else(|| Err(anyhow::anyhow!("No location to fetch weather for")))?;
+        return formatter.format(&weather_data);
+    }
+
+    let weather = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| match result {
+            Err(e) if index == shown => Err(e),
+            result => Ok(result.ok()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = weather
+        .get(shown)
+        .cloned()
+        .flatten()
+        .with_context(|| format!("No location numbered {}", shown + 1))?;
+    let output = formatter.format_with_summary(&data, &summarise(locations, weather, shown))?;
     Ok(output)
 }
 
+/// How often daemon mode refetches when not told otherwise
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::minutes(30);
+
+/// How soon daemon mode retries after a failed refresh
+const RETRY_INTERVAL: Duration = Duration::minutes(1);
+
+/// Long-running driver that refetches on its own schedule.
+///
+/// Between fetches the last result is rolled forward and re-rendered, so hours
+/// drop off the forecast as they pass without a network request. When a refresh
+/// fails, the previous data keeps being shown, marked as stale. With more than one
+/// location, every location is refetched together and summarised alongside the
+/// shown one.
+pub struct Daemon<F, Fmt> {
+    fetcher: F,
+    formatter: Fmt,
+    /// Locations fetched, the primary one first
+    locations: Vec<LocationQuery>,
+    refresh_interval: Duration,
+    /// Last successfully fetched data for each location and when it was fetched
+    latest: Vec<Option<(OffsetDateTime, WeatherData)>>,
+    /// Index of the location the output is about
+    shown: usize,
+    next_fetch: Option<OffsetDateTime>,
+}
+
+impl<F: WeatherFetcher + Sync, Fmt: WeatherFormatter> Daemon<F, Fmt> {
+    /// Create a daemon that fetches weather for `location` every `refresh_interval`
+    pub fn new(
+        fetcher: F,
+        formatter: Fmt,
+        location: LocationQuery,
+        refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            fetcher,
+            formatter,
+            locations: vec![location],
+            refresh_interval,
+            latest: vec![None],
+            shown: 0,
+            next_fetch: None,
+        }
+    }
+
+    /// Also fetch these locations, summarising them alongside the primary one
+    pub fn with_more_locations(mut self, locations: Vec<LocationQuery>) -> Self {
+        self.latest.extend(locations.iter().map(|_| None));
+        self.locations.extend(locations);
+        self
+    }
+
+    /// Make the next tick refetch regardless of the schedule
+    pub fn refresh_now(&mut self) {
+        self.next_fetch = None;
+    }
+
+    /// Make the output about the next location that has data to show, wrapping around
+    /// to the primary one
+    pub fn show_next_location(&mut self) {
+        let count = self.locations.len();
+        if let Some(next) = (1..=count)
+            .map(|step| (self.shown + step) % count)
+            .find(|&index| self.latest[index].is_some())
+        {
+            self.shown = next;
+        }
+    }
+
+    /// Access the formatter, e.g. to change what it displays between ticks
+    pub fn formatter_mut(&mut self) -> &mut Fmt {
+        &mut self.formatter
+    }
+
+    /// Render output for `now`, refetching first if a refresh is due.
+    ///
+    /// Returns `None` when there is nothing to show: no data has been fetched yet for
+    /// the shown location and the last failure has already been reported.
+    pub fn tick(&mut self, now: OffsetDateTime) -> Option<Result<Fmt::Output, anyhow::Error>> {
+        if self.next_fetch.is_none_or(|next_fetch| now >= next_fetch) {
+            let mut all_fetched = true;
+            let mut shown_error = None;
+            let results = fetch_all(&self.fetcher, &self.locations);
+            for (index, result) in results.into_iter().enumerate() {
+                match (result, &mut self.latest[index]) {
+                    (Ok(data), latest) => *latest = Some((now, data)),
+                    (Err(_), Some((fetched_at, data))) => {
+                        all_fetched = false;
+                        data.stale_since.get_or_insert(*fetched_at);
+                    }
+                    (Err(e), None) => {
+                        all_fetched = false;
+                        if index == self.shown {
+                            shown_error = Some(e);
+                        }
+                    }
+                }
+            }
+            self.next_fetch = Some(if all_fetched {
+                now + self.refresh_interval
+            } else {
+                now + RETRY_INTERVAL
+            });
+            if let Some(e) = shown_error {
+                return Some(Err(e));
+            }
+        }
+
+        let (_, data) = self.latest[self.shown].as_ref()?;
+        let data = data.clone().roll_forward(now);
+        if self.locations.len() == 1 {
+            return Some(self.formatter.format(&data));
+        }
+        let weather = self.latest.iter().map(|latest| {
+            latest
+                .as_ref()
+                .map(|(_, data)| data.clone().roll_forward(now))
+        });
+        let summary = summarise(&self.locations, weather, self.shown);
+        Some(self.formatter.format_with_summary(&data, &summary))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{
-        CurrentWeather, Humidity, LastUpdated, Location, Pressure, Temperature, WeatherCondition,
-        WeatherData, WeatherDay, WindDirection, WindSpeed,
-    };
     use crate::infra::display::WaybarFormatter;
+    use crate::test_support::weather_data;
+    use anyhow::Context;
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Mutex;
+    use time::macros::datetime;
+
+    fn wellington() -> LocationQuery {
+        LocationQuery::Name("Wellington".to_string())
+    }
 
     struct StubWeatherFetcher {
         data: Result<WeatherData, anyhow::Error>,
     }
 
+    /// Fetcher that always fails, for exercising fallback chains
+    struct FailingFetcher {
+        name: &'static str,
+        reason: &'static str,
+    }
+
+    impl WeatherFetcher for FailingFetcher {
+        fn fetch_weather(&self, _location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+            Err(anyhow::anyhow!(self.reason).context("Failed to send request"))
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    /// Fetcher counting its calls, failing while `fail` is set
+    struct FlakyFetcher {
+        calls: AtomicU32,
+        fail: AtomicBool,
+    }
+
+    impl FlakyFetcher {
+        fn new(fail: bool) -> Self {
+            Self {
+                calls: AtomicU32::new(0),
+                fail: AtomicBool::new(fail),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        fn set_fail(&self, fail: bool) {
+            self.fail.store(fail, Ordering::SeqCst);
+        }
+    }
+
+    impl WeatherFetcher for FlakyFetcher {
+        fn fetch_weather(&self, _location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                anyhow::bail!("connection refused");
+            }
+            Ok(weather_data("Wellington"))
+        }
+
+        fn name(&self) -> &str {
+            "Flaky"
+        }
+    }
+
+    /// Fetcher reporting weather for any place by its query, except the places in `down`
+    #[derive(Default)]
+    struct CityFetcher {
+        down: Mutex<Vec<String>>,
+    }
+
+    impl CityFetcher {
+        fn with_down(places: &[&str]) -> Self {
+            Self {
+                down: Mutex::new(places.iter().map(|place| place.to_string()).collect()),
+            }
+        }
+    }
+
+    impl WeatherFetcher for CityFetcher {
+        fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+            let name = location.to_string();
+            if self.down.lock().unwrap().contains(&name) {
+                anyhow::bail!("no weather for {}", name);
+            }
+            let mut data = weather_data("Wellington");
+            data.location = data.location.with_name(name);
+            Ok(data)
+        }
+
+        fn name(&self) -> &str {
+            "Cities"
+        }
+    }
+
+    fn cities() -> Vec<LocationQuery> {
+        ["Wellington", "Auckland", "Christchurch"]
+            .map(|name| LocationQuery::Name(name.to_string()))
+            .to_vec()
+    }
+
     impl WeatherFetcher for StubWeatherFetcher {
-        fn fetch_weather(&self, _location: &str) -> Result<WeatherData, anyhow::Error> {
+        fn fetch_weather(&self, _location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
             match &self.data {
-                Ok(_) => {
-                    // Rebuild since WeatherData is not Clone
-                    Ok(create_stub_weather_data())
-                }
+                Ok(data) => Ok(data.clone()),
                 Err(e) => Err(anyhow::anyhow!("{}", e)),
             }
         }
-    }
 
-    fn create_stub_weather_data() -> WeatherData {
-        WeatherData {
-            current: CurrentWeather {
-                last_updated: LastUpdated::from_epoch(1673620200).unwrap(),
-                temperature: Temperature::new(18).unwrap(),
-                feels_like: Temperature::new(16).unwrap(),
-                condition: WeatherCondition::new("Partly cloudy".to_string()),
-                humidity: Humidity::new(72.0).unwrap(),
-                wind_speed: WindSpeed::new(25).unwrap(),
-                wind_direction: WindDirection::from_compass("SW").unwrap(),
-                pressure: Pressure::new(1010).unwrap(),
-            },
-            location: Location::new("Wellington".to_string()),
-            weather_day: Some(WeatherDay {
-                astronomy: None,
-                hourly_weather: vec![],
-            }),
+        fn name(&self) -> &str {
+            "Stub"
         }
     }
 
     #[test]
     fn test_fetch_and_format_success() {
         let fetcher = StubWeatherFetcher {
-            data: Ok(create_stub_weather_data()),
+            data: Ok(weather_data("Wellington")),
         };
         let formatter = WaybarFormatter::new();
 
-        let output = fetch_and_format(&fetcher, &formatter, "Wellington").unwrap();
+        let output = fetch_and_format(&fetcher, &formatter, &[wellington()], 0).unwrap();
 
         assert!(output.text.contains("18°C"));
         assert!(output.text.contains("Wellington"));
@@ -103,7 +536,7 @@ mod tests {
         };
         let formatter = WaybarFormatter::new();
 
-        let result = fetch_and_format(&fetcher, &formatter, "Wellington");
+        let result = fetch_and_format(&fetcher, &formatter, &[wellington()], 0);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("connection refused"));
     }
@@ -111,14 +544,272 @@ mod tests {
     #[test]
     fn test_fetch_and_format_with_wind() {
         let fetcher = StubWeatherFetcher {
-            data: Ok(create_stub_weather_data()),
+            data: Ok(weather_data("Wellington")),
         };
         let formatter = WaybarFormatter::new();
 
-        let output = fetch_and_format(&fetcher, &formatter, "Wellington").unwrap();
+        let output = fetch_and_format(&fetcher, &formatter, &[wellington()], 0).unwrap();
 
         // Wind 25 km/h is ModerateBreezes (green)
         assert!(output.tooltip.contains("<span foreground=\"#00AA00\">25</span> km/h"));
         assert!(output.tooltip.contains("SW"));
     }
+
+    #[test]
+    fn test_fetch_all_keeps_order() {
+        let fetcher = CityFetcher::with_down(&["Auckland"]);
+
+        let results = fetch_all(&fetcher, &cities());
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().location.name(), "Wellington");
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().location.name(), "Christchurch");
+    }
+
+    #[test]
+    fn test_fetch_and_format_summarises_locations() {
+        let fetcher = CityFetcher::with_down(&["Christchurch"]);
+        let formatter = WaybarFormatter::new();
+
+        let output = fetch_and_format(&fetcher, &formatter, &cities(), 1).unwrap();
+        assert!(output.text.contains("Auckland"));
+        assert!(output.tooltip.contains("Location: Auckland"));
+        assert!(output
+            .tooltip
+            .contains("Locations:\n• ⛅ Wellington: 18°C, Partly cloudy"));
+        assert!(output
+            .tooltip
+            .contains("• <b>⛅ Auckland: 18°C, Partly cloudy</b>"));
+        assert!(output.tooltip.contains("• Christchurch: unavailable"));
+
+        // Only the shown location has to be fetched
+        let error = fetch_and_format(&fetcher, &formatter, &cities(), 2).unwrap_err();
+        assert_eq!(error.to_string(), "no weather for Christchurch");
+    }
+
+    #[test]
+    fn test_fallback_uses_first_successful_fetcher() {
+        let chain = FallbackFetcher::new(vec![
+            Box::new(FailingFetcher {
+                name: "Primary",
+                reason: "connection refused",
+            }),
+            Box::new(StubWeatherFetcher {
+                data: Ok(weather_data("Wellington")),
+            }),
+        ]);
+
+        assert_eq!(chain.name(), "Primary → Stub");
+        let data = chain.fetch_weather(&wellington()).unwrap();
+        assert_eq!(data.source, "Stub");
+    }
+
+    #[test]
+    fn test_fallback_lists_every_failure() {
+        let chain = FallbackFetcher::new(vec![
+            Box::new(FailingFetcher {
+                name: "Primary",
+                reason: "connection refused",
+            }),
+            Box::new(FailingFetcher {
+                name: "Secondary",
+                reason: "HTTP 503",
+            }),
+        ]);
+
+        let error = chain.fetch_weather(&wellington()).unwrap_err().to_string();
+        assert!(error.starts_with("All weather providers failed"));
+        assert!(error.contains("• Primary: Failed to send request: connection refused"));
+        assert!(error.contains("• Secondary: Failed to send request: HTTP 503"));
+    }
+
+    #[test]
+    fn test_fallback_single_fetcher_error_unchanged() {
+        let chain = FallbackFetcher::new(vec![Box::new(FailingFetcher {
+            name: "Primary",
+            reason: "connection refused",
+        })]);
+
+        let error = chain.fetch_weather(&wellington()).unwrap_err();
+        assert_eq!(error.to_string(), "Failed to send request");
+    }
+
+    #[test]
+    fn test_fallback_without_fetchers() {
+        let chain = FallbackFetcher::new(vec![]);
+        assert!(chain.fetch_weather(&wellington()).is_err());
+    }
+
+    #[test]
+    fn test_labelled_fetcher_renames_location() {
+        let stub = || StubWeatherFetcher {
+            data: Ok(weather_data("Wellington")),
+        };
+
+        let labelled = LabelledFetcher::new(stub(), Some("Home".to_string()));
+        assert_eq!(labelled.name(), "Stub");
+        let data = labelled.fetch_weather(&wellington()).unwrap();
+        assert_eq!(data.location.name(), "Home");
+
+        let unlabelled = LabelledFetcher::new(stub(), None);
+        let data = unlabelled.fetch_weather(&wellington()).unwrap();
+        assert_eq!(data.location.name(), "Wellington");
+    }
+
+    /// Location source reporting a fixed position, or failing without one
+    struct StubSource(Option<(f64, f64)>);
+
+    impl LocationSource for StubSource {
+        fn locate(&self) -> Result<Coordinates, anyhow::Error> {
+            let (latitude, longitude) = self.0.context("location service unavailable")?;
+            Ok(Coordinates::new(latitude, longitude)?)
+        }
+    }
+
+    /// Fetcher recording the queries it is asked for
+    #[derive(Default)]
+    struct QueryRecorder {
+        queries: RefCell<Vec<String>>,
+    }
+
+    impl WeatherFetcher for QueryRecorder {
+        fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData, anyhow::Error> {
+            self.queries.borrow_mut().push(location.to_string());
+            Ok(weather_data("Wellington"))
+        }
+
+        fn name(&self) -> &str {
+            "Recorder"
+        }
+    }
+
+    #[test]
+    fn test_locating_fetcher_resolves_current_location() {
+        let fetcher = LocatingFetcher::new(
+            QueryRecorder::default(),
+            StubSource(Some((-41.28664, 174.77557))),
+        );
+
+        fetcher.fetch_weather(&LocationQuery::Current).unwrap();
+        fetcher.fetch_weather(&wellington()).unwrap();
+        assert_eq!(
+            *fetcher.inner.queries.borrow(),
+            vec!["-41.29,174.78", "Wellington"]
+        );
+        assert_eq!(fetcher.name(), "Recorder");
+    }
+
+    #[test]
+    fn test_locating_fetcher_fails_without_position() {
+        let fetcher = LocatingFetcher::new(QueryRecorder::default(), StubSource(None));
+
+        let error = fetcher.fetch_weather(&LocationQuery::Current).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed to find the current location: location service unavailable"
+        );
+        assert!(fetcher.inner.queries.borrow().is_empty());
+        // An explicit location needs no position
+        assert!(fetcher.fetch_weather(&wellington()).is_ok());
+    }
+
+    const NOW: OffsetDateTime = datetime!(2023-01-13 01:30 UTC);
+
+    fn daemon(fetcher: FlakyFetcher) -> Daemon<FlakyFetcher, WaybarFormatter> {
+        Daemon::new(
+            fetcher,
+            WaybarFormatter::new(),
+            wellington(),
+            DEFAULT_REFRESH_INTERVAL,
+        )
+    }
+
+    #[test]
+    fn test_daemon_rerenders_between_refreshes() {
+        let mut daemon = daemon(FlakyFetcher::new(false));
+
+        for minute in 0..30 {
+            let output = daemon.tick(NOW + Duration::minutes(minute));
+            assert!(output.unwrap().unwrap().text.contains("18°C"));
+        }
+        assert_eq!(daemon.fetcher.calls(), 1);
+
+        daemon.tick(NOW + Duration::minutes(30)).unwrap().unwrap();
+        assert_eq!(daemon.fetcher.calls(), 2);
+    }
+
+    #[test]
+    fn test_daemon_shows_stale_data_when_refresh_fails() {
+        let mut daemon = daemon(FlakyFetcher::new(false));
+        daemon.tick(NOW).unwrap().unwrap();
+
+        daemon.fetcher.set_fail(true);
+        let output = daemon.tick(NOW + Duration::minutes(30)).unwrap().unwrap();
+        assert!(output.text.contains("18°C"));
+        assert!(output.tooltip.contains("Stale since"));
+
+        // Failed refreshes are retried a minute later rather than a full interval
+        daemon.tick(NOW + Duration::minutes(31)).unwrap().unwrap();
+        assert_eq!(daemon.fetcher.calls(), 3);
+
+        daemon.fetcher.set_fail(false);
+        let output = daemon.tick(NOW + Duration::minutes(32)).unwrap().unwrap();
+        assert!(!output.tooltip.contains("Stale since"));
+    }
+
+    #[test]
+    fn test_daemon_refresh_now_refetches_early() {
+        let mut daemon = daemon(FlakyFetcher::new(false));
+        daemon.tick(NOW).unwrap().unwrap();
+
+        daemon.refresh_now();
+        daemon.tick(NOW + Duration::minutes(2)).unwrap().unwrap();
+        assert_eq!(daemon.fetcher.calls(), 2);
+
+        // The schedule restarts from the forced refresh
+        daemon.tick(NOW + Duration::minutes(31)).unwrap().unwrap();
+        assert_eq!(daemon.fetcher.calls(), 2);
+    }
+
+    #[test]
+    fn test_daemon_rotates_through_locations() {
+        let [primary, more @ ..] = <[LocationQuery; 3]>::try_from(cities()).unwrap();
+        let mut daemon = Daemon::new(
+            CityFetcher::with_down(&["Auckland"]),
+            WaybarFormatter::new(),
+            primary,
+            DEFAULT_REFRESH_INTERVAL,
+        )
+        .with_more_locations(more.to_vec());
+
+        let output = daemon.tick(NOW).unwrap().unwrap();
+        assert!(output.text.contains("Wellington"));
+        assert!(output.tooltip.contains("• Auckland: unavailable"));
+
+        // Places without weather are skipped, and the rotation wraps around
+        daemon.show_next_location();
+        let output = daemon.tick(NOW + Duration::minutes(1)).unwrap().unwrap();
+        assert!(output.text.contains("Christchurch"));
+        assert!(output.tooltip.contains("<b>⛅ Christchurch"));
+        daemon.show_next_location();
+        let output = daemon.tick(NOW + Duration::minutes(2)).unwrap().unwrap();
+        assert!(output.text.contains("Wellington"));
+
+        // Once it recovers, the next refresh brings it into the rotation
+        daemon.fetcher.down.lock().unwrap().clear();
+        daemon.tick(NOW + Duration::minutes(3)).unwrap().unwrap();
+        daemon.show_next_location();
+        let output = daemon.tick(NOW + Duration::minutes(4)).unwrap().unwrap();
+        assert!(output.text.contains("Auckland"));
+    }
+
+    #[test]
+    fn test_daemon_reports_error_before_first_fetch() {
+        let mut daemon = daemon(FlakyFetcher::new(true));
+
+        let error = daemon.tick(NOW).unwrap().unwrap_err();
+        assert!(error.to_string().contains("connection refused"));
+        assert!(daemon.tick(NOW + Duration::seconds(30)).is_none());
+        assert!(daemon.tick(NOW + Duration::minutes(1)).unwrap().is_err());
+    }
 }
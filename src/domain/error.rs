@@ -25,6 +25,15 @@ pub enum WeatherError {
     InvalidTime(String),
     /// A timestamp could not be parsed or converted.
     InvalidTimestamp(String),
+    /// Category thresholds were not strictly increasing.
+    InvalidThresholds(String),
+    /// A location could not be understood.
+    InvalidLocation(String),
+    /// A unit or unit system name was not recognised.
+    UnknownUnit {
+        value: String,
+        expected: &'static str,
+    },
 }
 
 impl fmt::Display for WeatherError {
@@ -49,6 +58,15 @@ impl fmt::Display for WeatherError {
             Self::InvalidDirection(dir) => write!(f, "Invalid compass direction: {}", dir),
             Self::InvalidTime(time) => write!(f, "Unable to parse time: {}", time),
             Self::InvalidTimestamp(ts) => write!(f, "Invalid timestamp: {}", ts),
+            Self::InvalidThresholds(thresholds) => write!(
+                f,
+                "Thresholds must be positive and strictly increasing: {}",
+                thresholds
+            ),
+            Self::InvalidLocation(reason) => write!(f, "Invalid location: {}", reason),
+            Self::UnknownUnit { value, expected } => {
+                write!(f, "Unknown unit '{}'. Expected one of: {}", value, expected)
+            }
         }
     }
 }
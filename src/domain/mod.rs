@@ -3,9 +3,11 @@
 pub mod error;
 pub mod models;
 pub mod types;
+pub mod units;
 
 pub use models::*;
 pub use types::*;
+pub use units::*;
 
 #[cfg(test)]
 mod tests {
@@ -13,27 +15,27 @@ mod tests {
 
     #[test]
     fn test_temperature_creation_and_conversion() {
-        let temp = Temperature::new(25).expect("Valid temperature");
+        let temp = Temperature::new(25.0).expect("Valid temperature");
         assert_eq!(temp.as_celsius(), 25);
         assert_eq!(temp.to_string(), "25°C");
 
-        let temp_cold = Temperature::new(-10).expect("Valid temperature");
+        let temp_cold = Temperature::new(-10.0).expect("Valid temperature");
         assert_eq!(temp_cold.as_celsius(), -10);
     }
 
     #[test]
     fn test_temperature_validation() {
         // Should reject extreme temperatures
-        assert!(Temperature::new(-500).is_err());
-        assert!(Temperature::new(200).is_err());
+        assert!(Temperature::new(-500.0).is_err());
+        assert!(Temperature::new(200.0).is_err());
 
         // Should accept reasonable weather temperatures
-        assert!(Temperature::new(-40).is_ok());
-        assert!(Temperature::new(55).is_ok());
+        assert!(Temperature::new(-40.0).is_ok());
+        assert!(Temperature::new(55.0).is_ok());
 
         // Should reject temperatures outside realistic range
-        assert!(Temperature::new(-41).is_err());
-        assert!(Temperature::new(56).is_err());
+        assert!(Temperature::new(-41.0).is_err());
+        assert!(Temperature::new(56.0).is_err());
     }
 
     #[test]
@@ -47,6 +49,64 @@ mod tests {
         assert!(Humidity::new(101.0).is_err());
     }
 
+    #[test]
+    fn test_precipitation_and_probability_validation() {
+        let amount = Precipitation::new(1.4).expect("Valid precipitation");
+        assert_eq!(amount.to_string(), "1.4 mm");
+        assert!(amount.is_measurable());
+        assert!(!Precipitation::new(0.0).unwrap().is_measurable());
+        assert!(Precipitation::new(-0.1).is_err());
+        assert!(Precipitation::new(501.0).is_err());
+
+        let chance = Probability::new(40).expect("Valid probability");
+        assert_eq!(chance.to_string(), "40%");
+        assert!(Probability::new(100).is_ok());
+        assert!(Probability::new(101).is_err());
+    }
+
+    #[test]
+    fn test_uv_index_categories() {
+        let category = |index: f32| UvIndex::new(index).unwrap().category();
+        assert_eq!(category(0.0), UvCategory::Low);
+        assert_eq!(category(2.4), UvCategory::Low);
+        assert_eq!(category(2.5), UvCategory::Moderate);
+        assert_eq!(category(6.0), UvCategory::High);
+        assert_eq!(category(10.0), UvCategory::VeryHigh);
+        assert_eq!(category(11.0), UvCategory::Extreme);
+        assert_eq!(UvIndex::new(8.2).unwrap().to_string(), "8 (Very High)");
+        assert!(UvIndex::new(-1.0).is_err());
+        assert!(UvIndex::new(21.0).is_err());
+    }
+
+    #[test]
+    fn test_visibility_and_cloud_cover_validation() {
+        assert_eq!(Visibility::new(2.5).unwrap().to_string(), "2.5 km");
+        assert!(Visibility::new(-0.1).is_err());
+        assert_eq!(CloudCover::new(100).unwrap().to_string(), "100%");
+        assert!(CloudCover::new(101).is_err());
+    }
+
+    #[test]
+    fn test_air_quality_indices() {
+        let epa = |index| UsEpaIndex::new(index).unwrap().category();
+        assert_eq!(epa(1), AirQualityCategory::Good);
+        assert_eq!(epa(3), AirQualityCategory::UnhealthyForSensitiveGroups);
+        assert_eq!(epa(6), AirQualityCategory::Hazardous);
+        assert!(epa(3) > AirQualityCategory::Moderate);
+        assert!(UsEpaIndex::new(0).is_err());
+        assert!(UsEpaIndex::new(7).is_err());
+
+        let defra = |index| DefraIndex::new(index).unwrap().band();
+        assert_eq!(defra(3), DefraBand::Low);
+        assert_eq!(defra(4), DefraBand::Moderate);
+        assert_eq!(defra(9), DefraBand::High);
+        assert_eq!(DefraIndex::new(10).unwrap().to_string(), "10 (Very High)");
+        assert!(DefraIndex::new(11).is_err());
+
+        assert_eq!(Concentration::new(8.64).unwrap().to_string(), "8.6 µg/m³");
+        assert!(Concentration::new(-1.0).is_err());
+    }
+
     #[test]
     fn test_wind_speed_creation() {
         let wind = WindSpeed::new(15).expect("Valid wind speed");
@@ -144,6 +204,96 @@ mod tests {
 
         let empty_location = Location::new("".to_string());
         assert_eq!(empty_location.to_string(), "Unknown");
+
+        let location = Location::new("Richmond".to_string())
+            .with_region(Some("Virginia".to_string()))
+            .with_country(Some("".to_string()))
+            .with_coordinates(Coordinates::new(37.5538, -77.4603).ok());
+        assert_eq!(location.name(), "Richmond");
+        assert_eq!(location.region(), Some("Virginia"));
+        assert_eq!(location.country(), None);
+        assert_eq!(
+            location.coordinates().map(|c| c.longitude()),
+            Some(-77.4603)
+        );
+        assert_eq!(location.full_name(), "Richmond, Virginia");
+
+        let location = location
+            .with_name("Home".to_string())
+            .with_name(" ".to_string());
+        assert_eq!(location.full_name(), "Home, Virginia");
+        assert!(location.coordinates().is_some());
+
+        // A region repeating the name adds nothing
+        let location = Location::new("Wellington".to_string())
+            .with_region(Some("Wellington".to_string()))
+            .with_country(Some("New Zealand".to_string()));
+        assert_eq!(location.distinct_region(), None);
+        assert_eq!(location.full_name(), "Wellington, New Zealand");
+    }
+
+    #[test]
+    fn test_location_query_parsing() {
+        let parse = |input: &str| LocationQuery::parse(input).expect("Valid location");
+
+        assert_eq!(
+            parse(" -41.2866, 174.7756 "),
+            LocationQuery::Coordinates(Coordinates::new(-41.2866, 174.7756).unwrap())
+        );
+        assert_eq!(parse("90,-180").to_string(), "90,-180");
+        assert!(Coordinates::new(90.5, 0.0).is_err());
+        assert!(Coordinates::new(0.0, 180.5).is_err());
+        assert!(Coordinates::new(f64::NAN, 0.0).is_err());
+        assert!(Coordinates::new(0.0, f64::INFINITY).is_err());
+        let rounded = Coordinates::new(-41.28664, 174.77557).unwrap().rounded(2);
+        assert_eq!(rounded.to_string(), "-41.29,174.78");
+        assert_eq!(LocationQuery::Current.to_string(), "current location");
+        assert!(LocationQuery::parse("-95,174").is_err());
+        assert!(LocationQuery::parse("nan,0").is_err());
+        assert!(LocationQuery::parse("0,NaN").is_err());
+
+        // Postcodes are normalised to the form providers expect
+        assert_eq!(parse("90210"), LocationQuery::Postcode("90210".to_string()));
+        assert_eq!(parse("20500-0003").to_string(), "20500-0003");
+        assert_eq!(parse("sw1a1aa").to_string(), "SW1A 1AA");
+        assert_eq!(parse("M1 1AE").to_string(), "M1 1AE");
+        assert_eq!(parse("k1a0b1").to_string(), "K1A 0B1");
+        assert_eq!(parse("G2J").to_string(), "G2J");
+
+        assert_eq!(parse("IATA:wlg"), LocationQuery::Airport("WLG".to_string()));
+        assert_eq!(parse("iata:WLG").to_string(), "iata:WLG");
+        assert!(LocationQuery::parse("iata:wellington").is_err());
+        assert_eq!(parse("id:2801268"), LocationQuery::Id(2801268));
+        assert!(LocationQuery::parse("id:home").is_err());
+
+        // Anything else, including text with a comma, is a name for the provider
+        assert_eq!(
+            parse(" Wellington "),
+            LocationQuery::Name("Wellington".to_string())
+        );
+        assert_eq!(
+            parse("Paris, France"),
+            LocationQuery::Name("Paris, France".to_string())
+        );
+        assert_eq!(parse("Richmond VA").to_string(), "Richmond VA");
+        assert!(LocationQuery::parse("  ").is_err());
+    }
+
+    #[test]
+    fn test_place_match_query() {
+        let place = PlaceMatch {
+            location: Location::new("Richmond".to_string())
+                .with_coordinates(Coordinates::new(37.55, -77.46).ok()),
+            id: Some(2588521),
+        };
+        // Coordinates find the place again with any provider; the id only with one
+        assert_eq!(place.query().to_string(), "37.55,-77.46");
+
+        let place = PlaceMatch {
+            location: Location::new("Richmond".to_string()),
+            id: None,
+        };
+        assert_eq!(place.query(), LocationQuery::Name("Richmond".to_string()));
     }
 
     #[test]
@@ -181,13 +331,28 @@ mod tests {
 
     #[test]
     fn test_dew_point_calculation() {
-        let temp = Temperature::new(20).unwrap();
+        let temp = Temperature::new(20.0).unwrap();
         let humidity = Humidity::new(60.0).unwrap();
 
         let dew_point = humidity.dew_point(&temp);
         assert_eq!(dew_point.as_celsius(), 12);
     }
 
+    #[test]
+    fn test_apparent_temperature() {
+        let temp = Temperature::new(20.0).unwrap();
+
+        // Humid and still feels warmer than the air temperature
+        let humid = Humidity::new(90.0).unwrap();
+        let calm = WindSpeed::new(0).unwrap();
+        assert_eq!(temp.apparent(&humid, &calm).as_celsius(), 23);
+
+        // Dry and windy feels colder
+        let dry = Humidity::new(40.0).unwrap();
+        let windy = WindSpeed::new(36).unwrap();
+        assert_eq!(temp.apparent(&dry, &windy).as_celsius(), 12);
+    }
+
     #[test]
     fn test_day_length_calculation() {
         let sunrise = WeatherTime::parse("06:30 AM").unwrap();
@@ -230,6 +395,24 @@ mod tests {
         assert_eq!(solar_noon.minute(), 30);
     }
 
+    #[test]
+    fn test_astronomy_next_event() {
+        let astronomy = Astronomy::new(
+            WeatherTime::parse("06:00").unwrap(),
+            WeatherTime::parse("19:00").unwrap(),
+        );
+        let next = |now: &str| {
+            let (event, remaining) = astronomy.next_event(WeatherTime::parse(now).unwrap());
+            (event, remaining.hours(), remaining.minutes())
+        };
+
+        assert_eq!(next("04:30"), (SunEvent::Sunrise, 1, 30));
+        assert_eq!(next("06:00"), (SunEvent::Sunset, 13, 0));
+        assert_eq!(next("18:15"), (SunEvent::Sunset, 0, 45));
+        // After sunset the countdown runs to the next morning
+        assert_eq!(next("22:00"), (SunEvent::Sunrise, 8, 0));
+    }
+
     #[test]
     fn test_last_updated_from_epoch() {
         let last_updated = LastUpdated::from_epoch(1673620200).expect("Valid timestamp");
@@ -239,8 +422,18 @@ mod tests {
 
     #[test]
     fn test_last_updated_from_api_format() {
-        let last_updated = LastUpdated::from_api_format("2023-01-13 14:30").expect("Valid format");
+        use time::macros::{datetime, offset};
+        use time::UtcOffset;
+
+        let last_updated =
+            LastUpdated::from_api_format("2023-01-13 14:30", UtcOffset::UTC).expect("Valid format");
         assert_eq!(last_updated.format_display(), "2023-01-13 14:30Z");
+
+        // WeatherAPI.com gives the location's wall-clock time
+        let local =
+            LastUpdated::from_api_format("2023-01-14 03:30", offset!(+13)).expect("Valid format");
+        assert_eq!(local.format_display(), "2023-01-13 14:30Z");
+        assert_eq!(local.instant(), datetime!(2023-01-14 03:30 +13));
     }
 
     #[test]
@@ -251,24 +444,53 @@ mod tests {
 
     #[test]
     fn test_last_updated_invalid_api_format() {
+        use time::UtcOffset;
+
         // Test with various invalid formats
-        assert!(LastUpdated::from_api_format("invalid").is_err());
-        assert!(LastUpdated::from_api_format("2023-13-01 14:30").is_err()); // Invalid month
-        assert!(LastUpdated::from_api_format("2023-01-32 14:30").is_err()); // Invalid day
-        assert!(LastUpdated::from_api_format("2023-01-13 25:30").is_err()); // Invalid hour
-        assert!(LastUpdated::from_api_format("2023-01-13 14:60").is_err()); // Invalid minute
+        let parse = |s| LastUpdated::from_api_format(s, UtcOffset::UTC);
+        assert!(parse("invalid").is_err());
+        assert!(parse("2023-13-01 14:30").is_err()); // Invalid month
+        assert!(parse("2023-01-32 14:30").is_err()); // Invalid day
+        assert!(parse("2023-01-13 25:30").is_err()); // Invalid hour
+        assert!(parse("2023-01-13 14:60").is_err()); // Invalid minute
     }
 
     #[test]
     fn test_last_updated_consistency() {
+        use time::macros::offset;
+
         // Test that epoch and API format produce same result for same timestamp
         let epoch_version = LastUpdated::from_epoch(1673620200).expect("Valid timestamp");
-        let api_version = LastUpdated::from_api_format("2023-01-13 14:30").expect("Valid format");
+        let api_version =
+            LastUpdated::from_api_format("2023-01-14 03:30", offset!(+13)).expect("Valid format");
 
         assert_eq!(epoch_version.format_display(), api_version.format_display());
         assert_eq!(epoch_version.to_string(), api_version.to_string());
     }
 
+    #[test]
+    fn test_zoned_date_time() {
+        use time::macros::{datetime, offset};
+        use time::UtcOffset;
+
+        let instant = datetime!(2023-01-13 14:30 UTC);
+
+        let auckland = ZonedDateTime::in_named_zone(instant, "Pacific/Auckland").unwrap();
+        assert_eq!(auckland.datetime(), datetime!(2023-01-14 03:30 +13));
+        assert_eq!(auckland.zone_label(), "NZDT");
+        // Daylight saving time has ended by July
+        let winter =
+            ZonedDateTime::in_named_zone(datetime!(2023-07-01 0:00 UTC), "Pacific/Auckland");
+        assert_eq!(winter.unwrap().zone_label(), "NZST");
+        assert!(ZonedDateTime::in_named_zone(instant, "Mars/Olympus_Mons").is_none());
+
+        let fixed = |offset| ZonedDateTime::at_offset(instant.to_offset(offset)).zone_label();
+        assert_eq!(fixed(UtcOffset::UTC), "UTC");
+        assert_eq!(fixed(offset!(-7)), "UTC-7");
+        assert_eq!(fixed(offset!(+5:30)), "UTC+5:30");
+        assert_eq!(fixed(offset!(-3:30)), "UTC-3:30");
+    }
+
     #[test]
     fn test_wind_direction_creation_and_validation() {
         // Test valid compass directions
@@ -341,6 +563,19 @@ mod tests {
         assert!(WindDirection::from_compass("X").is_err());
     }
 
+    #[test]
+    fn test_wind_direction_from_degrees() {
+        assert_eq!(WindDirection::from_degrees(0.0), WindDirection::N);
+        assert_eq!(WindDirection::from_degrees(11.0), WindDirection::N);
+        assert_eq!(WindDirection::from_degrees(12.0), WindDirection::NNE);
+        assert_eq!(WindDirection::from_degrees(90.0), WindDirection::E);
+        assert_eq!(WindDirection::from_degrees(225.0), WindDirection::SW);
+        assert_eq!(WindDirection::from_degrees(315.0), WindDirection::NW);
+        assert_eq!(WindDirection::from_degrees(355.0), WindDirection::N);
+        assert_eq!(WindDirection::from_degrees(360.0), WindDirection::N);
+        assert_eq!(WindDirection::from_degrees(-90.0), WindDirection::W);
+    }
+
     #[test]
     fn test_wind_direction_edge_cases() {
         // Test edge cases for compass validation
@@ -352,8 +587,8 @@ mod tests {
     #[test]
     fn test_zero_cost_abstractions() {
         // Verify that our phantom type approach has zero runtime cost
-        let temp = Temperature::new(20).unwrap();
-        assert_eq!(std::mem::size_of_val(&temp), std::mem::size_of::<i32>());
+        let temp = Temperature::new(20.0).unwrap();
+        assert_eq!(std::mem::size_of_val(&temp), std::mem::size_of::<f64>());
 
         let humidity = Humidity::new(60.0).unwrap();
         assert_eq!(std::mem::size_of_val(&humidity), std::mem::size_of::<f32>());
@@ -440,6 +675,97 @@ mod tests {
         assert_eq!(WindSpeed::new(118).unwrap().category(), WindSpeedCategory::Hurricane);
     }
 
+    #[test]
+    fn test_wind_thresholds() {
+        let thresholds = WindThresholds::new(10, 30, 60, 100).unwrap();
+        assert_eq!(thresholds.categorize(9), WindSpeedCategory::Calm);
+        assert_eq!(
+            thresholds.categorize(10),
+            WindSpeedCategory::ModerateBreezes
+        );
+        assert_eq!(thresholds.categorize(30), WindSpeedCategory::Gales);
+        assert_eq!(thresholds.categorize(99), WindSpeedCategory::Storms);
+        assert_eq!(thresholds.categorize(100), WindSpeedCategory::Hurricane);
+
+        assert!(WindThresholds::new(0, 30, 60, 100).is_err());
+        assert!(WindThresholds::new(30, 30, 60, 100).is_err());
+        assert!(WindThresholds::new(10, 70, 60, 100).is_err());
+    }
+
+    #[test]
+    fn test_temperature_units() {
+        assert_eq!(TemperatureUnit::Celsius.convert_celsius(20.0), 20.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert_celsius(20.0), 68.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert_celsius(-40.0), -40.0);
+        assert!((TemperatureUnit::Kelvin.convert_celsius(0.0) - 273.15).abs() < 1e-9);
+        assert_eq!(
+            "F".parse::<TemperatureUnit>().unwrap(),
+            TemperatureUnit::Fahrenheit
+        );
+        assert!("rankine".parse::<TemperatureUnit>().is_err());
+    }
+
+    #[test]
+    fn test_speed_units() {
+        assert!((SpeedUnit::MilesPerHour.convert_kmh(100.0) - 62.137).abs() < 1e-3);
+        assert!((SpeedUnit::MetresPerSecond.convert_kmh(36.0) - 10.0).abs() < 1e-9);
+        assert!((SpeedUnit::Knots.convert_kmh(1.852) - 1.0).abs() < 1e-9);
+        assert!((SpeedUnit::MilesPerHour.to_kmh(10.0) - 16.09344).abs() < 1e-9);
+        assert_eq!(
+            "m/s".parse::<SpeedUnit>().unwrap(),
+            SpeedUnit::MetresPerSecond
+        );
+        assert!("furlongs".parse::<SpeedUnit>().is_err());
+    }
+
+    #[test]
+    fn test_beaufort_scale() {
+        assert_eq!(SpeedUnit::Beaufort.convert_kmh(0.0), 0.0);
+        assert_eq!(SpeedUnit::Beaufort.convert_kmh(19.0), 3.0);
+        assert_eq!(SpeedUnit::Beaufort.convert_kmh(20.0), 4.0);
+        assert_eq!(SpeedUnit::Beaufort.convert_kmh(117.0), 11.0);
+        assert_eq!(SpeedUnit::Beaufort.convert_kmh(300.0), 12.0);
+
+        assert_eq!(SpeedUnit::Beaufort.to_kmh(8.0), 62.0);
+        assert_eq!(SpeedUnit::Beaufort.to_kmh(20.0), 118.0);
+    }
+
+    #[test]
+    fn test_pressure_units() {
+        assert!((PressureUnit::InchesOfMercury.convert_hpa(1013.25) - 29.92).abs() < 0.01);
+        assert!((PressureUnit::MillimetresOfMercury.convert_hpa(1013.25) - 760.0).abs() < 0.1);
+        assert_eq!(PressureUnit::InchesOfMercury.precision(), 2);
+        assert_eq!(
+            "inHg".parse::<PressureUnit>().unwrap(),
+            PressureUnit::InchesOfMercury
+        );
+    }
+
+    #[test]
+    fn test_unit_systems() {
+        assert_eq!(UnitSystem::default(), UnitSystem::METRIC);
+        assert_eq!(
+            "imperial".parse::<UnitSystem>().unwrap(),
+            UnitSystem::IMPERIAL
+        );
+
+        let uk: UnitSystem = "UK".parse().unwrap();
+        assert_eq!(uk.temperature, TemperatureUnit::Celsius);
+        assert_eq!(uk.speed, SpeedUnit::MilesPerHour);
+        assert_eq!(uk.distance, DistanceUnit::Miles);
+        assert_eq!(
+            "km".parse::<DistanceUnit>().unwrap(),
+            DistanceUnit::Kilometres
+        );
+        assert!("leagues".parse::<DistanceUnit>().is_err());
+
+        let error = "nautical".parse::<UnitSystem>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown unit 'nautical'. Expected one of: metric, imperial, uk"
+        );
+    }
+
     #[test]
     fn test_wind_speed_gust_category() {
         // No gusts
@@ -453,4 +779,260 @@ mod tests {
         let with_gale_gusts = WindSpeed::with_gusts(25, Some(60)).unwrap();
         assert_eq!(with_gale_gusts.gust_category(), Some(WindSpeedCategory::Gales));
     }
+
+    #[test]
+    fn test_condition_family_and_daytime() {
+        let family = |description: &str| WeatherCondition::new(description.to_string()).family();
+        assert_eq!(family("Sunny"), ConditionFamily::Clear);
+        assert_eq!(family("Partly cloudy"), ConditionFamily::PartlyCloudy);
+        assert_eq!(family("Patchy light drizzle"), ConditionFamily::Rain);
+        assert_eq!(
+            family("Thundery outbreaks possible"),
+            ConditionFamily::Storm
+        );
+        assert_eq!(family("Freezing fog"), ConditionFamily::Fog);
+        assert_eq!(family("Haze"), ConditionFamily::Other);
+
+        let kind = |description: &str| ConditionKind::from_description(description);
+        assert_eq!(
+            kind("Patchy light rain with thunder"),
+            ConditionKind::Thunderstorm
+        );
+        assert_eq!(kind("Light rain shower"), ConditionKind::RainShowers);
+        assert_eq!(kind("Freezing fog"), ConditionKind::Fog);
+        assert_eq!(kind("Light freezing rain"), ConditionKind::FreezingRain);
+        assert_eq!(kind("Rain And Snow"), ConditionKind::Sleet);
+        assert_eq!(kind("Mostly Cloudy"), ConditionKind::Cloudy);
+        // A classification from the provider's code wins over the description
+        let coded = WeatherCondition::new("Mist".to_string()).with_kind(ConditionKind::Fog);
+        assert_eq!(coded.kind(), ConditionKind::Fog);
+        assert_eq!(coded.family(), ConditionFamily::Fog);
+
+        let astronomy = Astronomy::new(
+            WeatherTime::parse("06:30").unwrap(),
+            WeatherTime::parse("18:30").unwrap(),
+        );
+        let at = |time| WeatherTime::parse(time).unwrap();
+        assert!(!astronomy.is_daytime(at("06:29")));
+        assert!(astronomy.is_daytime(at("06:30")));
+        assert!(astronomy.is_daytime(at("18:29")));
+        assert!(!astronomy.is_daytime(at("18:30")));
+    }
+
+    #[test]
+    fn test_moon_phase() {
+        use time::macros::date;
+
+        let on = MoonPhase::on;
+        assert_eq!(on(date!(2000 - 01 - 06)), MoonPhase::New);
+        assert_eq!(on(date!(2024 - 04 - 08)), MoonPhase::New);
+        assert_eq!(on(date!(2024 - 04 - 15)), MoonPhase::FirstQuarter);
+        assert_eq!(on(date!(2024 - 04 - 19)), MoonPhase::WaxingGibbous);
+        assert_eq!(on(date!(2024 - 04 - 23)), MoonPhase::Full);
+        assert_eq!(on(date!(2024 - 05 - 01)), MoonPhase::LastQuarter);
+        assert_eq!(on(date!(1999 - 12 - 22)), MoonPhase::Full);
+        assert_eq!(MoonPhase::WaningCrescent.to_string(), "Waning Crescent");
+    }
+
+    #[test]
+    fn test_alert_severity_from_cap() {
+        assert_eq!(AlertSeverity::from_cap("Severe"), AlertSeverity::Severe);
+        assert_eq!(AlertSeverity::from_cap(" extreme "), AlertSeverity::Extreme);
+        assert_eq!(
+            AlertSeverity::from_cap("Catastrophic"),
+            AlertSeverity::Unknown
+        );
+        assert!(AlertSeverity::Extreme > AlertSeverity::Minor);
+
+        assert_eq!(AlertUrgency::from_cap("Immediate"), AlertUrgency::Immediate);
+        assert_eq!(AlertUrgency::from_cap("soon"), AlertUrgency::Unknown);
+        assert!(AlertUrgency::Immediate > AlertUrgency::Future);
+    }
+
+    #[test]
+    fn test_alert_active_until_expiry() {
+        use time::macros::datetime;
+
+        let alert = WeatherAlert {
+            id: "heat".to_string(),
+            event: "Heat Advisory".to_string(),
+            headline: "Heat Advisory until 8PM".to_string(),
+            severity: AlertSeverity::Minor,
+            urgency: AlertUrgency::Expected,
+            areas: WeatherAlert::parse_areas("Denver; ; Central Broomfield County "),
+            effective: None,
+            expires: Some(datetime!(2023-01-13 20:00 UTC)),
+        };
+        assert_eq!(alert.areas, vec!["Denver", "Central Broomfield County"]);
+        assert!(alert.is_active_at(datetime!(2023-01-13 19:59 UTC)));
+        assert!(!alert.is_active_at(datetime!(2023-01-13 20:00 UTC)));
+
+        let open_ended = WeatherAlert {
+            expires: None,
+            ..alert
+        };
+        assert!(open_ended.is_active_at(datetime!(2030-01-01 00:00 UTC)));
+    }
+
+    #[test]
+    fn test_serde_round_trip_revalidates() {
+        let wind = WindSpeed::with_gusts(20, Some(35)).unwrap();
+        let json = serde_json::to_string(&wind).unwrap();
+        assert_eq!(json, r#"{"sustained":20,"gusts":35}"#);
+        assert_eq!(serde_json::from_str::<WindSpeed>(&json).unwrap(), wind);
+
+        let time: WeatherTime = serde_json::from_str(r#""06:30""#).unwrap();
+        assert_eq!(time.format_24h(), "06:30");
+
+        // Values that fail domain validation are rejected on the way in
+        assert!(serde_json::from_str::<Temperature>("999").is_err());
+        assert!(serde_json::from_str::<WindSpeed>(r#"{"sustained":30,"gusts":10}"#).is_err());
+        assert!(serde_json::from_str::<WeatherTime>(r#""25:99""#).is_err());
+    }
+
+    #[test]
+    fn test_roll_forward_drops_past_hours() {
+        use time::macros::datetime;
+
+        let hour = |time| HourlyWeather {
+            time,
+            temperature: Temperature::new(20.0).unwrap(),
+            condition: WeatherCondition::new("Clear".to_string()),
+            wind_speed: WindSpeed::new(10).unwrap(),
+            wind_direction: WindDirection::N,
+            precipitation: None,
+            chance_of_rain: None,
+            chance_of_snow: None,
+        };
+        let day = |day| DailyForecast {
+            date: time::Date::from_calendar_date(2023, time::Month::January, day).unwrap(),
+            min_temperature: Temperature::new(12.0).unwrap(),
+            max_temperature: Temperature::new(20.0).unwrap(),
+            condition: WeatherCondition::new("Clear".to_string()),
+            chance_of_rain: None,
+            precipitation: Precipitation::new(0.0).unwrap(),
+            max_wind: WindSpeed::new(10).unwrap(),
+        };
+        let alert = |expires| WeatherAlert {
+            id: "wind".to_string(),
+            event: "Strong Wind Watch".to_string(),
+            headline: "Strong Wind Watch".to_string(),
+            severity: AlertSeverity::Moderate,
+            urgency: AlertUrgency::Expected,
+            areas: vec![],
+            effective: None,
+            expires,
+        };
+        let data = WeatherData {
+            current: CurrentWeather {
+                last_updated: LastUpdated::from_epoch(1673573400).unwrap(),
+                temperature: Temperature::new(20.0).unwrap(),
+                feels_like: Temperature::new(20.0).unwrap(),
+                condition: WeatherCondition::new("Clear".to_string()),
+                humidity: Humidity::new(60.0).unwrap(),
+                wind_speed: WindSpeed::new(10).unwrap(),
+                wind_direction: WindDirection::N,
+                pressure: Pressure::new(1013).unwrap(),
+                precipitation: None,
+                uv_index: None,
+                visibility: None,
+                cloud_cover: None,
+            },
+            location: Location::new("Wellington".to_string()),
+            weather_day: Some(WeatherDay {
+                astronomy: None,
+                hourly_weather: vec![
+                    hour(datetime!(2023-01-13 14:00 +13)),
+                    hour(datetime!(2023-01-13 15:00 +13)),
+                    hour(datetime!(2023-01-13 23:00 +13)),
+                    hour(datetime!(2023-01-14 00:00 +13)),
+                ],
+            }),
+            daily: vec![day(13), day(14)],
+            alerts: vec![alert(Some(datetime!(2023-01-13 15:00 +13))), alert(None)],
+            air_quality: None,
+            source: "Test".to_string(),
+            local_time: datetime!(2023-01-13 14:30 +13),
+            time_zone: None,
+            stale_since: None,
+        };
+        let hours = |data: &WeatherData| data.weather_day.as_ref().unwrap().hourly_weather.len();
+
+        // 15:10 in Wellington
+        let rolled = data.clone().roll_forward(datetime!(2023-01-13 02:10 UTC));
+        assert_eq!(hours(&rolled), 3);
+        assert_eq!(rolled.local_time, datetime!(2023-01-13 15:10 +13));
+        // The watch expired at 15:00; the open-ended alert stays
+        assert_eq!(rolled.alerts.len(), 1);
+        assert_eq!(rolled.alerts[0].expires, None);
+
+        // A clock behind the fetch time keeps everything
+        let kept = data.clone().roll_forward(datetime!(2023-01-13 00:00 UTC));
+        assert_eq!(hours(&kept), 4);
+        assert_eq!(kept.alerts.len(), 2);
+
+        // The window rolls across local midnight; the first day has passed
+        let rolled = data.roll_forward(datetime!(2023-01-13 11:30 UTC));
+        assert_eq!(hours(&rolled), 1);
+        assert_eq!(rolled.daily.len(), 1);
+        assert_eq!(rolled.daily[0].date.day(), 14);
+    }
+
+    #[test]
+    fn test_rain_likely_within_hours() {
+        use time::macros::datetime;
+
+        let hour = |time, chance: Option<u8>, amount: f64| HourlyWeather {
+            time,
+            temperature: Temperature::new(15.0).unwrap(),
+            condition: WeatherCondition::new("Cloudy".to_string()),
+            wind_speed: WindSpeed::new(10).unwrap(),
+            wind_direction: WindDirection::S,
+            precipitation: Some(Precipitation::new(amount).unwrap()),
+            chance_of_rain: chance.map(|chance| Probability::new(chance).unwrap()),
+            chance_of_snow: None,
+        };
+        let data = WeatherData {
+            current: CurrentWeather {
+                last_updated: LastUpdated::from_epoch(1673573400).unwrap(),
+                temperature: Temperature::new(15.0).unwrap(),
+                feels_like: Temperature::new(15.0).unwrap(),
+                condition: WeatherCondition::new("Cloudy".to_string()),
+                humidity: Humidity::new(80.0).unwrap(),
+                wind_speed: WindSpeed::new(10).unwrap(),
+                wind_direction: WindDirection::S,
+                pressure: Pressure::new(1008).unwrap(),
+                precipitation: None,
+                uv_index: None,
+                visibility: None,
+                cloud_cover: None,
+            },
+            location: Location::new("Wellington".to_string()),
+            weather_day: Some(WeatherDay {
+                astronomy: None,
+                hourly_weather: vec![
+                    hour(datetime!(2023-01-13 14:00 +13), Some(10), 0.0),
+                    hour(datetime!(2023-01-13 15:00 +13), Some(40), 0.4),
+                    hour(datetime!(2023-01-13 16:00 +13), Some(75), 1.8),
+                    hour(datetime!(2023-01-13 17:00 +13), None, 0.6),
+                ],
+            }),
+            daily: vec![],
+            alerts: vec![],
+            air_quality: None,
+            source: "Test".to_string(),
+            local_time: datetime!(2023-01-13 14:30 +13),
+            time_zone: None,
+            stale_since: None,
+        };
+
+        // A low chance outweighs a small forecast amount
+        assert!(!data.rain_likely_within(2));
+        assert!(data.rain_likely_within(3));
+
+        // Without a chance, a measurable amount counts
+        let hourly = &data.weather_day.as_ref().unwrap().hourly_weather;
+        assert!(hourly[3].rain_likely());
+        assert!(!hourly[1].rain_likely());
+    }
 }
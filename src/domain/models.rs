@@ -1,20 +1,105 @@
 //! Domain aggregate types for weather data.
 
 use crate::domain::{
-    Astronomy, Humidity, LastUpdated, Location, Pressure, Temperature, WeatherCondition,
-    WeatherTime, WindDirection, WindSpeed,
+    AlertSeverity, AlertUrgency, Astronomy, CloudCover, Concentration, DefraIndex, Humidity,
+    LastUpdated, Location, LocationQuery, Precipitation, Pressure, Probability, Temperature,
+    UsEpaIndex, UvIndex, Visibility, WeatherCondition, WeatherTime, WindDirection, WindSpeed,
+    ZonedDateTime,
 };
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime};
 
 /// Domain model for complete weather data
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherData {
     pub current: CurrentWeather,
     pub location: Location,
     pub weather_day: Option<WeatherDay>,
+    /// Forecast for today and the following days, as far ahead as the provider offers
+    #[serde(default)]
+    pub daily: Vec<DailyForecast>,
+    pub alerts: Vec<WeatherAlert>,
+    /// Current air quality, when the provider reports it
+    #[serde(default)]
+    pub air_quality: Option<AirQuality>,
+    /// Name of the service that provided the data
+    pub source: String,
+    /// Wall-clock time at the location, with its UTC offset, as of when the data was
+    /// fetched or last rolled forward
+    #[serde(with = "time::serde::rfc3339")]
+    pub local_time: OffsetDateTime,
+    /// IANA time zone of the location (e.g., "Pacific/Auckland"), when the provider
+    /// gave one
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// When this data was originally fetched, if it is being shown because a fresh
+    /// fetch failed
+    #[serde(skip)]
+    pub stale_since: Option<OffsetDateTime>,
 }
 
+impl WeatherData {
+    /// Re-apply the upcoming-hours window as of `now` without refetching.
+    ///
+    /// Hours that have passed are dropped, as are days before the location's current
+    /// date from the daily forecast, and alerts that have expired. `local_time`
+    /// advances to `now`.
+    pub fn roll_forward(mut self, now: OffsetDateTime) -> Self {
+        let local_now = now.max(self.local_time).to_offset(self.local_time.offset());
+
+        self.weather_day = self
+            .weather_day
+            .map(|day| day.filter_future_hours(local_now));
+        self.daily.retain(|day| day.date >= local_now.date());
+        self.alerts.retain(|alert| alert.is_active_at(local_now));
+        self.local_time = local_now;
+        self
+    }
+
+    /// An instant on the location's clocks: in its time zone when known, otherwise at
+    /// the UTC offset of `local_time`
+    pub fn zoned(&self, instant: OffsetDateTime) -> ZonedDateTime {
+        self.time_zone
+            .as_deref()
+            .and_then(|zone| ZonedDateTime::in_named_zone(instant, zone))
+            .unwrap_or_else(|| {
+                ZonedDateTime::at_offset(instant.to_offset(self.local_time.offset()))
+            })
+    }
+
+    /// Today's instant of a wall-clock time at the location (e.g., sunrise)
+    pub fn today_at(&self, time: WeatherTime) -> OffsetDateTime {
+        self.local_time.replace_time(time.as_time())
+    }
+
+    /// Whether rain is likely in the hour in progress or any of the following
+    /// `hours - 1` hours
+    pub fn rain_likely_within(&self, hours: u32) -> bool {
+        self.weather_day.iter().any(|day| {
+            day.hourly_weather
+                .iter()
+                .filter(|hourly| hourly.time + Duration::HOUR > self.local_time)
+                .take(hours as usize)
+                .any(HourlyWeather::rain_likely)
+        })
+    }
+
+    /// Whether it is daytime at the location, as the provider reported with the current
+    /// conditions or else from sunrise and sunset; `None` when neither is known
+    pub fn is_daytime(&self) -> Option<bool> {
+        if let Some(is_day) = self.current.condition.is_day() {
+            return Some(is_day);
+        }
+        let astronomy = self.weather_day.as_ref()?.astronomy.as_ref()?;
+        Some(astronomy.is_daytime(WeatherTime::from_time(self.local_time.time())))
+    }
+}
+
+/// Chance of rain from which rain counts as likely, in percent
+pub const RAIN_LIKELY_PERCENT: u8 = 50;
+
 /// Domain model for current weather conditions
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CurrentWeather {
     pub last_updated: LastUpdated,
     pub temperature: Temperature,
@@ -24,40 +109,169 @@ pub struct CurrentWeather {
     pub wind_speed: WindSpeed,
     pub wind_direction: WindDirection,
     pub pressure: Pressure,
+    /// Recent precipitation, when the provider reports it
+    pub precipitation: Option<Precipitation>,
+    pub uv_index: Option<UvIndex>,
+    pub visibility: Option<Visibility>,
+    pub cloud_cover: Option<CloudCover>,
 }
 
 /// Domain model for weather day with astronomy and hourly data
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherDay {
     pub astronomy: Option<Astronomy>,
     pub hourly_weather: Vec<HourlyWeather>,
 }
 
 impl WeatherDay {
-    /// Filter hourly weather to only include future hours using location's local time
-    pub fn filter_future_hours(mut self, current_local_hour: u32) -> Self {
-        // Filter to keep only future hours (including current hour for some tolerance)
-        self.hourly_weather
-            .retain(|hourly| hourly.time.hour24() >= current_local_hour);
-
-        // Calculate the end hour: either 12 hours from now or 23:00, whichever is smaller
-        // Subtract 1 to make it exclusive (12 hours max, not 13)
-        let max_end_hour = std::cmp::min(current_local_hour + 11, 23);
-
-        // Keep only hours up to the calculated end hour
+    /// Filter hourly weather to the hour in progress at `now` and those after it,
+    /// whichever day they fall on.
+    ///
+    /// How many of the remaining hours are shown is up to the presentation layer.
+    pub fn filter_future_hours(mut self, now: OffsetDateTime) -> Self {
         self.hourly_weather
-            .retain(|hourly| hourly.time.hour24() <= max_end_hour);
+            .retain(|hourly| hourly.time + Duration::HOUR > now);
 
         self
     }
 }
 
 /// Domain model for hourly weather
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HourlyWeather {
-    pub time: WeatherTime,
+    /// Start of the hour, in the location's local time
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
     pub temperature: Temperature,
     pub condition: WeatherCondition,
     pub wind_speed: WindSpeed,
     pub wind_direction: WindDirection,
+    /// Expected precipitation over the hour, when forecast
+    pub precipitation: Option<Precipitation>,
+    pub chance_of_rain: Option<Probability>,
+    pub chance_of_snow: Option<Probability>,
+}
+
+impl HourlyWeather {
+    /// Whether rain is likely this hour: a chance of at least `RAIN_LIKELY_PERCENT`,
+    /// or, when the provider gives no chance, a measurable forecast amount
+    pub fn rain_likely(&self) -> bool {
+        match self.chance_of_rain {
+            Some(chance) => chance.as_percent() >= RAIN_LIKELY_PERCENT,
+            None => self
+                .precipitation
+                .is_some_and(|amount| amount.is_measurable()),
+        }
+    }
+}
+
+/// Domain model for one day of the multi-day forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyForecast {
+    /// Local date at the location
+    pub date: Date,
+    pub min_temperature: Temperature,
+    pub max_temperature: Temperature,
+    pub condition: WeatherCondition,
+    /// Chance of rain, when the provider forecasts it
+    pub chance_of_rain: Option<Probability>,
+    /// Total precipitation over the day
+    pub precipitation: Precipitation,
+    /// Highest sustained wind (and gusts, when known) over the day
+    pub max_wind: WindSpeed,
+}
+
+/// Domain model for air quality at the location
+///
+/// Pollutants the provider did not measure are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AirQuality {
+    pub pm2_5: Option<Concentration>,
+    pub pm10: Option<Concentration>,
+    pub ozone: Option<Concentration>,
+    pub nitrogen_dioxide: Option<Concentration>,
+    pub sulphur_dioxide: Option<Concentration>,
+    pub carbon_monoxide: Option<Concentration>,
+    pub us_epa_index: Option<UsEpaIndex>,
+    pub gb_defra_index: Option<DefraIndex>,
+}
+
+/// Domain model for an official weather warning issued for the location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherAlert {
+    /// Identifier that stays the same across fetches, used to notify about each
+    /// alert only once
+    #[serde(default)]
+    pub id: String,
+    /// Short event type, e.g. "Winter Storm Warning"
+    pub event: String,
+    pub headline: String,
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub urgency: AlertUrgency,
+    /// Areas the alert covers, as named by the issuer
+    #[serde(default)]
+    pub areas: Vec<String>,
+    /// When the alert comes into force, if the issuer says
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub effective: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires: Option<OffsetDateTime>,
+}
+
+impl WeatherAlert {
+    /// Whether the alert is still in force at the given time
+    pub fn is_active_at(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_none_or(|expires| expires > now)
+    }
+
+    /// Split an issuer's list of areas (e.g., "Denver; Central Broomfield County")
+    pub fn parse_areas(areas: &str) -> Vec<String> {
+        areas
+            .split(';')
+            .map(str::trim)
+            .filter(|area| !area.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Domain model for a place offered by a location search, for the user to choose from
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceMatch {
+    pub location: Location,
+    /// The provider's id for the place, when it accepts `id:<n>` queries
+    pub id: Option<u64>,
+}
+
+impl PlaceMatch {
+    /// Query that finds this place again: its coordinates, which every provider takes,
+    /// or its name when the search gave none
+    pub fn query(&self) -> LocationQuery {
+        match self.location.coordinates() {
+            Some(coordinates) => LocationQuery::Coordinates(coordinates),
+            None => LocationQuery::Name(self.location.name().to_string()),
+        }
+    }
+}
+
+/// Domain model for one of several locations shown together, e.g. a team's cities
+#[derive(Debug, Clone)]
+pub struct LocationWeather {
+    pub query: LocationQuery,
+    /// Latest weather for the location; `None` when it could not be fetched
+    pub weather: Option<WeatherData>,
+    /// Whether this is the location the output is about
+    pub shown: bool,
+}
+
+impl LocationWeather {
+    /// Name of the location: the one the provider resolved, or the query while there
+    /// is no weather to take it from
+    pub fn name(&self) -> String {
+        match &self.weather {
+            Some(weather) => weather.location.to_string(),
+            None => self.query.to_string(),
+        }
+    }
 }
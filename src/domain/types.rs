@@ -1,9 +1,11 @@
 //! Core domain types for weather data with compile-time safety and validation.
 
 use super::error::WeatherError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::marker::PhantomData;
-use time::{macros::format_description, OffsetDateTime, PrimitiveDateTime, Time};
+use time::{macros::format_description, Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use time_tz::{timezones, Offset, OffsetDateTimeExt, TimeZone, Tz};
 
 // === Range Validation Trait ===
 
@@ -18,7 +20,8 @@ where
 
     /// Validate that a value is within the range [MIN, MAX]
     fn validate(value: T) -> Result<(), WeatherError> {
-        if value < Self::MIN || value > Self::MAX {
+        // Written as containment so that NaN, which compares false either way, is rejected
+        if !(Self::MIN..=Self::MAX).contains(&value) {
             return Err(WeatherError::OutOfRange {
                 value: value.to_string(),
                 min: Self::MIN.to_string(),
@@ -63,14 +66,36 @@ where
     }
 }
 
+impl<T, R> Serialize for RangeValidatedValue<T, R>
+where
+    T: PartialOrd + Copy + fmt::Display + Serialize,
+    R: RangeValidated<T>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+/// Deserialization goes through `new`, so out-of-range values are rejected
+impl<'de, T, R> Deserialize<'de> for RangeValidatedValue<T, R>
+where
+    T: PartialOrd + Copy + fmt::Display + Deserialize<'de>,
+    R: RangeValidated<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = T::deserialize(deserializer)?;
+        Self::new(value).map_err(serde::de::Error::custom)
+    }
+}
+
 // === Range Definitions ===
 
 /// Weather temperature range (-40 to 55°C)
 #[derive(Debug, Clone, Copy)]
 pub struct WeatherTempRange;
-impl RangeValidated<i32> for WeatherTempRange {
-    const MIN: i32 = -40;
-    const MAX: i32 = 55;
+impl RangeValidated<f64> for WeatherTempRange {
+    const MIN: f64 = -40.0;
+    const MAX: f64 = 55.0;
     const UNIT: &'static str = "°C";
 }
 
@@ -101,19 +126,123 @@ impl RangeValidated<u32> for WindSpeedRange {
     const UNIT: &'static str = "km/h";
 }
 
-/// Temperature in Celsius with validation
-pub type Temperature = RangeValidatedValue<i32, WeatherTempRange>;
+/// Precipitation amount range (0 to 500 mm)
+#[derive(Debug, Clone, Copy)]
+pub struct PrecipitationRange;
+impl RangeValidated<f64> for PrecipitationRange {
+    const MIN: f64 = 0.0;
+    const MAX: f64 = 500.0;
+    const UNIT: &'static str = "mm";
+}
+
+/// Probability percentage range (0 to 100%)
+#[derive(Debug, Clone, Copy)]
+pub struct ProbabilityRange;
+impl RangeValidated<u8> for ProbabilityRange {
+    const MIN: u8 = 0;
+    const MAX: u8 = 100;
+    const UNIT: &'static str = "%";
+}
+
+/// UV index range (0 to 20)
+#[derive(Debug, Clone, Copy)]
+pub struct UvIndexRange;
+impl RangeValidated<f32> for UvIndexRange {
+    const MIN: f32 = 0.0;
+    const MAX: f32 = 20.0;
+    const UNIT: &'static str = "";
+}
+
+/// Horizontal visibility range (0 to 500 km)
+#[derive(Debug, Clone, Copy)]
+pub struct VisibilityRange;
+impl RangeValidated<f64> for VisibilityRange {
+    const MIN: f64 = 0.0;
+    const MAX: f64 = 500.0;
+    const UNIT: &'static str = "km";
+}
+
+/// Cloud cover percentage range (0 to 100%)
+#[derive(Debug, Clone, Copy)]
+pub struct CloudCoverRange;
+impl RangeValidated<u8> for CloudCoverRange {
+    const MIN: u8 = 0;
+    const MAX: u8 = 100;
+    const UNIT: &'static str = "%";
+}
+
+/// Pollutant concentration range (0 to 100,000 µg/m³)
+#[derive(Debug, Clone, Copy)]
+pub struct ConcentrationRange;
+impl RangeValidated<f64> for ConcentrationRange {
+    const MIN: f64 = 0.0;
+    const MAX: f64 = 100_000.0;
+    const UNIT: &'static str = "µg/m³";
+}
+
+/// US EPA air quality index band range (1 to 6)
+#[derive(Debug, Clone, Copy)]
+pub struct UsEpaIndexRange;
+impl RangeValidated<u8> for UsEpaIndexRange {
+    const MIN: u8 = 1;
+    const MAX: u8 = 6;
+    const UNIT: &'static str = "";
+}
+
+/// UK DEFRA daily air quality index range (1 to 10)
+#[derive(Debug, Clone, Copy)]
+pub struct DefraIndexRange;
+impl RangeValidated<u8> for DefraIndexRange {
+    const MIN: u8 = 1;
+    const MAX: u8 = 10;
+    const UNIT: &'static str = "";
+}
+
+/// Latitude range (-90 to 90°, north positive)
+#[derive(Debug, Clone, Copy)]
+pub struct LatitudeRange;
+impl RangeValidated<f64> for LatitudeRange {
+    const MIN: f64 = -90.0;
+    const MAX: f64 = 90.0;
+    const UNIT: &'static str = "°N";
+}
+
+/// Longitude range (-180 to 180°, east positive)
+#[derive(Debug, Clone, Copy)]
+pub struct LongitudeRange;
+impl RangeValidated<f64> for LongitudeRange {
+    const MIN: f64 = -180.0;
+    const MAX: f64 = 180.0;
+    const UNIT: &'static str = "°E";
+}
+
+/// Temperature in Celsius with validation.
+///
+/// Kept as precise as the provider gave it, so that a reading in °F converted here and
+/// back for display rounds only once.
+pub type Temperature = RangeValidatedValue<f64, WeatherTempRange>;
 
 impl fmt::Display for Temperature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}°C", self.value())
+        write!(f, "{}°C", self.as_celsius())
     }
 }
 
 impl Temperature {
-    /// Get temperature in Celsius
+    /// Get temperature in whole degrees Celsius
     pub fn as_celsius(&self) -> i32 {
-        self.value()
+        self.value().round() as i32
+    }
+
+    /// Estimate the apparent ("feels like") temperature from humidity and wind,
+    /// using the Australian apparent temperature formula (Steadman, 1994)
+    pub fn apparent(&self, humidity: &Humidity, wind: &WindSpeed) -> Temperature {
+        let temp_c = self.value();
+        let vapour_pressure =
+            humidity.value() as f64 / 100.0 * 6.105 * (17.27 * temp_c / (237.7 + temp_c)).exp();
+        let wind_ms = wind.sustained_value() as f64 / 3.6;
+        let apparent = temp_c + 0.33 * vapour_pressure - 0.70 * wind_ms - 4.00;
+        Temperature::new(apparent).unwrap_or(*self)
     }
 }
 
@@ -137,11 +266,253 @@ impl Humidity {
         let temp_c = temperature.as_celsius();
         let humidity_percent = self.as_int();
         let dew_point = temp_c - (100 - humidity_percent) / 5;
-        Temperature::new(dew_point).unwrap_or(*temperature)
+        Temperature::new(f64::from(dew_point)).unwrap_or(*temperature)
+    }
+}
+
+/// Precipitation amount in millimetres (liquid equivalent) with validation
+pub type Precipitation = RangeValidatedValue<f64, PrecipitationRange>;
+
+impl fmt::Display for Precipitation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} mm", self.value())
+    }
+}
+
+impl Precipitation {
+    /// Get the amount in millimetres
+    pub fn as_mm(&self) -> f64 {
+        self.value()
+    }
+
+    /// Whether the amount is measurable, i.e. at least 0.1 mm once rounded
+    pub fn is_measurable(&self) -> bool {
+        self.value() >= 0.05
+    }
+}
+
+/// Chance of an event, as a whole percentage, with validation
+pub type Probability = RangeValidatedValue<u8, ProbabilityRange>;
+
+impl fmt::Display for Probability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.value())
+    }
+}
+
+impl Probability {
+    /// Get the chance as a percentage
+    pub fn as_percent(&self) -> u8 {
+        self.value()
+    }
+}
+
+/// UV index with validation
+pub type UvIndex = RangeValidatedValue<f32, UvIndexRange>;
+
+impl fmt::Display for UvIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.as_int(), self.category())
+    }
+}
+
+impl UvIndex {
+    /// Get the index rounded to a whole number, as it is reported to the public
+    pub fn as_int(&self) -> u8 {
+        self.value().round() as u8
+    }
+
+    /// Categorize the index into its WHO exposure band
+    pub fn category(&self) -> UvCategory {
+        UvCategory::from_index(self.as_int())
+    }
+}
+
+/// WHO exposure band of a UV index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvCategory {
+    /// Low: 0-2
+    Low,
+    /// Moderate: 3-5
+    Moderate,
+    /// High: 6-7
+    High,
+    /// Very high: 8-10
+    VeryHigh,
+    /// Extreme: 11+
+    Extreme,
+}
+
+impl UvCategory {
+    /// Categorize a whole-number UV index
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            0..=2 => Self::Low,
+            3..=5 => Self::Moderate,
+            6..=7 => Self::High,
+            8..=10 => Self::VeryHigh,
+            _ => Self::Extreme,
+        }
+    }
+}
+
+impl fmt::Display for UvCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Low => "Low",
+            Self::Moderate => "Moderate",
+            Self::High => "High",
+            Self::VeryHigh => "Very High",
+            Self::Extreme => "Extreme",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Horizontal visibility in kilometres with validation
+pub type Visibility = RangeValidatedValue<f64, VisibilityRange>;
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} km", self.value())
+    }
+}
+
+impl Visibility {
+    /// Get the visibility in kilometres
+    pub fn as_km(&self) -> f64 {
+        self.value()
     }
 }
 
-/// Wind speed category based on sustained wind speed
+/// Fraction of the sky covered by cloud, as a whole percentage, with validation
+pub type CloudCover = RangeValidatedValue<u8, CloudCoverRange>;
+
+impl fmt::Display for CloudCover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.value())
+    }
+}
+
+impl CloudCover {
+    /// Get the cover as a percentage
+    pub fn as_percent(&self) -> u8 {
+        self.value()
+    }
+}
+
+/// Pollutant concentration in µg/m³ with validation
+pub type Concentration = RangeValidatedValue<f64, ConcentrationRange>;
+
+impl fmt::Display for Concentration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} µg/m³", self.value())
+    }
+}
+
+impl Concentration {
+    /// Get the concentration in µg/m³
+    pub fn as_micrograms(&self) -> f64 {
+        self.value()
+    }
+}
+
+/// US EPA air quality index band (1 = good to 6 = hazardous) with validation
+pub type UsEpaIndex = RangeValidatedValue<u8, UsEpaIndexRange>;
+
+impl fmt::Display for UsEpaIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl UsEpaIndex {
+    /// Categorize the band
+    pub fn category(&self) -> AirQualityCategory {
+        match self.value() {
+            1 => AirQualityCategory::Good,
+            2 => AirQualityCategory::Moderate,
+            3 => AirQualityCategory::UnhealthyForSensitiveGroups,
+            4 => AirQualityCategory::Unhealthy,
+            5 => AirQualityCategory::VeryUnhealthy,
+            _ => AirQualityCategory::Hazardous,
+        }
+    }
+}
+
+/// US EPA air quality category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AirQualityCategory {
+    Good,
+    Moderate,
+    /// Unhealthy for sensitive groups, such as people with asthma
+    UnhealthyForSensitiveGroups,
+    Unhealthy,
+    VeryUnhealthy,
+    Hazardous,
+}
+
+impl fmt::Display for AirQualityCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Good => "Good",
+            Self::Moderate => "Moderate",
+            Self::UnhealthyForSensitiveGroups => "Unhealthy for Sensitive Groups",
+            Self::Unhealthy => "Unhealthy",
+            Self::VeryUnhealthy => "Very Unhealthy",
+            Self::Hazardous => "Hazardous",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// UK DEFRA daily air quality index (1 to 10) with validation
+pub type DefraIndex = RangeValidatedValue<u8, DefraIndexRange>;
+
+impl fmt::Display for DefraIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.value(), self.band())
+    }
+}
+
+impl DefraIndex {
+    /// Categorize the index into its DEFRA band
+    pub fn band(&self) -> DefraBand {
+        match self.value() {
+            1..=3 => DefraBand::Low,
+            4..=6 => DefraBand::Moderate,
+            7..=9 => DefraBand::High,
+            _ => DefraBand::VeryHigh,
+        }
+    }
+}
+
+/// UK DEFRA air pollution band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefraBand {
+    /// Low: 1-3
+    Low,
+    /// Moderate: 4-6
+    Moderate,
+    /// High: 7-9
+    High,
+    /// Very high: 10
+    VeryHigh,
+}
+
+impl fmt::Display for DefraBand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Low => "Low",
+            Self::Moderate => "Moderate",
+            Self::High => "High",
+            Self::VeryHigh => "Very High",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Wind speed category based on sustained wind speed (ranges are the defaults)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WindSpeedCategory {
     /// Calm winds: 0-19 km/h
@@ -157,14 +528,77 @@ pub enum WindSpeedCategory {
 }
 
 impl WindSpeedCategory {
-    /// Categorize a raw wind speed value in km/h
+    /// Categorize a raw wind speed value in km/h using the default thresholds
     pub fn from_speed(speed: u32) -> Self {
+        WindThresholds::default().categorize(speed)
+    }
+}
+
+/// Lowest wind speed (km/h) of each category above calm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindThresholds {
+    moderate: u32,
+    gale: u32,
+    storm: u32,
+    hurricane: u32,
+}
+
+impl WindThresholds {
+    /// Create thresholds, which must be positive and strictly increasing
+    pub fn new(moderate: u32, gale: u32, storm: u32, hurricane: u32) -> Result<Self, WeatherError> {
+        if moderate == 0 || moderate >= gale || gale >= storm || storm >= hurricane {
+            return Err(WeatherError::InvalidThresholds(format!(
+                "{}, {}, {}, {}",
+                moderate, gale, storm, hurricane
+            )));
+        }
+        Ok(Self {
+            moderate,
+            gale,
+            storm,
+            hurricane,
+        })
+    }
+
+    /// Categorize a raw wind speed value in km/h
+    pub fn categorize(&self, speed: u32) -> WindSpeedCategory {
         match speed {
-            0..=19 => Self::Calm,
-            20..=50 => Self::ModerateBreezes,
-            51..=88 => Self::Gales,
-            89..=117 => Self::Storms,
-            118.. => Self::Hurricane,
+            s if s >= self.hurricane => WindSpeedCategory::Hurricane,
+            s if s >= self.storm => WindSpeedCategory::Storms,
+            s if s >= self.gale => WindSpeedCategory::Gales,
+            s if s >= self.moderate => WindSpeedCategory::ModerateBreezes,
+            _ => WindSpeedCategory::Calm,
+        }
+    }
+
+    /// Lowest speed counted as a moderate breeze
+    pub fn moderate(&self) -> u32 {
+        self.moderate
+    }
+
+    /// Lowest speed counted as a gale
+    pub fn gale(&self) -> u32 {
+        self.gale
+    }
+
+    /// Lowest speed counted as a storm
+    pub fn storm(&self) -> u32 {
+        self.storm
+    }
+
+    /// Lowest speed counted as hurricane force
+    pub fn hurricane(&self) -> u32 {
+        self.hurricane
+    }
+}
+
+impl Default for WindThresholds {
+    fn default() -> Self {
+        Self {
+            moderate: 20,
+            gale: 51,
+            storm: 89,
+            hurricane: 118,
         }
     }
 }
@@ -212,12 +646,28 @@ impl Default for WindSpeedBuilder {
 }
 
 /// Wind speed with gusts and validation
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedWindSpeed")]
 pub struct WindSpeed {
     sustained: u32,
     gusts: Option<u32>,
 }
 
+/// Serialized form of `WindSpeed`, validated on the way in
+#[derive(Deserialize)]
+struct UncheckedWindSpeed {
+    sustained: u32,
+    gusts: Option<u32>,
+}
+
+impl TryFrom<UncheckedWindSpeed> for WindSpeed {
+    type Error = WeatherError;
+
+    fn try_from(value: UncheckedWindSpeed) -> Result<Self, WeatherError> {
+        Self::with_gusts(value.sustained, value.gusts)
+    }
+}
+
 impl WindSpeed {
     /// Create wind speed with just sustained wind
     pub fn new(sustained: u32) -> Result<Self, WeatherError> {
@@ -247,13 +697,7 @@ impl WindSpeed {
 
     /// Categorize wind speed based on sustained wind
     pub fn category(&self) -> WindSpeedCategory {
-        match self.sustained {
-            0..=19 => WindSpeedCategory::Calm,
-            20..=50 => WindSpeedCategory::ModerateBreezes,
-            51..=88 => WindSpeedCategory::Gales,
-            89..=117 => WindSpeedCategory::Storms,
-            118.. => WindSpeedCategory::Hurricane,
-        }
+        WindSpeedCategory::from_speed(self.sustained)
     }
 
     /// Get the category of gust wind speed, if gusts are present
@@ -291,7 +735,8 @@ impl fmt::Display for Pressure {
 }
 
 /// Wind direction as one of 16 compass points
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum WindDirection {
     N, NNE, NE, ENE,
     E, ESE, SE, SSE,
@@ -346,12 +791,209 @@ impl WindDirection {
             _ => Err(WeatherError::InvalidDirection(compass.to_string())),
         }
     }
+
+    /// Convert a meteorological bearing in degrees (direction the wind blows from)
+    /// to the nearest of the 16 compass points
+    pub fn from_degrees(degrees: f64) -> Self {
+        const POINTS: [WindDirection; 16] = [
+            WindDirection::N,
+            WindDirection::NNE,
+            WindDirection::NE,
+            WindDirection::ENE,
+            WindDirection::E,
+            WindDirection::ESE,
+            WindDirection::SE,
+            WindDirection::SSE,
+            WindDirection::S,
+            WindDirection::SSW,
+            WindDirection::SW,
+            WindDirection::WSW,
+            WindDirection::W,
+            WindDirection::WNW,
+            WindDirection::NW,
+            WindDirection::NNW,
+        ];
+        let normalized = degrees.rem_euclid(360.0);
+        let index = (normalized / 22.5).round() as usize % POINTS.len();
+        POINTS[index]
+    }
+}
+
+/// Latitude and longitude in decimal degrees, validated to lie on the globe
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    latitude: f64,
+    longitude: f64,
 }
 
-/// Location name with fallback handling
+impl Coordinates {
+    /// Create coordinates, rejecting a latitude beyond the poles or a longitude
+    /// beyond the antimeridian
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, WeatherError> {
+        LatitudeRange::validate(latitude)?;
+        LongitudeRange::validate(longitude)?;
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Degrees north of the equator; negative in the southern hemisphere
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Degrees east of Greenwich; negative in the western hemisphere
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// The same point to `decimals` decimal places (2 is about a kilometre)
+    pub fn rounded(self, decimals: i32) -> Self {
+        let scale = 10f64.powi(decimals);
+        Self {
+            latitude: (self.latitude * scale).round() / scale,
+            longitude: (self.longitude * scale).round() / scale,
+        }
+    }
+}
+
+/// Written as `lat,lon`, the form `LocationQuery` parses
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.latitude, self.longitude)
+    }
+}
+
+/// What the user asked for weather at, before any provider has resolved it.
+///
+/// Each provider adapter translates a query into its own syntax; kinds a provider
+/// cannot look up are reported as errors so that the next provider is tried.
 #[derive(Debug, Clone, PartialEq)]
+pub enum LocationQuery {
+    /// Exact coordinates, written `lat,lon` (e.g., "-41.2866,174.7756")
+    Coordinates(Coordinates),
+    /// US ZIP code, UK postcode or Canadian postal code, normalised to upper case
+    Postcode(String),
+    /// Airport by its three-letter IATA code, written `iata:WLG`
+    Airport(String),
+    /// A provider's identifier for a place, written `id:<n>`
+    Id(u64),
+    /// Place name, resolved by the provider's own geocoding
+    Name(String),
+    /// Wherever the computer is, found with a location service before fetching; chosen
+    /// in the configuration rather than written as text
+    Current,
+}
+
+impl LocationQuery {
+    /// Parse a location as written on the command line or in the configuration
+    pub fn parse(input: &str) -> Result<Self, WeatherError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(WeatherError::MissingField("Location"));
+        }
+
+        if let Some(code) = strip_prefix_ignore_case(trimmed, "iata:") {
+            let code = code.trim();
+            if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(WeatherError::InvalidLocation(format!(
+                    "IATA codes have three letters: {}",
+                    trimmed
+                )));
+            }
+            return Ok(Self::Airport(code.to_ascii_uppercase()));
+        }
+        if let Some(id) = strip_prefix_ignore_case(trimmed, "id:") {
+            return id.trim().parse().map(Self::Id).map_err(|_| {
+                WeatherError::InvalidLocation(format!("Place ids are numbers: {}", trimmed))
+            });
+        }
+        if let Some((latitude, longitude)) = trimmed.split_once(',') {
+            if let (Ok(latitude), Ok(longitude)) =
+                (latitude.trim().parse(), longitude.trim().parse())
+            {
+                return Coordinates::new(latitude, longitude).map(Self::Coordinates);
+            }
+        }
+        if let Some(postcode) = normalize_postcode(trimmed) {
+            return Ok(Self::Postcode(postcode));
+        }
+        Ok(Self::Name(trimmed.to_string()))
+    }
+}
+
+impl std::str::FromStr for LocationQuery {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Written back in the form it is parsed from
+impl fmt::Display for LocationQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Coordinates(coordinates) => write!(f, "{}", coordinates),
+            Self::Postcode(postcode) => write!(f, "{}", postcode),
+            Self::Airport(code) => write!(f, "iata:{}", code),
+            Self::Id(id) => write!(f, "id:{}", id),
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Current => write!(f, "current location"),
+        }
+    }
+}
+
+/// `input` without a leading `prefix`, compared ASCII case-insensitively
+fn strip_prefix_ignore_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = input.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &input[prefix.len()..])
+}
+
+/// The canonical form of a US ZIP code ("12345" or "12345-6789"), a full UK postcode
+/// ("SW1A 1AA") or a Canadian postal code ("K1A 0B1", or its first three characters)
+fn normalize_postcode(input: &str) -> Option<String> {
+    let compact: String = input
+        .chars()
+        .filter(|c| *c != ' ')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let shape: String = compact
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => '9',
+            'A'..='Z' => 'A',
+            other => other,
+        })
+        .collect();
+
+    match shape.as_str() {
+        "99999" | "99999-9999" => Some(compact),
+        "A9A" => Some(compact),
+        "A9A9A9" => Some(format!("{} {}", &compact[..3], &compact[3..])),
+        // UK outward codes (A9, A99, AA9, AA99, A9A, AA9A) followed by the inward code
+        "A99AA" | "A999AA" | "AA99AA" | "AA999AA" | "A9A9AA" | "AA9A9AA" => {
+            let split = compact.len() - 3;
+            Some(format!("{} {}", &compact[..split], &compact[split..]))
+        }
+        _ => None,
+    }
+}
+
+/// A resolved place: its name and, when the provider reports them, the region and
+/// country it lies in and its coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Location {
     name: String,
+    /// State, province or other first-level area
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    coordinates: Option<Coordinates>,
 }
 
 impl Location {
@@ -362,7 +1004,75 @@ impl Location {
         } else {
             name
         };
-        Self { name }
+        Self {
+            name,
+            region: None,
+            country: None,
+            coordinates: None,
+        }
+    }
+
+    /// Set the region, ignoring an empty one
+    pub fn with_region(mut self, region: Option<String>) -> Self {
+        self.region = region.filter(|region| !region.trim().is_empty());
+        self
+    }
+
+    /// Set the country, ignoring an empty one
+    pub fn with_country(mut self, country: Option<String>) -> Self {
+        self.country = country.filter(|country| !country.trim().is_empty());
+        self
+    }
+
+    /// Set the coordinates
+    pub fn with_coordinates(mut self, coordinates: Option<Coordinates>) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Show the place under another name, keeping what else is known about it; an
+    /// empty name is ignored
+    pub fn with_name(mut self, name: String) -> Self {
+        if !name.trim().is_empty() {
+            self.name = name;
+        }
+        self
+    }
+
+    /// Place name as the provider gave it
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// State, province or other first-level area, when known
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Country name, when known
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    /// Coordinates the provider reported the weather for, when known
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        self.coordinates
+    }
+
+    /// Region, when it tells the place apart from others of the same name; one that
+    /// only repeats the name (e.g., "Wellington, Wellington") is left out
+    pub fn distinct_region(&self) -> Option<&str> {
+        self.region().filter(|region| *region != self.name)
+    }
+
+    /// Name with its region and country, as far as they are known
+    /// (e.g., "Richmond, Virginia, United States of America")
+    pub fn full_name(&self) -> String {
+        std::iter::once(self.name())
+            .chain(self.distinct_region())
+            .chain(self.country())
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 }
 
@@ -373,7 +1083,8 @@ impl fmt::Display for Location {
 }
 
 /// Weather time with parsing and formatting
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct WeatherTime {
     time: Time,
 }
@@ -416,6 +1127,16 @@ impl WeatherTime {
         Err(WeatherError::InvalidTime(time_str.to_string()))
     }
 
+    /// Wrap a time of day
+    pub fn from_time(time: Time) -> Self {
+        Self { time }
+    }
+
+    /// The time of day
+    pub fn as_time(self) -> Time {
+        self.time
+    }
+
     /// Get hour in 24-hour format
     pub fn hour24(self) -> u32 {
         self.time.hour() as u32
@@ -445,6 +1166,20 @@ impl fmt::Display for WeatherTime {
     }
 }
 
+impl TryFrom<String> for WeatherTime {
+    type Error = WeatherError;
+
+    fn try_from(value: String) -> Result<Self, WeatherError> {
+        Self::parse(&value)
+    }
+}
+
+impl From<WeatherTime> for String {
+    fn from(value: WeatherTime) -> Self {
+        value.format_24h()
+    }
+}
+
 /// Duration representing day length
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Duration {
@@ -477,7 +1212,7 @@ impl fmt::Display for Duration {
 }
 
 /// Astronomical data with sunrise/sunset times and calculations
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Astronomy {
     sunrise: WeatherTime,
     sunset: WeatherTime,
@@ -535,24 +1270,266 @@ impl Astronomy {
             solar_noon_hours, solar_noon_minutes
         ))
     }
+
+    /// Whether local time `now` falls between sunrise and sunset
+    pub fn is_daytime(&self, now: WeatherTime) -> bool {
+        let now_seconds = now.total_seconds();
+        now_seconds >= self.sunrise.total_seconds() && now_seconds < self.sunset.total_seconds()
+    }
+
+    /// The next sunrise or sunset after local time `now`, and how long until it.
+    ///
+    /// After sunset, today's sunrise time stands in for tomorrow's.
+    pub fn next_event(&self, now: WeatherTime) -> (SunEvent, Duration) {
+        let now_seconds = now.total_seconds();
+        let sunrise_seconds = self.sunrise.total_seconds();
+        let sunset_seconds = self.sunset.total_seconds();
+
+        let (event, remaining_seconds) = if now_seconds < sunrise_seconds {
+            (SunEvent::Sunrise, sunrise_seconds - now_seconds)
+        } else if now_seconds < sunset_seconds {
+            (SunEvent::Sunset, sunset_seconds - now_seconds)
+        } else {
+            (SunEvent::Sunrise, 24 * 3600 - now_seconds + sunrise_seconds)
+        };
+
+        (event, Duration::from_minutes(remaining_seconds / 60))
+    }
 }
 
-/// Weather condition with icon mapping
-#[derive(Debug, Clone, PartialEq)]
+/// A solar event at the location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SunEvent {
+    Sunrise,
+    Sunset,
+}
+
+/// Julian date of the new moon of 2000-01-06 18:14 UTC, the reference for moon phases
+const REFERENCE_NEW_MOON_JD: f64 = 2_451_550.26;
+
+/// Mean length of a lunar cycle in days
+const SYNODIC_MONTH_DAYS: f64 = 29.530_588_853;
+
+/// One of the eight named phases of the moon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    /// All phases in the order of the lunar cycle
+    pub const ALL: [Self; 8] = [
+        Self::New,
+        Self::WaxingCrescent,
+        Self::FirstQuarter,
+        Self::WaxingGibbous,
+        Self::Full,
+        Self::WaningGibbous,
+        Self::LastQuarter,
+        Self::WaningCrescent,
+    ];
+
+    /// The phase at midday UTC on a date, from the mean lunar cycle; accurate to
+    /// within a day or so of the exact phase changes
+    pub fn on(date: Date) -> Self {
+        // Julian day numbers count from midday
+        let days = date.to_julian_day() as f64 - REFERENCE_NEW_MOON_JD;
+        let age = days.rem_euclid(SYNODIC_MONTH_DAYS) / SYNODIC_MONTH_DAYS;
+        Self::ALL[(age * 8.0).round() as usize % 8]
+    }
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::New => "New Moon",
+            Self::WaxingCrescent => "Waxing Crescent",
+            Self::FirstQuarter => "First Quarter",
+            Self::WaxingGibbous => "Waxing Gibbous",
+            Self::Full => "Full Moon",
+            Self::WaningGibbous => "Waning Gibbous",
+            Self::LastQuarter => "Last Quarter",
+            Self::WaningCrescent => "Waning Crescent",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Weather condition as described by the provider, with its classification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WeatherCondition {
     description: String,
+    /// Classification from the provider's condition code; `Unknown` when only the
+    /// description is known
+    #[serde(default)]
+    kind: ConditionKind,
+    /// The provider's numeric condition code, for providers that have one
+    #[serde(default)]
+    code: Option<u16>,
+    /// Whether the provider reported the condition for daylight hours
+    #[serde(default)]
+    is_day: Option<bool>,
 }
 
 impl WeatherCondition {
     /// Create weather condition
     pub fn new(description: String) -> Self {
-        Self { description }
+        Self {
+            description,
+            kind: ConditionKind::Unknown,
+            code: None,
+            is_day: None,
+        }
+    }
+
+    /// Set the classification the provider's condition code maps to
+    pub fn with_kind(mut self, kind: ConditionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the provider's numeric condition code
+    pub fn with_code(mut self, code: u16) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Set whether the condition applies during daylight
+    pub fn with_is_day(mut self, is_day: bool) -> Self {
+        self.is_day = Some(is_day);
+        self
     }
 
     /// Get the condition description
     pub fn description(&self) -> &str {
         &self.description
     }
+
+    /// The provider's numeric condition code, if it has one
+    pub fn code(&self) -> Option<u16> {
+        self.code
+    }
+
+    /// Whether the provider reported the condition for daylight hours, if it said
+    pub fn is_day(&self) -> Option<bool> {
+        self.is_day
+    }
+
+    /// Classify the condition, from the provider's code when it was mapped and from the
+    /// description otherwise
+    pub fn kind(&self) -> ConditionKind {
+        match self.kind {
+            ConditionKind::Unknown => ConditionKind::from_description(&self.description),
+            kind => kind,
+        }
+    }
+
+    /// Classify the condition into a broad family of weather
+    pub fn family(&self) -> ConditionFamily {
+        self.kind().family()
+    }
+}
+
+/// Provider-independent classification of the weather
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConditionKind {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Overcast,
+    Fog,
+    Drizzle,
+    Rain,
+    HeavyRain,
+    FreezingRain,
+    /// Passing or patchy rain with sunny spells
+    RainShowers,
+    /// Sleet, ice pellets and mixed rain and snow
+    Sleet,
+    Snow,
+    HeavySnow,
+    SnowShowers,
+    /// Any precipitation with thunder
+    Thunderstorm,
+    Wind,
+    /// Anything not recognised
+    #[default]
+    Unknown,
+}
+
+impl ConditionKind {
+    /// Classify an English description (e.g., "Patchy light rain with thunder");
+    /// thunder wins over the precipitation that comes with it
+    pub fn from_description(description: &str) -> Self {
+        let description = description.to_lowercase();
+        match description.as_str() {
+            c if c.contains("thunder") || c.contains("storm") => Self::Thunderstorm,
+            c if c.contains("blizzard") || c.contains("heavy snow") => Self::HeavySnow,
+            c if c.contains("sleet")
+                || c.contains("ice pellets")
+                || (c.contains("snow") && c.contains("rain")) =>
+            {
+                Self::Sleet
+            }
+            c if c.contains("snow") && c.contains("shower") => Self::SnowShowers,
+            c if c.contains("snow") => Self::Snow,
+            c if c.contains("freezing") && (c.contains("rain") || c.contains("drizzle")) => {
+                Self::FreezingRain
+            }
+            c if c.contains("shower") => Self::RainShowers,
+            c if c.contains("heavy rain") || c.contains("torrential") => Self::HeavyRain,
+            c if c.contains("drizzle") => Self::Drizzle,
+            c if c.contains("rain") => Self::Rain,
+            c if c.contains("fog") || c.contains("mist") => Self::Fog,
+            c if c.contains("overcast") => Self::Overcast,
+            c if c.contains("partly") || c.contains("partial") => Self::PartlyCloudy,
+            c if c.contains("cloudy") => Self::Cloudy,
+            c if c.contains("sunny") || c.contains("clear") => Self::Clear,
+            c if c.contains("wind") => Self::Wind,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The broad family of weather this belongs to
+    pub fn family(self) -> ConditionFamily {
+        match self {
+            Self::Clear => ConditionFamily::Clear,
+            Self::PartlyCloudy => ConditionFamily::PartlyCloudy,
+            Self::Cloudy | Self::Overcast => ConditionFamily::Cloudy,
+            Self::Fog => ConditionFamily::Fog,
+            Self::Drizzle
+            | Self::Rain
+            | Self::HeavyRain
+            | Self::FreezingRain
+            | Self::RainShowers => ConditionFamily::Rain,
+            Self::Sleet | Self::Snow | Self::HeavySnow | Self::SnowShowers => ConditionFamily::Snow,
+            Self::Thunderstorm => ConditionFamily::Storm,
+            Self::Wind => ConditionFamily::Wind,
+            Self::Unknown => ConditionFamily::Other,
+        }
+    }
+}
+
+/// Broad family of weather a condition belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionFamily {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Rain,
+    Storm,
+    Snow,
+    Fog,
+    Wind,
+    /// Anything not recognised
+    Other,
 }
 
 impl fmt::Display for WeatherCondition {
@@ -562,8 +1539,9 @@ impl fmt::Display for WeatherCondition {
 }
 
 /// Timestamp representing when weather data was last updated by the API
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LastUpdated {
+    #[serde(with = "time::serde::rfc3339")]
     datetime: OffsetDateTime,
 }
 
@@ -576,21 +1554,27 @@ impl LastUpdated {
         Ok(Self { datetime })
     }
 
-    /// Create from WeatherAPI format string (e.g., "2023-01-13 14:30")
-    pub fn from_api_format(api_string: &str) -> Result<Self, WeatherError> {
+    /// Create from WeatherAPI format string (e.g., "2023-01-13 14:30"), which is the
+    /// wall-clock time at the location with the given UTC offset
+    pub fn from_api_format(api_string: &str, offset: UtcOffset) -> Result<Self, WeatherError> {
         let format = format_description!("[year]-[month]-[day] [hour]:[minute]");
         let primitive_datetime = PrimitiveDateTime::parse(api_string, &format)
             .map_err(|e| WeatherError::InvalidTimestamp(format!("{}: {}", api_string, e)))?;
 
-        // Assume UTC timezone
-        let datetime = primitive_datetime.assume_utc();
+        let datetime = primitive_datetime.assume_offset(offset);
         Ok(Self { datetime })
     }
 
+    /// The instant of the update
+    pub fn instant(&self) -> OffsetDateTime {
+        self.datetime
+    }
+
     /// Format as yyyy-MM-dd HH:mmZ for display (Z indicates UTC, ISO 8601 standard)
     pub fn format_display(&self) -> String {
         let format = format_description!("[year]-[month]-[day] [hour]:[minute]Z");
         self.datetime
+            .to_offset(UtcOffset::UTC)
             .format(&format)
             .unwrap_or_else(|_| "Invalid date".to_string())
     }
@@ -601,3 +1585,149 @@ impl fmt::Display for LastUpdated {
         write!(f, "{}", self.format_display())
     }
 }
+
+/// An instant as the clocks of a time zone show it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZonedDateTime {
+    /// The instant, at the zone's UTC offset for that instant
+    datetime: OffsetDateTime,
+    /// The zone from the time zone database; `None` when only the UTC offset is known
+    zone: Option<&'static Tz>,
+}
+
+impl ZonedDateTime {
+    /// An instant in a zone from the time zone database
+    pub fn in_zone(instant: OffsetDateTime, zone: &'static Tz) -> Self {
+        Self {
+            datetime: instant.to_timezone(zone),
+            zone: Some(zone),
+        }
+    }
+
+    /// An instant in a zone named by its IANA identifier (e.g., "Pacific/Auckland");
+    /// `None` when the time zone database does not know the name
+    pub fn in_named_zone(instant: OffsetDateTime, name: &str) -> Option<Self> {
+        timezones::get_by_name(name).map(|zone| Self::in_zone(instant, zone))
+    }
+
+    /// An instant in a zone known only by the UTC offset it carries
+    pub fn at_offset(instant: OffsetDateTime) -> Self {
+        Self {
+            datetime: instant,
+            zone: None,
+        }
+    }
+
+    /// The date and time on the zone's clocks, with its UTC offset
+    pub fn datetime(&self) -> OffsetDateTime {
+        self.datetime
+    }
+
+    /// Short name of the zone at this instant: its abbreviation (e.g., "NZDT") when the
+    /// zone has one, otherwise its UTC offset (e.g., "UTC+5:30")
+    pub fn zone_label(&self) -> String {
+        if let Some(zone) = self.zone {
+            let offset = zone.get_offset_utc(&self.datetime);
+            let abbreviation = offset.name();
+            if !abbreviation.starts_with(['+', '-']) {
+                return abbreviation.to_string();
+            }
+        }
+
+        let offset = self.datetime.offset();
+        let (hours, minutes, _) = offset.as_hms();
+        match (hours, minutes) {
+            (0, 0) => "UTC".to_string(),
+            (hours, 0) => format!("UTC{:+}", hours),
+            (hours, minutes) => format!(
+                "UTC{}{}:{:02}",
+                if offset.is_negative() { '-' } else { '+' },
+                hours.abs(),
+                minutes.abs()
+            ),
+        }
+    }
+}
+
+/// Alert severity following the Common Alerting Protocol (CAP) scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    /// Severity not known or not given by the issuer
+    Unknown,
+    /// Minimal to no known threat to life or property
+    Minor,
+    /// Possible threat to life or property
+    Moderate,
+    /// Significant threat to life or property
+    Severe,
+    /// Extraordinary threat to life or property
+    Extreme,
+}
+
+impl AlertSeverity {
+    /// Parse a CAP severity value, treating anything unrecognised as `Unknown`
+    pub fn from_cap(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "minor" => Self::Minor,
+            "moderate" => Self::Moderate,
+            "severe" => Self::Severe,
+            "extreme" => Self::Extreme,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Unknown => "Unknown",
+            Self::Minor => "Minor",
+            Self::Moderate => "Moderate",
+            Self::Severe => "Severe",
+            Self::Extreme => "Extreme",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Alert urgency following the Common Alerting Protocol (CAP) scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum AlertUrgency {
+    /// Urgency not known or not given by the issuer
+    #[default]
+    Unknown,
+    /// Responsive action is no longer required
+    Past,
+    /// Responsive action should be taken in the near future
+    Future,
+    /// Responsive action should be taken soon (within the next hour)
+    Expected,
+    /// Responsive action should be taken immediately
+    Immediate,
+}
+
+impl AlertUrgency {
+    /// Parse a CAP urgency value, treating anything unrecognised as `Unknown`
+    pub fn from_cap(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "past" => Self::Past,
+            "future" => Self::Future,
+            "expected" => Self::Expected,
+            "immediate" => Self::Immediate,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for AlertUrgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Unknown => "Unknown",
+            Self::Past => "Past",
+            Self::Future => "Future",
+            Self::Expected => "Expected",
+            Self::Immediate => "Immediate",
+        };
+        write!(f, "{}", s)
+    }
+}
@@ -0,0 +1,332 @@
+//! Units of measurement for presenting weather values.
+//!
+//! Domain values are always stored in canonical metric units (°C, km/h, hPa, mm, km); a
+//! `UnitSystem` only describes how they are converted for display.
+
+use super::error::WeatherError;
+use std::str::FromStr;
+
+/// Lowest wind speed (km/h) of each Beaufort force from 0 to 12
+const BEAUFORT_KMH: [f64; 13] = [
+    0.0, 1.0, 6.0, 12.0, 20.0, 29.0, 39.0, 50.0, 62.0, 75.0, 89.0, 103.0, 118.0,
+];
+
+/// Unit for displaying temperatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    /// Convert a temperature in °C to this unit
+    pub fn convert_celsius(self, celsius: f64) -> f64 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            Self::Kelvin => celsius + 273.15,
+        }
+    }
+
+    /// Convert a temperature in this unit to °C
+    pub fn to_celsius(self, value: f64) -> f64 {
+        match self {
+            Self::Celsius => value,
+            Self::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Self::Kelvin => value - 273.15,
+        }
+    }
+
+    /// Symbol written after a value, including any separating space
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => " K",
+        }
+    }
+}
+
+impl FromStr for TemperatureUnit {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "c" | "celsius" => Ok(Self::Celsius),
+            "f" | "fahrenheit" => Ok(Self::Fahrenheit),
+            "k" | "kelvin" => Ok(Self::Kelvin),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "celsius, fahrenheit, kelvin",
+            }),
+        }
+    }
+}
+
+/// Unit for displaying wind speeds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpeedUnit {
+    #[default]
+    KilometresPerHour,
+    MilesPerHour,
+    MetresPerSecond,
+    Knots,
+    /// Beaufort force number (0 to 12)
+    Beaufort,
+}
+
+impl SpeedUnit {
+    /// Convert a speed in km/h to this unit
+    pub fn convert_kmh(self, kmh: f64) -> f64 {
+        match self {
+            Self::KilometresPerHour => kmh,
+            Self::MilesPerHour => kmh / 1.609344,
+            Self::MetresPerSecond => kmh / 3.6,
+            Self::Knots => kmh / 1.852,
+            Self::Beaufort => BEAUFORT_KMH
+                .iter()
+                .rposition(|&lowest| kmh.round() >= lowest)
+                .unwrap_or(0) as f64,
+        }
+    }
+
+    /// Convert a speed in this unit to km/h; a Beaufort force maps to its lowest speed
+    pub fn to_kmh(self, value: f64) -> f64 {
+        match self {
+            Self::KilometresPerHour => value,
+            Self::MilesPerHour => value * 1.609344,
+            Self::MetresPerSecond => value * 3.6,
+            Self::Knots => value * 1.852,
+            Self::Beaufort => BEAUFORT_KMH[(value.max(0.0) as usize).min(12)],
+        }
+    }
+
+    /// Symbol written after a value
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::KilometresPerHour => "km/h",
+            Self::MilesPerHour => "mph",
+            Self::MetresPerSecond => "m/s",
+            Self::Knots => "kn",
+            Self::Beaufort => "Bft",
+        }
+    }
+}
+
+impl FromStr for SpeedUnit {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "kmh" | "km/h" | "kph" => Ok(Self::KilometresPerHour),
+            "mph" => Ok(Self::MilesPerHour),
+            "ms" | "m/s" => Ok(Self::MetresPerSecond),
+            "kn" | "kt" | "knots" => Ok(Self::Knots),
+            "bft" | "beaufort" => Ok(Self::Beaufort),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "kmh, mph, ms, knots, beaufort",
+            }),
+        }
+    }
+}
+
+/// Unit for displaying atmospheric pressure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PressureUnit {
+    #[default]
+    Hectopascals,
+    InchesOfMercury,
+    MillimetresOfMercury,
+}
+
+impl PressureUnit {
+    /// Convert a pressure in hPa to this unit
+    pub fn convert_hpa(self, hpa: f64) -> f64 {
+        match self {
+            Self::Hectopascals => hpa,
+            Self::InchesOfMercury => hpa / 33.8639,
+            Self::MillimetresOfMercury => hpa / 1.333224,
+        }
+    }
+
+    /// Decimal places worth showing; inches of mercury need two to be useful
+    pub fn precision(self) -> usize {
+        match self {
+            Self::InchesOfMercury => 2,
+            Self::Hectopascals | Self::MillimetresOfMercury => 0,
+        }
+    }
+
+    /// Symbol written after a value
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Hectopascals => "hPa",
+            Self::InchesOfMercury => "inHg",
+            Self::MillimetresOfMercury => "mmHg",
+        }
+    }
+}
+
+impl FromStr for PressureUnit {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "hpa" | "mbar" | "mb" => Ok(Self::Hectopascals),
+            "inhg" => Ok(Self::InchesOfMercury),
+            "mmhg" => Ok(Self::MillimetresOfMercury),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "hpa, inhg, mmhg",
+            }),
+        }
+    }
+}
+
+/// Unit for displaying precipitation amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrecipitationUnit {
+    #[default]
+    Millimetres,
+    Inches,
+}
+
+impl PrecipitationUnit {
+    /// Convert an amount in mm to this unit
+    pub fn convert_mm(self, mm: f64) -> f64 {
+        match self {
+            Self::Millimetres => mm,
+            Self::Inches => mm / 25.4,
+        }
+    }
+
+    /// Decimal places worth showing; inches need two to be useful
+    pub fn precision(self) -> usize {
+        match self {
+            Self::Millimetres => 1,
+            Self::Inches => 2,
+        }
+    }
+
+    /// Symbol written after a value
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Millimetres => "mm",
+            Self::Inches => "in",
+        }
+    }
+}
+
+impl FromStr for PrecipitationUnit {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "mm" | "millimetres" | "millimeters" => Ok(Self::Millimetres),
+            "in" | "inches" => Ok(Self::Inches),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "mm, in",
+            }),
+        }
+    }
+}
+
+/// Unit for displaying distances such as visibility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceUnit {
+    #[default]
+    Kilometres,
+    Miles,
+}
+
+impl DistanceUnit {
+    /// Convert a distance in km to this unit
+    pub fn convert_km(self, km: f64) -> f64 {
+        match self {
+            Self::Kilometres => km,
+            Self::Miles => km / 1.609344,
+        }
+    }
+
+    /// Symbol written after a value
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Kilometres => "km",
+            Self::Miles => "mi",
+        }
+    }
+}
+
+impl FromStr for DistanceUnit {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "km" | "kilometres" | "kilometers" => Ok(Self::Kilometres),
+            "mi" | "miles" => Ok(Self::Miles),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "km, mi",
+            }),
+        }
+    }
+}
+
+/// Units used to display each kind of measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UnitSystem {
+    pub temperature: TemperatureUnit,
+    pub speed: SpeedUnit,
+    pub pressure: PressureUnit,
+    pub precipitation: PrecipitationUnit,
+    pub distance: DistanceUnit,
+}
+
+impl UnitSystem {
+    /// °C, km/h, hPa, mm and km
+    pub const METRIC: Self = Self {
+        temperature: TemperatureUnit::Celsius,
+        speed: SpeedUnit::KilometresPerHour,
+        pressure: PressureUnit::Hectopascals,
+        precipitation: PrecipitationUnit::Millimetres,
+        distance: DistanceUnit::Kilometres,
+    };
+
+    /// °F, mph, inHg, inches and miles, as used in the United States
+    pub const IMPERIAL: Self = Self {
+        temperature: TemperatureUnit::Fahrenheit,
+        speed: SpeedUnit::MilesPerHour,
+        pressure: PressureUnit::InchesOfMercury,
+        precipitation: PrecipitationUnit::Inches,
+        distance: DistanceUnit::Miles,
+    };
+
+    /// °C, mph, hPa, mm and miles, as used in the United Kingdom
+    pub const UK: Self = Self {
+        temperature: TemperatureUnit::Celsius,
+        speed: SpeedUnit::MilesPerHour,
+        pressure: PressureUnit::Hectopascals,
+        precipitation: PrecipitationUnit::Millimetres,
+        distance: DistanceUnit::Miles,
+    };
+}
+
+impl FromStr for UnitSystem {
+    type Err = WeatherError;
+
+    fn from_str(s: &str) -> Result<Self, WeatherError> {
+        match s.trim().to_lowercase().as_str() {
+            "metric" => Ok(Self::METRIC),
+            "imperial" | "us" => Ok(Self::IMPERIAL),
+            "uk" => Ok(Self::UK),
+            _ => Err(WeatherError::UnknownUnit {
+                value: s.trim().to_string(),
+                expected: "metric, imperial, uk",
+            }),
+        }
+    }
+}
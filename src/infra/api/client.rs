@@ -1,11 +1,15 @@
 //! HTTP client for fetching weather data from WeatherAPI.com API.
 
-use crate::app::WeatherFetcher;
-use crate::infra::api::models::WeatherApiResponse;
-use crate::domain::models::WeatherData;
+use crate::app::{LocationSearcher, WeatherFetcher};
+use crate::domain::models::{PlaceMatch, WeatherData};
+use crate::domain::LocationQuery;
+use crate::infra::api::models::{SearchResultApi, WeatherApiResponse};
+use crate::infra::api::{http_agent, DEFAULT_TIMEOUT};
 
 use anyhow::{Context, Result};
-use std::time::Duration;
+
+/// Days of forecast requested; the most WeatherAPI.com's free plan provides
+const FORECAST_DAYS: u32 = 3;
 
 /// Weather API client for WeatherAPI.com service
 pub struct WeatherClient {
@@ -15,15 +19,20 @@ pub struct WeatherClient {
 }
 
 impl WeatherClient {
+    /// Service name shown to the user
+    pub const NAME: &'static str = "WeatherAPI.com";
+
     /// Create a new weather client with API key from environment
     pub fn new() -> Result<Self> {
+        Self::with_agent(http_agent(DEFAULT_TIMEOUT))
+    }
+
+    /// Create a weather client with API key from environment, issuing requests
+    /// through the given agent
+    pub fn with_agent(agent: ureq::Agent) -> Result<Self> {
         let api_key = std::env::var("WEATHER_API_KEY")
             .context("WEATHER_API_KEY environment variable not set. Get your free API key from https://www.weatherapi.com/")?;
 
-        let agent = ureq::AgentBuilder::new()
-            .timeout(Duration::from_secs(10))
-            .build();
-
         Ok(Self {
             agent,
             base_url: "http://api.weatherapi.com/v1".to_string(),
@@ -34,32 +43,66 @@ impl WeatherClient {
     /// Create a new weather client with explicit API key (for testing)
     #[cfg(test)]
     pub fn with_api_key(api_key: String) -> Self {
-        let agent = ureq::AgentBuilder::new()
-            .timeout(Duration::from_secs(10))
-            .build();
-
         Self {
-            agent,
+            agent: http_agent(DEFAULT_TIMEOUT),
             base_url: "http://api.weatherapi.com/v1".to_string(),
             api_key,
         }
     }
 
     /// Fetch weather data for a location
-    pub fn fetch_weather(&self, location: &str) -> Result<WeatherData> {
-        // Use forecast endpoint with days=1 to get current weather + today's astronomy/hourly data
+    pub fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        // The forecast endpoint gives current weather, air quality and alerts, today's
+        // astronomy/hourly data and the daily summaries
+        let url = format!(
+            "{}/forecast.json?key={}&q={}&days={}&aqi=yes&alerts=yes",
+            self.base_url,
+            self.api_key,
+            self.format_location(location),
+            FORECAST_DAYS
+        );
+
+        let api_response: WeatherApiResponse = self
+            .get(&url)?
+            .into_json()
+            .context("Failed to parse JSON response from weather API")?;
+
+        api_response
+            .try_into()
+            .context("Failed to convert API response to domain model")
+    }
+
+    /// Look up to `limit` places matching a name, best match first
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<PlaceMatch>> {
         let url = format!(
-            "{}/forecast.json?key={}&q={}&days=1&aqi=no&alerts=no",
+            "{}/search.json?key={}&q={}",
             self.base_url,
             self.api_key,
-            self.format_location(location)
+            urlencoding::encode(query.trim())
         );
 
+        let results: Vec<SearchResultApi> = self
+            .get(&url)?
+            .into_json()
+            .context("Failed to parse search results from weather API")?;
+
+        Ok(results
+            .into_iter()
+            .take(limit)
+            .map(PlaceMatch::from)
+            .collect())
+    }
+
+    /// Send a GET request, failing on any status other than 200
+    ///
+    /// The API key is redacted from errors, whose text ureq prefixes with the URL.
+    fn get(&self, url: &str) -> Result<ureq::Response> {
         let response = self
             .agent
-            .get(&url)
+            .get(url)
             .call()
-            .with_context(|| format!("Failed to send request to: {}", url))?;
+            .map_err(|error| anyhow::anyhow!(redact_key(&error.to_string())))
+            .with_context(|| format!("Failed to send request to: {}", redact_key(url)))?;
 
         if response.status() != 200 {
             let status = response.status();
@@ -67,24 +110,51 @@ impl WeatherClient {
             anyhow::bail!(
                 "Weather API returned error status {}: {}. Response: {}",
                 status,
-                url,
+                redact_key(url),
                 error_text
             );
         }
+        Ok(response)
+    }
 
-        let api_response: WeatherApiResponse = response
-            .into_json()
-            .context("Failed to parse JSON response from weather API")?;
-
-        api_response
-            .try_into()
-            .context("Failed to convert API response to domain model")
+    /// Translate a location into WeatherAPI.com's `q` syntax, encoded for the URL
+    fn format_location(&self, location: &LocationQuery) -> String {
+        let query = match location {
+            LocationQuery::Coordinates(coordinates) => {
+                format!("{},{}", coordinates.latitude(), coordinates.longitude())
+            }
+            LocationQuery::Postcode(postcode) => postcode.clone(),
+            LocationQuery::Airport(code) => format!("iata:{}", code),
+            LocationQuery::Id(id) => format!("id:{}", id),
+            LocationQuery::Name(name) => name.clone(),
+            // Normally resolved before it gets here; WeatherAPI.com can instead place the
+            // request by its IP address
+            LocationQuery::Current => "auto:ip".to_string(),
+        };
+        urlencoding::encode(&query).to_string()
     }
+}
 
-    /// Format location for URL (encode spaces and special characters)
-    fn format_location(&self, location: &str) -> String {
-        urlencoding::encode(location.trim()).to_string()
+/// Replace the value of every `key` query parameter in `text`, so that errors quoting a
+/// request URL do not leak the API key
+fn redact_key(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = ["?key=", "&key="]
+        .iter()
+        .filter_map(|prefix| rest.find(prefix).map(|index| index + prefix.len()))
+        .min()
+    {
+        redacted.push_str(&rest[..start]);
+        redacted.push_str("[REDACTED]");
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| matches!(c, '&' | '#' | ':') || c.is_whitespace())
+            .unwrap_or(rest.len());
+        rest = &rest[end..];
     }
+    redacted.push_str(rest);
+    redacted
 }
 
 impl std::fmt::Debug for WeatherClient {
@@ -100,12 +170,8 @@ impl Default for WeatherClient {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| {
             // Fallback for tests or when API key is not available
-            let agent = ureq::AgentBuilder::new()
-                .timeout(Duration::from_secs(10))
-                .build();
-
             Self {
-                agent,
+                agent: http_agent(DEFAULT_TIMEOUT),
                 base_url: "http://api.weatherapi.com/v1".to_string(),
                 api_key: "test_key".to_string(),
             }
@@ -114,23 +180,62 @@ impl Default for WeatherClient {
 }
 
 impl WeatherFetcher for WeatherClient {
-    fn fetch_weather(&self, location: &str) -> Result<WeatherData> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
         self.fetch_weather(location)
     }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+}
+
+impl LocationSearcher for WeatherClient {
+    fn search_places(&self, query: &str, limit: usize) -> Result<Vec<PlaceMatch>> {
+        self.search(query, limit)
+    }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
 
     #[test]
     fn test_format_location() {
         let client = WeatherClient::with_api_key("test_key".to_string());
+        let format = |location: &str| client.format_location(&location.parse().unwrap());
+
+        assert_eq!(format("Wellington"), "Wellington");
+        assert_eq!(format("New York"), "New%20York");
+        assert_eq!(format(" London "), "London");
+        assert_eq!(format("São Paulo"), "S%C3%A3o%20Paulo");
+        assert_eq!(format("-41.2866, 174.7756"), "-41.2866%2C174.7756");
+        assert_eq!(format("sw1a1aa"), "SW1A%201AA");
+        assert_eq!(format("iata:wlg"), "iata%3AWLG");
+        assert_eq!(format("id:2801268"), "id%3A2801268");
+        assert_eq!(client.format_location(&LocationQuery::Current), "auto%3Aip");
+    }
 
-        assert_eq!(client.format_location("Wellington"), "Wellington");
-        assert_eq!(client.format_location("New York"), "New%20York");
-        assert_eq!(client.format_location(" London "), "London");
-        assert_eq!(client.format_location("São Paulo"), "S%C3%A3o%20Paulo");
+    #[test]
+    fn test_redact_key() {
+        assert_eq!(
+            redact_key("http://host/v1/forecast.json?key=s3cret&q=Wellington&days=3"),
+            "http://host/v1/forecast.json?key=[REDACTED]&q=Wellington&days=3"
+        );
+        assert_eq!(
+            redact_key("http://host/v1/search.json?key=s3cret: status code 401"),
+            "http://host/v1/search.json?key=[REDACTED]: status code 401"
+        );
+        assert_eq!(
+            redact_key("http://host/v1/search.json?q=monkey=1&key=s3cret"),
+            "http://host/v1/search.json?q=monkey=1&key=[REDACTED]"
+        );
+        assert_eq!(redact_key("no url here"), "no url here");
     }
 
     #[test]
@@ -181,7 +286,7 @@ mod tests {
         if let Ok(api_key) = std::env::var("WEATHER_API_KEY") {
             let client = WeatherClient::with_api_key(api_key);
 
-            match client.fetch_weather("Wellington") {
+            match client.fetch_weather(&LocationQuery::Name("Wellington".to_string())) {
                 Ok(weather_data) => {
                     // Basic validation that we got weather data
                     assert!(!weather_data.location.to_string().is_empty());
@@ -201,22 +306,39 @@ mod tests {
 
     #[test]
     fn test_fetch_weather_invalid_api_key() {
-        // Skip in CI environments
-        if std::env::var("CI").is_ok() {
-            return;
-        }
+        // Answer the one request the way WeatherAPI.com rejects an unknown key
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let body = r#"{"error":{"code":2006,"message":"API key is invalid."}}"#;
+            let response = format!(
+                "HTTP/1.1 401 Unauthorized\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
 
-        let client = WeatherClient::with_api_key("invalid_key".to_string());
+        let mut client = WeatherClient::with_api_key("invalid_key".to_string());
+        client.base_url = base_url;
 
-        let result = client.fetch_weather("Wellington");
+        let result = client.fetch_weather(&LocationQuery::Name("Wellington".to_string()));
         assert!(result.is_err());
 
-        let error_message = result.unwrap_err().to_string();
-        // WeatherAPI returns 401 or 403 for invalid API key
+        let error_message = format!("{:#}", result.unwrap_err());
+        assert!(error_message.contains("401"), "{}", error_message);
+        // The URL in the error is shown to the user, so the key must not be
         assert!(
-            error_message.contains("401")
-                || error_message.contains("403")
-                || error_message.contains("invalid")
+            error_message.contains("key=[REDACTED]"),
+            "{}",
+            error_message
         );
+        assert!(!error_message.contains("invalid_key"), "{}", error_message);
     }
 }
@@ -0,0 +1,250 @@
+//! Conditional HTTP GET backed by an on-disk response cache.
+//!
+//! Some public data services (MET Norway in particular) require clients to honour the
+//! `Expires` header and to revalidate with `If-Modified-Since` rather than re-download
+//! unchanged data. Waybar runs this program as a fresh process on every poll, so the
+//! cache has to live on disk to be of any use.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use time::{macros::format_description, OffsetDateTime, PrimitiveDateTime};
+
+/// A cached response body with its freshness metadata
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    /// Unix timestamp after which the body must be revalidated
+    expires: i64,
+    /// `Last-Modified` header as sent by the server, echoed back verbatim
+    last_modified: Option<String>,
+    body: String,
+}
+
+/// On-disk cache of HTTP response bodies keyed by URL
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    /// Create a cache storing its entries in `dir`
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Fetch `url`, serving the cached body while it is fresh and revalidating it
+    /// with `If-Modified-Since` once it has expired
+    pub fn get(&self, agent: &ureq::Agent, url: &str) -> Result<String> {
+        self.get_at(agent, url, OffsetDateTime::now_utc())
+    }
+
+    fn get_at(&self, agent: &ureq::Agent, url: &str, now: OffsetDateTime) -> Result<String> {
+        let cached = self.load(url);
+
+        if let Some(entry) = &cached {
+            if now.unix_timestamp() < entry.expires {
+                return Ok(entry.body.clone());
+            }
+        }
+
+        let mut request = agent.get(url);
+        if let Some(last_modified) = cached.as_ref().and_then(|e| e.last_modified.as_deref()) {
+            request = request.set("If-Modified-Since", last_modified);
+        }
+
+        let response = request
+            .call()
+            .with_context(|| format!("Failed to send request to: {}", url))?;
+
+        let expires = response
+            .header("Expires")
+            .and_then(parse_http_date)
+            .map_or(now.unix_timestamp(), |expires| expires.unix_timestamp());
+
+        let entry = match (response.status(), cached) {
+            (304, Some(mut entry)) => {
+                entry.expires = expires;
+                entry
+            }
+            (304, None) => anyhow::bail!("Server returned 304 Not Modified for uncached {}", url),
+            _ => CachedResponse {
+                expires,
+                last_modified: response.header("Last-Modified").map(str::to_string),
+                body: response
+                    .into_string()
+                    .with_context(|| format!("Failed to read response body from: {}", url))?,
+            },
+        };
+
+        // Caching is best effort: a read-only or full disk must not break fetching
+        let _ = self.store(url, &entry);
+        Ok(entry.body)
+    }
+
+    /// Path of the cache file for a URL
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let key: String = url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("http-{}.json", key))
+    }
+
+    fn load(&self, url: &str) -> Option<CachedResponse> {
+        let contents = std::fs::read_to_string(self.entry_path(url)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn store(&self, url: &str, entry: &CachedResponse) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.entry_path(url), serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+/// Parse an HTTP-date in IMF-fixdate form (e.g., "Fri, 13 Jan 2023 01:30:00 GMT")
+pub fn parse_http_date(value: &str) -> Option<OffsetDateTime> {
+    let format = format_description!(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+    );
+    PrimitiveDateTime::parse(value.trim(), &format)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Serve the given raw HTTP responses in order, reporting each request's headers
+    fn serve(responses: Vec<String>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/forecast", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                tx.send(request).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let mut raw = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\n", status, body.len());
+        for (name, value) in headers {
+            raw.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        raw.push_str("Connection: close\r\n\r\n");
+        raw.push_str(body);
+        raw
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let parsed = parse_http_date("Fri, 13 Jan 2023 01:30:00 GMT").unwrap();
+        assert_eq!(parsed.unix_timestamp(), 1673573400);
+    }
+
+    #[test]
+    fn test_http_date_invalid() {
+        assert!(parse_http_date("yesterday").is_none());
+        assert!(parse_http_date("2023-01-13T01:30:00Z").is_none());
+    }
+
+    #[test]
+    fn test_fresh_entry_served_without_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path().to_path_buf());
+        let now = parse_http_date("Fri, 13 Jan 2023 01:30:00 GMT").unwrap();
+        let (url, requests) = serve(vec![response(
+            "200 OK",
+            &[
+                ("Expires", "Fri, 13 Jan 2023 02:00:00 GMT"),
+                ("Last-Modified", "Fri, 13 Jan 2023 01:00:00 GMT"),
+            ],
+            "first",
+        )]);
+        let agent = ureq::agent();
+
+        assert_eq!(cache.get_at(&agent, &url, now).unwrap(), "first");
+        assert!(requests.recv().is_ok());
+
+        // Still before Expires: served from disk, no second request reaches the server
+        let later = now + time::Duration::minutes(10);
+        assert_eq!(cache.get_at(&agent, &url, later).unwrap(), "first");
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_expired_entry_revalidated_with_if_modified_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path().to_path_buf());
+        let now = parse_http_date("Fri, 13 Jan 2023 01:30:00 GMT").unwrap();
+        let (url, requests) = serve(vec![
+            response(
+                "200 OK",
+                &[
+                    ("Expires", "Fri, 13 Jan 2023 01:40:00 GMT"),
+                    ("Last-Modified", "Fri, 13 Jan 2023 01:00:00 GMT"),
+                ],
+                "original",
+            ),
+            response(
+                "304 Not Modified",
+                &[("Expires", "Fri, 13 Jan 2023 02:30:00 GMT")],
+                "",
+            ),
+        ]);
+        let agent = ureq::agent();
+
+        assert_eq!(cache.get_at(&agent, &url, now).unwrap(), "original");
+        let first = requests.recv().unwrap();
+        assert!(!first.to_lowercase().contains("if-modified-since"));
+
+        let expired = now + time::Duration::hours(1);
+        assert_eq!(cache.get_at(&agent, &url, expired).unwrap(), "original");
+        let second = requests.recv().unwrap();
+        assert!(second
+            .to_lowercase()
+            .contains("if-modified-since: fri, 13 jan 2023 01:00:00 gmt"));
+
+        // The 304's Expires header extended the entry's lifetime
+        let entry = cache.load(&url).unwrap();
+        assert_eq!(entry.expires, 1673573400 + 3600);
+    }
+
+    #[test]
+    fn test_entry_path_is_filesystem_safe() {
+        let cache = HttpCache::new(PathBuf::from("/cache"));
+        let path = cache.entry_path("https://api.met.no/weatherapi/x?lat=-41.2866&lon=174.7756");
+        assert_eq!(
+            path,
+            PathBuf::from("/cache/http-api.met.no_weatherapi_x_lat_-41.2866_lon_174.7756.json")
+        );
+    }
+}
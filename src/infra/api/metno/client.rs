@@ -0,0 +1,181 @@
+//! HTTP client for the MET Norway (api.met.no) locationforecast and sunrise services.
+//!
+//! MET Norway's terms of service require an identifying `User-Agent`, coordinates
+//! truncated to four decimals, and respect for the `Expires` header; responses are
+//! therefore fetched through the on-disk `HttpCache`.
+
+use crate::app::WeatherFetcher;
+use crate::domain::models::WeatherData;
+use crate::domain::LocationQuery;
+use crate::infra::api::http_cache::HttpCache;
+use crate::infra::api::metno::models::{LocationForecastApi, MetNoResponse, SunriseApi};
+use crate::infra::api::openmeteo::Geocoder;
+use crate::infra::api::{http_agent, DEFAULT_TIMEOUT};
+use crate::infra::paths;
+
+use anyhow::{Context, Result};
+use time::{macros::format_description, OffsetDateTime};
+use time_tz::{timezones, OffsetDateTimeExt};
+
+const FORECAST_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0/compact";
+const SUNRISE_URL: &str = "https://api.met.no/weatherapi/sunrise/3.0/sun";
+
+/// Weather API client for MET Norway's public forecast data (no API key required)
+pub struct MetNoClient {
+    agent: ureq::Agent,
+    geocoder: Geocoder,
+    cache: HttpCache,
+    forecast_url: String,
+    sunrise_url: String,
+}
+
+impl MetNoClient {
+    /// Service name shown to the user
+    pub const NAME: &'static str = "MET Norway";
+
+    /// Create a new MET Norway client caching responses under the XDG cache directory
+    pub fn new() -> Self {
+        Self::with_agent(http_agent(DEFAULT_TIMEOUT))
+    }
+
+    /// Create a client that issues requests through the given agent, which must send
+    /// an identifying `User-Agent`
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        let cache_dir =
+            paths::cache_dir().unwrap_or_else(|_| std::env::temp_dir().join("waybar_weather"));
+
+        Self {
+            geocoder: Geocoder::new(agent.clone()),
+            agent,
+            cache: HttpCache::new(cache_dir),
+            forecast_url: FORECAST_URL.to_string(),
+            sunrise_url: SUNRISE_URL.to_string(),
+        }
+    }
+
+    /// Fetch weather data for a location by geocoding it first
+    pub fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        let place = self.geocoder.geocode(location)?;
+        let now = OffsetDateTime::now_utc();
+
+        let body = self
+            .cache
+            .get(
+                &self.agent,
+                &self.forecast_request_url(place.latitude, place.longitude),
+            )
+            .context("Failed to fetch MET Norway forecast")?;
+        let forecast: LocationForecastApi =
+            serde_json::from_str(&body).context("Failed to parse JSON response from MET Norway")?;
+
+        // Sunrise data is supplementary; a failure here should not hide the forecast
+        let local_now = match place.timezone.as_deref().and_then(timezones::get_by_name) {
+            Some(tz) => now.to_timezone(tz),
+            None => now,
+        };
+        let sun = self
+            .cache
+            .get(
+                &self.agent,
+                &self.sunrise_request_url(place.latitude, place.longitude, local_now),
+            )
+            .ok()
+            .and_then(|body| serde_json::from_str::<SunriseApi>(&body).ok());
+
+        MetNoResponse {
+            place,
+            forecast,
+            sun,
+            now,
+        }
+        .try_into()
+        .context("Failed to convert API response to domain model")
+    }
+
+    /// Build the locationforecast URL; MET asks for at most four decimals
+    fn forecast_request_url(&self, latitude: f64, longitude: f64) -> String {
+        format!(
+            "{}?lat={:.4}&lon={:.4}",
+            self.forecast_url, latitude, longitude
+        )
+    }
+
+    /// Build the sunrise URL for the location's current local date and UTC offset
+    fn sunrise_request_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        local_now: OffsetDateTime,
+    ) -> String {
+        let date = local_now
+            .format(format_description!("[year]-[month]-[day]"))
+            .unwrap_or_default();
+        let offset = local_now
+            .offset()
+            .format(format_description!(
+                "[offset_hour sign:mandatory]:[offset_minute]"
+            ))
+            .unwrap_or_default();
+
+        format!(
+            "{}?lat={:.4}&lon={:.4}&date={}&offset={}",
+            self.sunrise_url,
+            latitude,
+            longitude,
+            date,
+            urlencoding::encode(&offset)
+        )
+    }
+}
+
+impl std::fmt::Debug for MetNoClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetNoClient")
+            .field("forecast_url", &self.forecast_url)
+            .field("sunrise_url", &self.sunrise_url)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl Default for MetNoClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherFetcher for MetNoClient {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        self.fetch_weather(location)
+    }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_forecast_request_url_truncates_coordinates() {
+        let client = MetNoClient::new();
+        assert_eq!(
+            client.forecast_request_url(-41.28664, 174.77557),
+            "https://api.met.no/weatherapi/locationforecast/2.0/compact?lat=-41.2866&lon=174.7756"
+        );
+    }
+
+    #[test]
+    fn test_sunrise_request_url_uses_local_date_and_offset() {
+        let client = MetNoClient::new();
+        let url =
+            client.sunrise_request_url(-41.28664, 174.77557, datetime!(2023-01-13 14:30 +13:00));
+        assert_eq!(
+            url,
+            "https://api.met.no/weatherapi/sunrise/3.0/sun?lat=-41.2866&lon=174.7756&date=2023-01-13&offset=%2B13%3A00"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! MET Norway provider backed by the public api.met.no locationforecast service.
+
+pub mod client;
+pub mod models;
+
+pub use client::*;
+
+#[cfg(test)]
+mod tests {
+    use super::models::*;
+    use crate::domain::models::*;
+    use crate::domain::{ConditionKind, WindDirection};
+    use crate::infra::api::openmeteo::models::GeocodingResultApi;
+    use time::macros::datetime;
+
+    const FORECAST_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/metno/compact_wellington.json");
+    const SUN_FIXTURE: &str = include_str!("../../../../tests/fixtures/metno/sun_wellington.json");
+    const POLAR_NIGHT_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/metno/sun_polar_night.json");
+
+    fn wellington() -> GeocodingResultApi {
+        GeocodingResultApi {
+            name: "Wellington".to_string(),
+            latitude: -41.28664,
+            longitude: 174.77557,
+            timezone: Some("Pacific/Auckland".to_string()),
+            admin1: Some("Wellington".to_string()),
+            country: Some("New Zealand".to_string()),
+        }
+    }
+
+    fn fixture_weather_data(sun_fixture: Option<&str>) -> WeatherData {
+        MetNoResponse {
+            place: wellington(),
+            forecast: serde_json::from_str(FORECAST_FIXTURE).expect("Valid forecast JSON"),
+            sun: sun_fixture.map(|json| serde_json::from_str(json).expect("Valid sun JSON")),
+            // 14:30 local time in Wellington (NZDT, UTC+13)
+            now: datetime!(2023-01-13 01:30 UTC),
+        }
+        .try_into()
+        .expect("Valid domain conversion")
+    }
+
+    #[test]
+    fn test_current_conditions_from_latest_started_step() {
+        let weather_data = fixture_weather_data(Some(SUN_FIXTURE));
+        let current = &weather_data.current;
+
+        assert_eq!(weather_data.location.to_string(), "Wellington");
+        assert_eq!(weather_data.source, "MET Norway");
+        assert_eq!(current.temperature.as_celsius(), 20);
+        assert_eq!(current.humidity.as_int(), 61);
+        assert_eq!(current.pressure.value(), 1013);
+        assert_eq!(current.cloud_cover.map(|c| c.as_percent()), Some(26));
+        // UV comes only with the complete product, visibility not at all
+        assert!(current.uv_index.is_none());
+        assert!(current.visibility.is_none());
+        assert_eq!(current.condition.to_string(), "Partly cloudy");
+        assert_eq!(current.condition.is_day(), Some(true));
+        assert_eq!(current.wind_direction, WindDirection::NW);
+        // 4.6 m/s is 16.56 km/h
+        assert_eq!(current.wind_speed.to_string(), "17 km/h");
+        assert_eq!(current.feels_like.as_celsius(), 17);
+        assert_eq!(current.last_updated.to_string(), "2023-01-13 01:12Z");
+    }
+
+    #[test]
+    fn test_hourly_runs_past_local_midnight() {
+        let weather_data = fixture_weather_data(Some(SUN_FIXTURE));
+        let hours = &weather_data.weather_day.as_ref().unwrap().hourly_weather;
+
+        // From 14:00 local time (01:00Z) to the last step with a next-hour summary,
+        // 18:00 the following day (05:00Z)
+        assert_eq!(hours.len(), 29);
+        assert_eq!(hours[0].time, datetime!(2023-01-13 14:00 +13));
+        assert_eq!(hours[0].condition.to_string(), "Partly cloudy");
+        assert_eq!(hours[5].condition.to_string(), "Rain and thunder");
+        assert_eq!(hours[5].condition.kind(), ConditionKind::Thunderstorm);
+        // Symbols for wet weather have no day or night variant
+        assert_eq!(hours[5].condition.is_day(), None);
+        // The compact product gives amounts but no chance of precipitation
+        assert_eq!(hours[4].precipitation.map(|p| p.as_mm()), Some(0.6));
+        assert!(hours[4].chance_of_rain.is_none());
+        assert!(hours[4].rain_likely());
+        assert!(!hours[0].rain_likely());
+        assert_eq!(hours[10].time, datetime!(2023-01-14 00:00 +13));
+        assert_eq!(hours.last().unwrap().time, datetime!(2023-01-14 18:00 +13));
+        assert_eq!(weather_data.local_time, datetime!(2023-01-13 14:30 +13));
+    }
+
+    #[test]
+    fn test_astronomy_from_sunrise_service() {
+        let weather_data = fixture_weather_data(Some(SUN_FIXTURE));
+        let astronomy = weather_data
+            .weather_day
+            .as_ref()
+            .and_then(|day| day.astronomy.as_ref())
+            .expect("Astronomy present");
+
+        assert_eq!(astronomy.sunrise().format_24h(), "05:58");
+        assert_eq!(astronomy.sunset().format_24h(), "20:55");
+    }
+
+    #[test]
+    fn test_astronomy_absent_during_polar_night() {
+        let weather_data = fixture_weather_data(Some(POLAR_NIGHT_FIXTURE));
+        assert!(weather_data.weather_day.unwrap().astronomy.is_none());
+    }
+
+    #[test]
+    fn test_astronomy_absent_without_sun_data() {
+        let weather_data = fixture_weather_data(None);
+        assert!(weather_data.weather_day.unwrap().astronomy.is_none());
+    }
+
+    #[test]
+    fn test_symbol_description() {
+        assert_eq!(symbol_description("clearsky_day"), "Clear sky");
+        assert_eq!(symbol_description("clearsky_night"), "Clear sky");
+        assert_eq!(symbol_description("fair_polartwilight"), "Fair");
+        assert_eq!(
+            symbol_description("heavyrainandthunder"),
+            "Heavy rain and thunder"
+        );
+        assert_eq!(
+            symbol_description("lightssnowshowersandthunder_day"),
+            "Light snow showers and thunder"
+        );
+        assert_eq!(symbol_description("volcanicash"), "Unknown");
+
+        assert_eq!(symbol_kind("fair_night"), ConditionKind::PartlyCloudy);
+        assert_eq!(
+            symbol_kind("heavyrainshowers_day"),
+            ConditionKind::RainShowers
+        );
+        assert_eq!(
+            symbol_kind("lightssleetshowersandthunder_day"),
+            ConditionKind::Thunderstorm
+        );
+        assert_eq!(symbol_kind("sleetshowers_night"), ConditionKind::Sleet);
+        assert_eq!(symbol_kind("volcanicash"), ConditionKind::Unknown);
+    }
+}
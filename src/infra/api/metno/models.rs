@@ -0,0 +1,447 @@
+//! API models for deserializing MET Norway (api.met.no) responses and converting to
+//! domain types.
+//!
+//! The locationforecast product is a GeoJSON feature whose `timeseries` holds one entry
+//! per forecast step. Each step has an `instant` block of point values plus summaries
+//! for the following 1, 6 and 12 hours. Times are UTC; the location's IANA zone comes
+//! from geocoding and is used to present local times.
+
+use crate::domain::{
+    Astronomy, CloudCover, ConditionKind, CurrentWeather, HourlyWeather, Humidity, LastUpdated,
+    Precipitation, Pressure, Probability, Temperature, UvIndex, WeatherCondition, WeatherData,
+    WeatherDay, WeatherTime, WindDirection, WindSpeed,
+};
+use crate::infra::api::metno::MetNoClient;
+use crate::infra::api::openmeteo::models::GeocodingResultApi;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::{macros::format_description, OffsetDateTime};
+use time_tz::{timezones, OffsetDateTimeExt};
+
+/// Root locationforecast response from MET Norway
+#[derive(Debug, Deserialize)]
+pub struct LocationForecastApi {
+    pub properties: ForecastPropertiesApi,
+}
+
+/// Forecast metadata and time series
+#[derive(Debug, Deserialize)]
+pub struct ForecastPropertiesApi {
+    pub meta: MetaApi,
+    pub timeseries: Vec<TimeStepApi>,
+}
+
+/// Forecast metadata
+#[derive(Debug, Deserialize)]
+pub struct MetaApi {
+    /// When the forecast model run was published, RFC 3339 UTC
+    pub updated_at: String,
+}
+
+/// A single forecast step
+#[derive(Debug, Deserialize)]
+pub struct TimeStepApi {
+    /// Step time, RFC 3339 UTC (e.g., "2023-01-13T01:00:00Z")
+    pub time: String,
+    pub data: TimeStepDataApi,
+}
+
+/// Values valid at and after a forecast step
+#[derive(Debug, Deserialize)]
+pub struct TimeStepDataApi {
+    pub instant: InstantApi,
+    /// Only present for the first couple of days of the forecast
+    pub next_1_hours: Option<PeriodApi>,
+    pub next_6_hours: Option<PeriodApi>,
+}
+
+/// Point-in-time values
+#[derive(Debug, Deserialize)]
+pub struct InstantApi {
+    pub details: InstantDetailsApi,
+}
+
+/// Point-in-time measurements in SI units
+#[derive(Debug, Deserialize)]
+pub struct InstantDetailsApi {
+    pub air_pressure_at_sea_level: f64,
+    pub air_temperature: f64,
+    pub relative_humidity: f64,
+    pub wind_from_direction: f64,
+    /// Metres per second
+    pub wind_speed: f64,
+    /// Metres per second; only in the "complete" product
+    pub wind_speed_of_gust: Option<f64>,
+    /// Percent
+    pub cloud_area_fraction: Option<f64>,
+    /// Only in the "complete" product
+    pub ultraviolet_index_clear_sky: Option<f64>,
+}
+
+/// Summary of the period following a forecast step
+#[derive(Debug, Deserialize)]
+pub struct PeriodApi {
+    pub summary: SummaryApi,
+    #[serde(default)]
+    pub details: PeriodDetailsApi,
+}
+
+/// Aggregate values over a period
+#[derive(Debug, Default, Deserialize)]
+pub struct PeriodDetailsApi {
+    /// Millimetres
+    pub precipitation_amount: Option<f64>,
+    /// Percent; only in the "complete" product
+    pub probability_of_precipitation: Option<f64>,
+}
+
+/// Weather symbol for a period
+#[derive(Debug, Deserialize)]
+pub struct SummaryApi {
+    /// e.g. "partlycloudy_day", "heavyrainandthunder"
+    pub symbol_code: String,
+}
+
+/// Root sunrise/3.0 response from MET Norway
+#[derive(Debug, Deserialize)]
+pub struct SunriseApi {
+    pub properties: SunPropertiesApi,
+}
+
+/// Sun events for a single day
+#[derive(Debug, Deserialize)]
+pub struct SunPropertiesApi {
+    pub sunrise: Option<SunEventApi>,
+    pub sunset: Option<SunEventApi>,
+}
+
+/// A sun event; `time` is null during polar day or night
+#[derive(Debug, Deserialize)]
+pub struct SunEventApi {
+    /// Local time with offset (e.g., "2023-01-13T05:58+13:00")
+    pub time: Option<String>,
+}
+
+/// Everything needed to build domain weather data from MET Norway responses
+#[derive(Debug)]
+pub struct MetNoResponse {
+    pub place: GeocodingResultApi,
+    pub forecast: LocationForecastApi,
+    pub sun: Option<SunriseApi>,
+    /// Reference time used to pick the current step and upcoming hours
+    pub now: OffsetDateTime,
+}
+
+impl TryFrom<MetNoResponse> for WeatherData {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MetNoResponse) -> Result<Self> {
+        let to_local = |datetime: OffsetDateTime| match value
+            .place
+            .timezone
+            .as_deref()
+            .and_then(timezones::get_by_name)
+        {
+            Some(tz) => datetime.to_timezone(tz),
+            None => datetime,
+        };
+        let local_now = to_local(value.now);
+
+        let steps = value
+            .forecast
+            .properties
+            .timeseries
+            .into_iter()
+            .map(|step| Ok((parse_rfc3339(&step.time)?, step.data)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The current step is the latest one that has already started
+        let current_index = steps
+            .iter()
+            .rposition(|(time, _)| *time <= value.now)
+            .unwrap_or(0);
+        let (_, current_data) = steps
+            .get(current_index)
+            .context("Forecast contains no time steps")?;
+
+        let last_updated = LastUpdated::from_epoch(
+            parse_rfc3339(&value.forecast.properties.meta.updated_at)?.unix_timestamp(),
+        )?;
+        let current = current_weather(current_data, last_updated)
+            .context("Failed to parse current conditions")?;
+
+        let hourly_weather = steps
+            .iter()
+            .filter_map(|(time, data)| {
+                data.next_1_hours
+                    .as_ref()
+                    .map(|period| hourly_weather(to_local(*time), &data.instant.details, period))
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse hourly weather data")?;
+
+        let astronomy = value
+            .sun
+            .map(|sun| sun.properties)
+            .and_then(|sun| Some((sun.sunrise?.time?, sun.sunset?.time?)))
+            .map(|(sunrise, sunset)| -> Result<Astronomy> {
+                Ok(Astronomy::new(
+                    parse_sun_time(&sunrise)?,
+                    parse_sun_time(&sunset)?,
+                ))
+            })
+            .transpose()
+            .context("Failed to parse astronomy data")?;
+
+        Ok(WeatherData {
+            current,
+            location: value.place.location(),
+            weather_day: Some(
+                WeatherDay {
+                    astronomy,
+                    hourly_weather,
+                }
+                .filter_future_hours(local_now),
+            ),
+            daily: Vec::new(),
+            alerts: Vec::new(),
+            air_quality: None,
+            source: MetNoClient::NAME.to_string(),
+            local_time: local_now,
+            time_zone: value.place.timezone,
+            stale_since: None,
+        })
+    }
+}
+
+/// Build current conditions from a forecast step
+fn current_weather(data: &TimeStepDataApi, last_updated: LastUpdated) -> Result<CurrentWeather> {
+    let details = &data.instant.details;
+
+    let temperature = temperature(details.air_temperature)?;
+    let humidity = Humidity::new(details.relative_humidity as f32)
+        .with_context(|| format!("Humidity out of range: {}", details.relative_humidity))?;
+    let wind_speed = wind_speed(details)?;
+    let pressure =
+        Pressure::new(details.air_pressure_at_sea_level.round() as u32).with_context(|| {
+            format!(
+                "Pressure out of range: {}",
+                details.air_pressure_at_sea_level
+            )
+        })?;
+
+    let symbol = data
+        .next_1_hours
+        .as_ref()
+        .or(data.next_6_hours.as_ref())
+        .map_or("", |period| period.summary.symbol_code.as_str());
+
+    // The amount expected over the hour in progress
+    let precipitation = data
+        .next_1_hours
+        .as_ref()
+        .and_then(|period| period.details.precipitation_amount)
+        .map(self::precipitation)
+        .transpose()?;
+
+    let cloud_cover = details
+        .cloud_area_fraction
+        .map(|percent| {
+            CloudCover::new(percent.round() as u8)
+                .with_context(|| format!("Cloud cover out of range: {}", percent))
+        })
+        .transpose()?;
+
+    // A clear-sky index, so an upper bound under cloud
+    let uv_index = details
+        .ultraviolet_index_clear_sky
+        .map(|uv| UvIndex::new(uv as f32).with_context(|| format!("UV index out of range: {}", uv)))
+        .transpose()?;
+
+    Ok(CurrentWeather {
+        last_updated,
+        temperature,
+        feels_like: temperature.apparent(&humidity, &wind_speed),
+        condition: symbol_condition(symbol),
+        humidity,
+        wind_speed,
+        wind_direction: WindDirection::from_degrees(details.wind_from_direction),
+        pressure,
+        precipitation,
+        uv_index,
+        // Not forecast by MET Norway
+        visibility: None,
+        cloud_cover,
+    })
+}
+
+/// Build an hourly forecast entry from a step's instant values and its next-hour summary
+fn hourly_weather(
+    local_time: OffsetDateTime,
+    details: &InstantDetailsApi,
+    period: &PeriodApi,
+) -> Result<HourlyWeather> {
+    let symbol = &period.summary.symbol_code;
+
+    // The chance covers any precipitation; attribute it by the expected weather
+    let chance = period
+        .details
+        .probability_of_precipitation
+        .map(|percent| {
+            Probability::new(percent.round() as u8)
+                .with_context(|| format!("Probability out of range: {}", percent))
+        })
+        .transpose()?;
+    let (chance_of_rain, chance_of_snow) = if symbol.contains("snow") {
+        (None, chance)
+    } else {
+        (chance, None)
+    };
+
+    Ok(HourlyWeather {
+        time: local_time,
+        temperature: temperature(details.air_temperature)?,
+        condition: symbol_condition(symbol),
+        wind_speed: wind_speed(details)?,
+        wind_direction: WindDirection::from_degrees(details.wind_from_direction),
+        precipitation: period
+            .details
+            .precipitation_amount
+            .map(precipitation)
+            .transpose()?,
+        chance_of_rain,
+        chance_of_snow,
+    })
+}
+
+fn precipitation(mm: f64) -> Result<Precipitation> {
+    Precipitation::new(mm).with_context(|| format!("Precipitation out of range: {}", mm))
+}
+
+fn temperature(celsius: f64) -> Result<Temperature> {
+    Temperature::new(celsius).with_context(|| format!("Temperature out of range: {}", celsius))
+}
+
+/// Convert MET's m/s wind values to a validated km/h wind speed
+fn wind_speed(details: &InstantDetailsApi) -> Result<WindSpeed> {
+    let to_kph = |ms: f64| (ms * 3.6).round() as u32;
+    let sustained = to_kph(details.wind_speed);
+
+    match details.wind_speed_of_gust.map(to_kph) {
+        Some(gusts) if gusts > sustained => WindSpeed::builder()
+            .sustained(sustained)
+            .with_gusts(gusts)
+            .build()
+            .with_context(|| {
+                format!(
+                    "Invalid wind data: sustained {} km/h, gusts {} km/h",
+                    sustained, gusts
+                )
+            }),
+        _ => WindSpeed::new(sustained)
+            .with_context(|| format!("Wind speed out of range: {}", sustained)),
+    }
+}
+
+fn parse_rfc3339(value: &str) -> Result<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339).with_context(|| format!("Invalid timestamp: {}", value))
+}
+
+/// Parse a sunrise/3.0 local time such as "2023-01-13T05:58+13:00"
+fn parse_sun_time(value: &str) -> Result<WeatherTime> {
+    let format = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"
+    );
+    let datetime = OffsetDateTime::parse(value, &format)
+        .with_context(|| format!("Invalid sun event time: {}", value))?;
+    Ok(WeatherTime::parse(&format!(
+        "{:02}:{:02}",
+        datetime.hour(),
+        datetime.minute()
+    ))?)
+}
+
+/// Build the domain condition for a MET Norway weather symbol code, which carries no
+/// numeric code but marks day and night with a suffix (e.g., "clearsky_night")
+fn symbol_condition(symbol_code: &str) -> WeatherCondition {
+    let condition = WeatherCondition::new(symbol_description(symbol_code).to_string())
+        .with_kind(symbol_kind(symbol_code));
+    // Polar twilight is neither, so it is left to sunrise and sunset
+    match symbol_code.rsplit_once('_') {
+        Some((_, "day")) => condition.with_is_day(true),
+        Some((_, "night")) => condition.with_is_day(false),
+        _ => condition,
+    }
+}
+
+/// Classify a MET Norway weather symbol code, ignoring its variant suffix
+pub fn symbol_kind(symbol_code: &str) -> ConditionKind {
+    let base = symbol_code.split('_').next().unwrap_or_default();
+    match base {
+        "clearsky" => ConditionKind::Clear,
+        "fair" | "partlycloudy" => ConditionKind::PartlyCloudy,
+        "cloudy" => ConditionKind::Cloudy,
+        "fog" => ConditionKind::Fog,
+        "lightrain" | "rain" => ConditionKind::Rain,
+        "heavyrain" => ConditionKind::HeavyRain,
+        "lightrainshowers" | "rainshowers" | "heavyrainshowers" => ConditionKind::RainShowers,
+        "lightsnow" | "snow" => ConditionKind::Snow,
+        "heavysnow" => ConditionKind::HeavySnow,
+        "lightsnowshowers" | "snowshowers" | "heavysnowshowers" => ConditionKind::SnowShowers,
+        thunder if thunder.ends_with("thunder") => ConditionKind::Thunderstorm,
+        sleet if sleet.contains("sleet") => ConditionKind::Sleet,
+        _ => ConditionKind::Unknown,
+    }
+}
+
+/// Describe a MET Norway weather symbol code, ignoring its day/night/polar-twilight
+/// variant suffix
+pub fn symbol_description(symbol_code: &str) -> &'static str {
+    let base = symbol_code.split('_').next().unwrap_or_default();
+    match base {
+        "clearsky" => "Clear sky",
+        "fair" => "Fair",
+        "partlycloudy" => "Partly cloudy",
+        "cloudy" => "Cloudy",
+        "fog" => "Fog",
+        "lightrain" => "Light rain",
+        "rain" => "Rain",
+        "heavyrain" => "Heavy rain",
+        "lightrainshowers" => "Light rain showers",
+        "rainshowers" => "Rain showers",
+        "heavyrainshowers" => "Heavy rain showers",
+        "lightrainandthunder" => "Light rain and thunder",
+        "rainandthunder" => "Rain and thunder",
+        "heavyrainandthunder" => "Heavy rain and thunder",
+        "lightrainshowersandthunder" => "Light rain showers and thunder",
+        "rainshowersandthunder" => "Rain showers and thunder",
+        "heavyrainshowersandthunder" => "Heavy rain showers and thunder",
+        "lightsleet" => "Light sleet",
+        "sleet" => "Sleet",
+        "heavysleet" => "Heavy sleet",
+        "lightsleetshowers" => "Light sleet showers",
+        "sleetshowers" => "Sleet showers",
+        "heavysleetshowers" => "Heavy sleet showers",
+        "lightsleetandthunder" => "Light sleet and thunder",
+        "sleetandthunder" => "Sleet and thunder",
+        "heavysleetandthunder" => "Heavy sleet and thunder",
+        // MET's own spelling of these two codes has a doubled "s"
+        "lightssleetshowersandthunder" => "Light sleet showers and thunder",
+        "sleetshowersandthunder" => "Sleet showers and thunder",
+        "heavysleetshowersandthunder" => "Heavy sleet showers and thunder",
+        "lightsnow" => "Light snow",
+        "snow" => "Snow",
+        "heavysnow" => "Heavy snow",
+        "lightsnowshowers" => "Light snow showers",
+        "snowshowers" => "Snow showers",
+        "heavysnowshowers" => "Heavy snow showers",
+        "lightsnowandthunder" => "Light snow and thunder",
+        "snowandthunder" => "Snow and thunder",
+        "heavysnowandthunder" => "Heavy snow and thunder",
+        "lightssnowshowersandthunder" => "Light snow showers and thunder",
+        "snowshowersandthunder" => "Snow showers and thunder",
+        "heavysnowshowersandthunder" => "Heavy snow showers and thunder",
+        _ => "Unknown",
+    }
+}
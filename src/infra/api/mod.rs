@@ -1,14 +1,46 @@
 //! API module for external weather service integration with type-safe parsing.
 
 pub mod client;
+pub mod http_cache;
+pub mod metno;
 pub mod models;
+pub mod nws;
+pub mod openmeteo;
+pub mod provider;
 
 pub use client::*;
+pub use provider::Provider;
+
+use std::time::Duration;
+
+/// `User-Agent` sent to services whose terms require clients to identify themselves
+pub const USER_AGENT: &str = concat!(
+    "waybar_weather/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/pukeko37/waybar_weather)"
+);
+
+/// Default timeout applied to every request made by the API clients
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Build the HTTP agent used by the API clients, giving up on requests after `timeout`.
+///
+/// Agents pool connections, so sharing one between clients (and across refreshes
+/// in daemon mode) avoids repeating DNS and TLS setup for every request.
+pub fn http_agent(timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .build()
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::infra::api::models::*;
     use crate::domain::models::*;
+    use crate::domain::{
+        AirQualityCategory, AlertSeverity, AlertUrgency, ConditionKind, DefraBand,
+    };
+    use crate::infra::api::models::*;
 
     #[test]
     fn test_weather_response_parsing() {
@@ -16,7 +48,13 @@ mod tests {
         {
             "location": {
                 "name": "Wellington",
-                "localtime": "2023-01-13 14:30"
+                "region": "Wellington",
+                "country": "New Zealand",
+                "lat": -41.29,
+                "lon": 174.78,
+                "localtime": "2023-01-13 14:30",
+                "localtime_epoch": 1673573400,
+                "tz_id": "Pacific/Auckland"
             },
             "current": {
                 "last_updated_epoch": 1673620200,
@@ -45,17 +83,39 @@ mod tests {
                 "vis_miles": 6.0,
                 "uv": 6.0,
                 "gust_mph": 18.8,
-                "gust_kph": 30.2
+                "gust_kph": 30.2,
+                "air_quality": {
+                    "co": 230.3,
+                    "no2": 13.5,
+                    "o3": 54.3,
+                    "so2": -0.4,
+                    "pm2_5": 8.6,
+                    "pm10": 12.3,
+                    "us-epa-index": 2,
+                    "gb-defra-index": 3
+                }
             },
             "forecast": {
                 "forecastday": [
                     {
+                        "date": "2023-01-13",
+                        "day": {
+                            "maxtemp_c": 23.4,
+                            "mintemp_c": 14.1,
+                            "maxwind_kph": 24.8,
+                            "totalprecip_mm": 0.0,
+                            "daily_chance_of_rain": 0,
+                            "condition": {
+                                "text": "Sunny"
+                            }
+                        },
                         "astro": {
                             "sunrise": "06:30 AM",
                             "sunset": "06:30 PM"
                         },
                         "hour": [
                             {
+                                "time_epoch": 1673564400,
                                 "time": "2023-01-13 12:00",
                                 "temp_c": 22.0,
                                 "condition": {
@@ -63,11 +123,79 @@ mod tests {
                                 },
                                 "wind_kph": 10.0,
                                 "wind_dir": "N",
-                                "gust_kph": 18.0
+                                "gust_kph": 18.0,
+                                "precip_mm": 0.0,
+                                "chance_of_rain": 0,
+                                "chance_of_snow": 0
+                            }
+                        ]
+                    },
+                    {
+                        "date": "2023-01-14",
+                        "day": {
+                            "maxtemp_c": 17.6,
+                            "mintemp_c": 12.0,
+                            "maxwind_kph": 51.5,
+                            "totalprecip_mm": 8.3,
+                            "daily_chance_of_rain": 89,
+                            "condition": {
+                                "text": "Moderate rain"
+                            }
+                        },
+                        "hour": [
+                            {
+                                "time_epoch": 1673607600,
+                                "time": "2023-01-14 00:00",
+                                "temp_c": 15.0,
+                                "condition": {
+                                    "text": "Light rain",
+                                    "code": 1183
+                                },
+                                "is_day": 0,
+                                "wind_kph": 20.0,
+                                "wind_dir": "S",
+                                "gust_kph": 31.0,
+                                "precip_mm": 1.4,
+                                "chance_of_rain": 87,
+                                "chance_of_snow": 0
                             }
                         ]
                     }
                 ]
+            },
+            "alerts": {
+                "alert": [
+                    {
+                        "headline": "Strong Wind Watch for Wellington",
+                        "msgtype": "Alert",
+                        "severity": "Moderate",
+                        "urgency": "Future",
+                        "areas": "Wellington; Kapiti Coast",
+                        "category": "Met",
+                        "certainty": "Possible",
+                        "event": "Strong Wind Watch",
+                        "note": "",
+                        "effective": "2023-01-13T12:00:00+13:00",
+                        "expires": "2023-01-14T06:00:00+13:00",
+                        "desc": "Southerly gales may approach severe in exposed places.",
+                        "instruction": ""
+                    },
+                    {
+                        "headline": "",
+                        "msgtype": "Alert",
+                        "severity": "",
+                        "urgency": "",
+                        "areas": "Wellington",
+                        "category": "Met",
+                        "certainty": "",
+                        "event": "Heavy Rain Watch",
+                        "note": "",
+                        "effective": "2023-01-12T09:00:00+13:00",
+                        "expires": "2023-01-13T09:00:00+13:00",
+                        "desc": "",
+                        "instruction": ""
+                    }
+                ]
             }
         }
         "#;
@@ -78,6 +206,130 @@ mod tests {
         assert_eq!(weather_data.current.temperature.as_celsius(), 20);
         assert_eq!(weather_data.current.humidity.as_int(), 60);
         assert_eq!(weather_data.location.to_string(), "Wellington");
+        assert_eq!(weather_data.location.region(), Some("Wellington"));
+        assert_eq!(weather_data.location.country(), Some("New Zealand"));
+        let coordinates = weather_data
+            .location
+            .coordinates()
+            .expect("Coordinates present");
+        assert_eq!(
+            (coordinates.latitude(), coordinates.longitude()),
+            (-41.29, 174.78)
+        );
+        assert_eq!(weather_data.source, "WeatherAPI.com");
+        assert_eq!(weather_data.current.condition.code(), Some(1000));
+        assert_eq!(weather_data.current.condition.kind(), ConditionKind::Clear);
+        assert_eq!(weather_data.current.condition.is_day(), Some(true));
+
+        let air_quality = weather_data
+            .air_quality
+            .as_ref()
+            .expect("Air quality present");
+        assert_eq!(air_quality.pm2_5.map(|c| c.as_micrograms()), Some(8.6));
+        assert_eq!(
+            air_quality.carbon_monoxide.map(|c| c.as_micrograms()),
+            Some(230.3)
+        );
+        // Negative readings mark pollutants the station does not measure
+        assert!(air_quality.sulphur_dioxide.is_none());
+        assert_eq!(
+            air_quality.us_epa_index.map(|index| index.category()),
+            Some(AirQualityCategory::Moderate)
+        );
+        assert_eq!(
+            air_quality.gb_defra_index.map(|index| index.band()),
+            Some(DefraBand::Low)
+        );
+
+        // The watch that lapsed this morning is no longer shown
+        assert_eq!(weather_data.alerts.len(), 1);
+        let alert = &weather_data.alerts[0];
+        assert_eq!(alert.event, "Strong Wind Watch");
+        assert_eq!(alert.headline, "Strong Wind Watch for Wellington");
+        assert_eq!(alert.severity, AlertSeverity::Moderate);
+        assert_eq!(alert.urgency, AlertUrgency::Future);
+        assert_eq!(alert.areas, vec!["Wellington", "Kapiti Coast"]);
+        assert_eq!(
+            alert.effective,
+            Some(time::macros::datetime!(2023-01-13 12:00 +13))
+        );
+        assert_eq!(
+            alert.id,
+            "Strong Wind Watch|2023-01-13T12:00:00+13:00|Wellington; Kapiti Coast"
+        );
+        assert_eq!(
+            weather_data.local_time,
+            time::macros::datetime!(2023-01-13 14:30 +13)
+        );
+        assert_eq!(weather_data.time_zone.as_deref(), Some("Pacific/Auckland"));
+
+        // Astronomy is today's; past hours are dropped and later days' hours follow on
+        let weather_day = weather_data
+            .weather_day
+            .as_ref()
+            .expect("Forecast day present");
+        assert!(weather_day.astronomy.is_some());
+        assert_eq!(weather_day.hourly_weather.len(), 1);
+        assert_eq!(
+            weather_day.hourly_weather[0].time,
+            time::macros::datetime!(2023-01-14 00:00 +13)
+        );
+        let condition = &weather_day.hourly_weather[0].condition;
+        assert_eq!(condition.kind(), ConditionKind::Rain);
+        assert_eq!(condition.is_day(), Some(false));
+
+        // Every forecast day is summarised, not only the first
+        assert_eq!(weather_data.daily.len(), 2);
+        let tomorrow = &weather_data.daily[1];
+        assert_eq!(tomorrow.date.to_string(), "2023-01-14");
+        assert_eq!(tomorrow.min_temperature.as_celsius(), 12);
+        assert_eq!(tomorrow.max_temperature.as_celsius(), 18);
+        assert_eq!(tomorrow.condition.to_string(), "Moderate rain");
+        assert_eq!(tomorrow.chance_of_rain.map(|c| c.as_percent()), Some(89));
+        assert_eq!(tomorrow.precipitation.as_mm(), 8.3);
+        assert_eq!(tomorrow.max_wind.sustained_value(), 52);
+    }
+
+    #[test]
+    fn test_alert_with_bad_timestamps() {
+        let alert: AlertApi = serde_json::from_str(
+            r#"{
+                "headline": "Heavy Rain Watch for Wellington",
+                "severity": "Moderate",
+                "urgency": "Expected",
+                "areas": "Wellington",
+                "event": "Heavy Rain Watch",
+                "effective": "",
+                "expires": "tomorrow"
+            }"#,
+        )
+        .expect("Valid JSON");
+
+        // Times that do not parse are left out rather than failing the whole response
+        let alert = WeatherAlert::from(alert);
+        assert_eq!(alert.event, "Heavy Rain Watch");
+        assert_eq!(alert.effective, None);
+        assert_eq!(alert.expires, None);
+    }
+
+    #[test]
+    fn test_condition_code_classification() {
+        let condition = |json: &str| {
+            let condition: ConditionApi = serde_json::from_str(json).expect("Valid JSON");
+            condition.condition(Some(1))
+        };
+
+        // The code wins over the wording, which mentions rain before thunder
+        let thundery = condition(r#"{"text": "Patchy light rain with thunder", "code": 1273}"#);
+        assert_eq!(thundery.kind(), ConditionKind::Thunderstorm);
+        assert_eq!(thundery.code(), Some(1273));
+
+        // Unknown or missing codes fall back to the description
+        let unknown = condition(r#"{"text": "Light drizzle", "code": 9999}"#);
+        assert_eq!(unknown.kind(), ConditionKind::Drizzle);
+        let uncoded = condition(r#"{"text": "Overcast"}"#);
+        assert_eq!(uncoded.kind(), ConditionKind::Overcast);
+        assert_eq!(uncoded.code(), None);
     }
 
     #[test]
@@ -95,23 +347,38 @@ mod tests {
             "pressure_mb": 1010.0,
             "humidity": 70,
             "feelslike_c": 18.0,
-            "gust_kph": 24.0
+            "gust_kph": 24.0,
+            "precip_mm": 0.2,
+            "uv": 4.0,
+            "vis_km": 10.0,
+            "cloud": 75
         }
         "#;
 
         let current: CurrentApi = serde_json::from_str(current_json).expect("Valid JSON");
-        let domain_current: CurrentWeather = current.try_into().expect("Valid domain conversion");
+        let offset = time::macros::offset!(+13);
+        let domain_current: CurrentWeather = CurrentWithOffset { current, offset }
+            .try_into()
+            .expect("Valid domain conversion");
 
         assert_eq!(domain_current.temperature.as_celsius(), 19); // Rounded from 18.5
         assert_eq!(domain_current.humidity.as_int(), 70);
         assert_eq!(domain_current.condition.to_string(), "Partly cloudy");
         assert_eq!(domain_current.wind_direction.to_string(), "SW");
+        assert_eq!(domain_current.precipitation.map(|p| p.as_mm()), Some(0.2));
+        assert_eq!(
+            domain_current.uv_index.map(|uv| uv.to_string()).as_deref(),
+            Some("4 (Moderate)")
+        );
+        assert_eq!(domain_current.visibility.map(|v| v.as_km()), Some(10.0));
+        assert_eq!(domain_current.cloud_cover.map(|c| c.as_percent()), Some(75));
     }
 
     #[test]
     fn test_hourly_weather_parsing() {
         let hourly_json = r#"
         {
+            "time_epoch": 1673586000,
             "time": "2023-01-13 18:00",
             "temp_c": 18.0,
             "condition": {
@@ -119,20 +386,32 @@ mod tests {
             },
             "wind_kph": 12.0,
             "wind_dir": "SW",
-            "gust_kph": 20.0
+            "gust_kph": 20.0,
+            "precip_mm": 0.3,
+            "chance_of_rain": 64,
+            "chance_of_snow": 0
         }
         "#;
 
         let hourly: HourApi = serde_json::from_str(hourly_json).expect("Valid JSON");
         let domain_hourly: HourlyWeather = hourly.try_into().expect("Valid domain conversion");
 
-        assert_eq!(domain_hourly.time.hour24(), 18);
+        assert_eq!(
+            domain_hourly.time,
+            time::macros::datetime!(2023-01-13 18:00 +13)
+        );
         assert_eq!(domain_hourly.temperature.as_celsius(), 18);
         assert_eq!(domain_hourly.condition.to_string(), "Partly cloudy");
         assert_eq!(
             domain_hourly.wind_speed.to_string(),
             "12 km/h (Gusts: 20 km/h)"
         );
+        assert_eq!(domain_hourly.precipitation.map(|p| p.as_mm()), Some(0.3));
+        assert_eq!(
+            domain_hourly.chance_of_rain.map(|c| c.as_percent()),
+            Some(64)
+        );
+        assert!(domain_hourly.rain_likely());
     }
 
     #[test]
@@ -168,52 +447,71 @@ mod tests {
             "pressure_mb": 1013.0,
             "humidity": 60,
             "feelslike_c": 999.0,
-            "gust_kph": 30.2
+            "gust_kph": 30.2,
+            "precip_mm": 0.0,
+            "uv": 6.0,
+            "vis_km": 10.0,
+            "cloud": 0
         }
         "#;
 
         let current: CurrentApi = serde_json::from_str(invalid_temp_json).expect("Valid JSON");
-        let result: Result<CurrentWeather, _> = current.try_into();
+        let offset = time::UtcOffset::UTC;
+        let result: Result<CurrentWeather, _> = CurrentWithOffset { current, offset }.try_into();
         assert!(result.is_err());
     }
 
     #[test]
     fn test_time_parsing_variations() {
+        // Local times in Wellington (UTC+13) with their Unix timestamps
         let time_variations = vec![
-            ("2023-01-13 06:00", 6),
-            ("2023-01-13 12:00", 12),
-            ("2023-01-13 18:00", 18),
-            ("2023-01-13 00:30", 0),
+            ("2023-01-13 06:00", 1673542800, 6),
+            ("2023-01-13 12:00", 1673564400, 12),
+            ("2023-01-13 18:00", 1673586000, 18),
+            ("2023-01-13 00:30", 1673523000, 0),
         ];
 
-        for (time_str, expected_hour) in time_variations {
+        for (time_str, time_epoch, expected_hour) in time_variations {
             let hourly = HourApi {
+                time_epoch,
                 time: time_str.to_string(),
                 temp_c: 20.0,
                 condition: ConditionApi {
                     text: "Clear".to_string(),
+                    code: None,
                 },
+                is_day: None,
                 wind_kph: 10.0,
                 wind_dir: "N".to_string(),
                 gust_kph: 19.0,
+                precip_mm: 0.0,
+                chance_of_rain: None,
+                chance_of_snow: None,
             };
 
             let domain_hourly: HourlyWeather = hourly.try_into().expect("Valid conversion");
-            assert_eq!(domain_hourly.time.hour24(), expected_hour);
+            assert_eq!(domain_hourly.time.hour(), expected_hour);
+            assert_eq!(domain_hourly.time.offset().whole_hours(), 13);
         }
     }
 
     #[test]
     fn test_wind_speed_with_gusts() {
         let hourly_with_gusts = HourApi {
+            time_epoch: 1673575200,
             time: "2023-01-13 15:00".to_string(),
             temp_c: 20.0,
             condition: ConditionApi {
                 text: "Windy".to_string(),
+                code: None,
             },
+            is_day: None,
             wind_kph: 30.0,
             wind_dir: "W".to_string(),
             gust_kph: 50.0,
+            precip_mm: 0.4,
+            chance_of_rain: Some(70),
+            chance_of_snow: Some(0),
         };
 
         let domain_hourly: HourlyWeather = hourly_with_gusts.try_into().expect("Valid conversion");
@@ -222,4 +520,43 @@ mod tests {
             "30 km/h (Gusts: 50 km/h)"
         );
     }
+
+    #[test]
+    fn test_search_results_parsing() {
+        let json_data = r#"
+        [
+            {
+                "id": 2588521,
+                "name": "Richmond",
+                "region": "Virginia",
+                "country": "United States of America",
+                "lat": 37.55,
+                "lon": -77.46,
+                "url": "richmond-virginia-united-states-of-america"
+            },
+            {
+                "id": 2801268,
+                "name": "Richmond",
+                "region": "",
+                "country": "New Zealand",
+                "lat": -41.33,
+                "lon": 173.18,
+                "url": "richmond-new-zealand"
+            }
+        ]"#;
+
+        let results: Vec<SearchResultApi> =
+            serde_json::from_str(json_data).expect("Valid search results");
+        let places: Vec<PlaceMatch> = results.into_iter().map(PlaceMatch::from).collect();
+
+        assert_eq!(places.len(), 2);
+        assert_eq!(places[0].id, Some(2588521));
+        assert_eq!(
+            places[0].location.full_name(),
+            "Richmond, Virginia, United States of America"
+        );
+        assert_eq!(places[0].query().to_string(), "37.55,-77.46");
+        // An empty region is dropped rather than shown as a trailing comma
+        assert_eq!(places[1].location.full_name(), "Richmond, New Zealand");
+    }
 }
@@ -4,11 +4,18 @@
 //! This module handles the JSON response structure and converts it to our domain models.
 
 use crate::domain::{
-    Astronomy, CurrentWeather, HourlyWeather, Humidity, LastUpdated, Location, Pressure,
-    Temperature, WeatherCondition, WeatherData, WeatherDay, WeatherTime, WindDirection, WindSpeed,
+    AirQuality, AlertSeverity, AlertUrgency, Astronomy, CloudCover, Concentration, ConditionKind,
+    Coordinates, CurrentWeather, DailyForecast, DefraIndex, HourlyWeather, Humidity, LastUpdated,
+    Location, PlaceMatch, Precipitation, Pressure, Probability, Temperature, UsEpaIndex, UvIndex,
+    Visibility, WeatherAlert, WeatherCondition, WeatherData, WeatherDay, WeatherTime,
+    WindDirection, WindSpeed,
 };
+use crate::infra::api::client::WeatherClient;
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
 /// Root weather API response from WeatherAPI.com
 #[derive(Debug, Deserialize)]
@@ -16,41 +23,86 @@ pub struct WeatherApiResponse {
     pub location: LocationApi,
     pub current: CurrentApi,
     pub forecast: Option<ForecastApi>,
+    /// Present when requested with `alerts=yes`
+    pub alerts: Option<AlertsApi>,
 }
 
 impl TryFrom<WeatherApiResponse> for WeatherData {
     type Error = anyhow::Error;
 
     fn try_from(value: WeatherApiResponse) -> Result<Self> {
-        let current = value
+        let air_quality = value
             .current
-            .try_into()
-            .context("Failed to parse current conditions")?;
-
-        let location = Location::new(value.location.name);
-
-        // Parse location's local time for filtering using the localtime string
-        let location_local_hour = value
-            .location
-            .localtime
-            .split(' ')
-            .nth(1)
-            .and_then(|time_part| time_part.split(':').next())
-            .and_then(|hour_str| hour_str.parse::<u32>().ok())
-            .unwrap_or(0);
-
-        let weather_day = value
-            .forecast
-            .and_then(|f| f.forecastday.into_iter().next())
-            .map(|day| day.try_into())
+            .air_quality
+            .as_ref()
+            .map(AirQuality::try_from)
             .transpose()
+            .context("Failed to parse air quality")?;
+
+        let local_time =
+            parse_local_time(&value.location.localtime, value.location.localtime_epoch)
+                .context("Failed to parse location local time")?;
+
+        let current = CurrentWithOffset {
+            current: value.current,
+            offset: local_time.offset(),
+        }
+        .try_into()
+        .context("Failed to parse current conditions")?;
+
+        let time_zone = value.location.tz_id;
+        let coordinates = match (value.location.lat, value.location.lon) {
+            (Some(latitude), Some(longitude)) => Coordinates::new(latitude, longitude).ok(),
+            _ => None,
+        };
+        let location = Location::new(value.location.name)
+            .with_region(value.location.region)
+            .with_country(value.location.country)
+            .with_coordinates(coordinates);
+
+        let alerts = value
+            .alerts
+            .map_or_else(Vec::new, |alerts| alerts.alert)
+            .into_iter()
+            .map(WeatherAlert::from)
+            // WeatherAPI.com keeps listing alerts for a while after they lapse
+            .filter(|alert| alert.is_active_at(local_time))
+            .collect();
+
+        let forecast_days = value.forecast.map(|f| f.forecastday).unwrap_or_default();
+
+        let daily = forecast_days
+            .iter()
+            .map(ForecastDayApi::daily_forecast)
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse daily forecast")?;
+
+        let mut days = forecast_days
+            .into_iter()
+            .map(WeatherDay::try_from)
+            .collect::<Result<Vec<_>>>()
             .context("Failed to parse weather day data")?
-            .map(|day: WeatherDay| day.filter_future_hours(location_local_hour));
+            .into_iter();
+
+        // Astronomy is today's; the hourly forecast runs on through the following days
+        let weather_day = days.next().map(|mut today| {
+            today
+                .hourly_weather
+                .extend(days.flat_map(|day| day.hourly_weather));
+            today.filter_future_hours(local_time)
+        });
 
         Ok(WeatherData {
             current,
             location,
             weather_day,
+            daily,
+            alerts,
+            air_quality,
+            source: WeatherClient::NAME.to_string(),
+            local_time,
+            time_zone,
+            stale_since: None,
         })
     }
 }
@@ -59,7 +111,66 @@ impl TryFrom<WeatherApiResponse> for WeatherData {
 #[derive(Debug, Deserialize)]
 pub struct LocationApi {
     pub name: String,
+    /// State, province or other first-level area; empty when there is none
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lon: Option<f64>,
+    /// Wall-clock time at the location, e.g. "2023-01-13 9:05"
     pub localtime: String,
+    /// The same instant as a Unix timestamp
+    pub localtime_epoch: i64,
+    /// IANA time zone name, e.g. "Pacific/Auckland"
+    pub tz_id: Option<String>,
+}
+
+/// One place matched by WeatherAPI.com's search endpoint
+#[derive(Debug, Deserialize)]
+pub struct SearchResultApi {
+    /// Place id, accepted back as an `id:<n>` query
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl From<SearchResultApi> for PlaceMatch {
+    fn from(value: SearchResultApi) -> Self {
+        let location = Location::new(value.name)
+            .with_region(value.region)
+            .with_country(value.country)
+            .with_coordinates(Coordinates::new(value.lat, value.lon).ok());
+        Self {
+            location,
+            id: Some(value.id),
+        }
+    }
+}
+
+/// Combine WeatherAPI.com's local wall-clock time with its epoch to recover the
+/// location's UTC offset
+fn parse_local_time(localtime: &str, epoch: i64) -> Result<OffsetDateTime> {
+    let local = PrimitiveDateTime::parse(
+        localtime,
+        format_description!("[year]-[month]-[day] [hour padding:none]:[minute]"),
+    )
+    .with_context(|| format!("Invalid local time: {}", localtime))?;
+
+    // The wall-clock time is truncated to the minute; offsets are whole quarter hours
+    let difference = local.assume_utc().unix_timestamp() - epoch;
+    let quarter_hours = (difference as f64 / 900.0).round() as i32;
+    let offset = UtcOffset::from_whole_seconds(quarter_hours * 900)
+        .with_context(|| format!("UTC offset out of range: {}", difference))?;
+
+    Ok(local.assume_offset(offset))
 }
 
 /// Forecast data from WeatherAPI.com
@@ -71,10 +182,60 @@ pub struct ForecastApi {
 /// Single forecast day from WeatherAPI.com
 #[derive(Debug, Deserialize)]
 pub struct ForecastDayApi {
+    /// Local date, e.g. "2023-01-13"
+    pub date: String,
+    pub day: DayApi,
     pub astro: Option<AstroApi>,
     pub hour: Vec<HourApi>,
 }
 
+impl ForecastDayApi {
+    /// Summarise the day for the multi-day forecast
+    fn daily_forecast(&self) -> Result<DailyForecast> {
+        let date = Date::parse(&self.date, format_description!("[year]-[month]-[day]"))
+            .with_context(|| format!("Invalid forecast date: {}", self.date))?;
+        let day = &self.day;
+
+        let min_temperature = Temperature::new(day.mintemp_c)
+            .with_context(|| format!("Temperature out of range: {}", day.mintemp_c))?;
+        let max_temperature = Temperature::new(day.maxtemp_c)
+            .with_context(|| format!("Temperature out of range: {}", day.maxtemp_c))?;
+        let max_wind = WindSpeed::new(day.maxwind_kph.round() as u32)
+            .with_context(|| format!("Wind speed out of range: {}", day.maxwind_kph))?;
+        let precipitation = Precipitation::new(day.totalprecip_mm)
+            .with_context(|| format!("Precipitation out of range: {}", day.totalprecip_mm))?;
+        let chance_of_rain = day
+            .daily_chance_of_rain
+            .map(|chance| {
+                Probability::new(chance)
+                    .with_context(|| format!("Chance of rain out of range: {}", chance))
+            })
+            .transpose()?;
+
+        Ok(DailyForecast {
+            date,
+            min_temperature,
+            max_temperature,
+            condition: day.condition.condition(None),
+            chance_of_rain,
+            precipitation,
+            max_wind,
+        })
+    }
+}
+
+/// Whole-day summary within a WeatherAPI.com forecast day
+#[derive(Debug, Deserialize)]
+pub struct DayApi {
+    pub maxtemp_c: f64,
+    pub mintemp_c: f64,
+    pub maxwind_kph: f64,
+    pub totalprecip_mm: f64,
+    /// Percentage; absent from some plans
+    pub daily_chance_of_rain: Option<u8>,
+    pub condition: ConditionApi,
+}
+
 impl TryFrom<ForecastDayApi> for WeatherDay {
     type Error = anyhow::Error;
 
@@ -123,31 +284,33 @@ impl TryFrom<AstroApi> for Astronomy {
 /// Hourly weather data from WeatherAPI.com
 #[derive(Debug, Deserialize)]
 pub struct HourApi {
+    pub time_epoch: i64,
+    /// Local time, e.g. "2023-01-13 14:00"
     pub time: String,
     pub temp_c: f64,
     pub condition: ConditionApi,
+    /// 1 for daylight hours, 0 at night
+    pub is_day: Option<u8>,
     pub wind_kph: f64,
     pub wind_dir: String,
     pub gust_kph: f64,
+    pub precip_mm: f64,
+    /// Percentages; absent from some plans
+    pub chance_of_rain: Option<u8>,
+    pub chance_of_snow: Option<u8>,
 }
 
 impl TryFrom<HourApi> for HourlyWeather {
     type Error = anyhow::Error;
 
     fn try_from(value: HourApi) -> Result<Self> {
-        // Parse time from ISO format "2023-01-13 14:00"
-        let time_parts: Vec<&str> = value.time.split(' ').collect();
-        let time_str = time_parts
-            .get(1)
-            .ok_or_else(|| anyhow::anyhow!("Invalid time format: {}", value.time))?;
+        let time = parse_local_time(&value.time, value.time_epoch)
+            .with_context(|| format!("Failed to parse time: {}", value.time))?;
 
-        let time = WeatherTime::parse(time_str)
-            .with_context(|| format!("Failed to parse time: {}", time_str))?;
-
-        let temperature = Temperature::new(value.temp_c.round() as i32)
+        let temperature = Temperature::new(value.temp_c)
             .with_context(|| format!("Temperature out of range: {}", value.temp_c))?;
 
-        let condition = WeatherCondition::new(value.condition.text);
+        let condition = value.condition.condition(value.is_day);
 
         let sustained_wind = value.wind_kph.round() as u32;
         let gust_wind = value.gust_kph.round() as u32;
@@ -171,12 +334,27 @@ impl TryFrom<HourApi> for HourlyWeather {
         let wind_direction = WindDirection::from_compass(&value.wind_dir)
             .with_context(|| format!("Invalid wind direction: {}", value.wind_dir))?;
 
+        let precipitation = Precipitation::new(value.precip_mm)
+            .with_context(|| format!("Precipitation out of range: {}", value.precip_mm))?;
+
+        let probability = |chance: Option<u8>| {
+            chance
+                .map(|chance| {
+                    Probability::new(chance)
+                        .with_context(|| format!("Probability out of range: {}", chance))
+                })
+                .transpose()
+        };
+
         Ok(HourlyWeather {
             time,
             temperature,
             condition,
             wind_speed,
             wind_direction,
+            precipitation: Some(precipitation),
+            chance_of_rain: probability(value.chance_of_rain)?,
+            chance_of_snow: probability(value.chance_of_snow)?,
         })
     }
 }
@@ -188,20 +366,40 @@ pub struct CurrentApi {
     pub last_updated: String,
     pub temp_c: f64,
     pub condition: ConditionApi,
+    /// 1 during daylight, 0 at night
+    pub is_day: Option<u8>,
     pub wind_kph: f64,
     pub wind_dir: String,
     pub pressure_mb: f64,
     pub humidity: i32,
     pub feelslike_c: f64,
     pub gust_kph: f64,
+    pub precip_mm: f64,
+    pub uv: f64,
+    pub vis_km: f64,
+    /// Cloud cover in percent
+    pub cloud: u8,
+    /// Present when requested with `aqi=yes`
+    pub air_quality: Option<AirQualityApi>,
 }
 
-impl TryFrom<CurrentApi> for CurrentWeather {
+/// Current conditions paired with the location's UTC offset, needed to read the local
+/// update time when its epoch is unusable
+pub struct CurrentWithOffset {
+    pub current: CurrentApi,
+    pub offset: UtcOffset,
+}
+
+impl TryFrom<CurrentWithOffset> for CurrentWeather {
     type Error = anyhow::Error;
 
-    fn try_from(value: CurrentApi) -> Result<Self> {
+    fn try_from(value: CurrentWithOffset) -> Result<Self> {
+        let CurrentWithOffset {
+            current: value,
+            offset,
+        } = value;
         let last_updated = LastUpdated::from_epoch(value.last_updated_epoch)
-            .or_else(|_| LastUpdated::from_api_format(&value.last_updated))
+            .or_else(|_| LastUpdated::from_api_format(&value.last_updated, offset))
             .with_context(|| {
                 format!(
                     "Failed to parse last updated timestamp: epoch={}, string={}",
@@ -209,10 +407,10 @@ impl TryFrom<CurrentApi> for CurrentWeather {
                 )
             })?;
 
-        let temperature = Temperature::new(value.temp_c.round() as i32)
+        let temperature = Temperature::new(value.temp_c)
             .with_context(|| format!("Temperature out of range: {}", value.temp_c))?;
 
-        let feels_like = Temperature::new(value.feelslike_c.round() as i32).with_context(|| {
+        let feels_like = Temperature::new(value.feelslike_c).with_context(|| {
             format!("Feels like temperature out of range: {}", value.feelslike_c)
         })?;
 
@@ -241,7 +439,19 @@ impl TryFrom<CurrentApi> for CurrentWeather {
         let pressure = Pressure::new(value.pressure_mb.round() as u32)
             .with_context(|| format!("Pressure out of range: {}", value.pressure_mb))?;
 
-        let condition = WeatherCondition::new(value.condition.text);
+        let precipitation = Precipitation::new(value.precip_mm)
+            .with_context(|| format!("Precipitation out of range: {}", value.precip_mm))?;
+
+        let uv_index = UvIndex::new(value.uv as f32)
+            .with_context(|| format!("UV index out of range: {}", value.uv))?;
+
+        let visibility = Visibility::new(value.vis_km)
+            .with_context(|| format!("Visibility out of range: {}", value.vis_km))?;
+
+        let cloud_cover = CloudCover::new(value.cloud)
+            .with_context(|| format!("Cloud cover out of range: {}", value.cloud))?;
+
+        let condition = value.condition.condition(value.is_day);
         let wind_direction = WindDirection::from_compass(&value.wind_dir)
             .with_context(|| format!("Invalid wind direction: {}", value.wind_dir))?;
 
@@ -254,12 +464,161 @@ impl TryFrom<CurrentApi> for CurrentWeather {
             wind_speed,
             wind_direction,
             pressure,
+            precipitation: Some(precipitation),
+            uv_index: Some(uv_index),
+            visibility: Some(visibility),
+            cloud_cover: Some(cloud_cover),
+        })
+    }
+}
+
+/// Air quality from WeatherAPI.com; concentrations in µg/m³
+#[derive(Debug, Deserialize)]
+pub struct AirQualityApi {
+    pub co: Option<f64>,
+    pub no2: Option<f64>,
+    pub o3: Option<f64>,
+    pub so2: Option<f64>,
+    pub pm2_5: Option<f64>,
+    pub pm10: Option<f64>,
+    #[serde(rename = "us-epa-index")]
+    pub us_epa_index: Option<u8>,
+    #[serde(rename = "gb-defra-index")]
+    pub gb_defra_index: Option<u8>,
+}
+
+impl TryFrom<&AirQualityApi> for AirQuality {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &AirQualityApi) -> Result<Self> {
+        // Pollutants a station does not measure are reported as negative values
+        let concentration =
+            |value: Option<f64>| value.and_then(|value| Concentration::new(value).ok());
+
+        let us_epa_index = value
+            .us_epa_index
+            .map(|index| {
+                UsEpaIndex::new(index)
+                    .with_context(|| format!("US EPA index out of range: {}", index))
+            })
+            .transpose()?;
+        let gb_defra_index = value
+            .gb_defra_index
+            .map(|index| {
+                DefraIndex::new(index)
+                    .with_context(|| format!("DEFRA index out of range: {}", index))
+            })
+            .transpose()?;
+
+        Ok(AirQuality {
+            pm2_5: concentration(value.pm2_5),
+            pm10: concentration(value.pm10),
+            ozone: concentration(value.o3),
+            nitrogen_dioxide: concentration(value.no2),
+            sulphur_dioxide: concentration(value.so2),
+            carbon_monoxide: concentration(value.co),
+            us_epa_index,
+            gb_defra_index,
         })
     }
 }
 
+/// Weather alerts from WeatherAPI.com
+#[derive(Debug, Deserialize)]
+pub struct AlertsApi {
+    pub alert: Vec<AlertApi>,
+}
+
+/// A single weather alert from WeatherAPI.com, with CAP severity and urgency
+#[derive(Debug, Deserialize)]
+pub struct AlertApi {
+    pub headline: String,
+    pub severity: String,
+    pub urgency: String,
+    /// Semicolon-separated list of areas
+    pub areas: String,
+    pub event: String,
+    /// RFC 3339 timestamps, e.g. "2023-01-13T13:42:00-07:00"
+    pub effective: Option<String>,
+    pub expires: Option<String>,
+}
+
+impl From<AlertApi> for WeatherAlert {
+    fn from(value: AlertApi) -> Self {
+        // Alerts are supplementary: an empty or malformed time is treated as not given
+        // rather than hiding the weather
+        let parse = |timestamp: &str| OffsetDateTime::parse(timestamp, &Rfc3339).ok();
+        let effective = value.effective.as_deref().and_then(parse);
+        let expires = value.expires.as_deref().and_then(parse);
+
+        // WeatherAPI.com gives alerts no identifier; these fields don't change while
+        // the alert is in force
+        let id = format!(
+            "{}|{}|{}",
+            value.event,
+            value.effective.as_deref().unwrap_or_default(),
+            value.areas
+        );
+        let headline = if value.headline.trim().is_empty() {
+            value.event.clone()
+        } else {
+            value.headline
+        };
+
+        WeatherAlert {
+            id,
+            event: value.event,
+            headline,
+            severity: AlertSeverity::from_cap(&value.severity),
+            urgency: AlertUrgency::from_cap(&value.urgency),
+            areas: WeatherAlert::parse_areas(&value.areas),
+            effective,
+            expires,
+        }
+    }
+}
+
 /// Weather condition from WeatherAPI.com
 #[derive(Debug, Deserialize)]
 pub struct ConditionApi {
     pub text: String,
+    /// Condition code, e.g. 1273 for "Patchy light rain with thunder"
+    pub code: Option<u16>,
+}
+
+impl ConditionApi {
+    /// Build the domain condition, classified by its code when present
+    pub fn condition(&self, is_day: Option<u8>) -> WeatherCondition {
+        let mut condition = WeatherCondition::new(self.text.clone());
+        if let Some(code) = self.code {
+            condition = condition.with_code(code).with_kind(condition_kind(code));
+        }
+        if let Some(is_day) = is_day {
+            condition = condition.with_is_day(is_day == 1);
+        }
+        condition
+    }
+}
+
+/// Classify a WeatherAPI.com condition code
+/// (<https://www.weatherapi.com/docs/weather_conditions.json>)
+fn condition_kind(code: u16) -> ConditionKind {
+    match code {
+        1000 => ConditionKind::Clear,
+        1003 => ConditionKind::PartlyCloudy,
+        1006 => ConditionKind::Cloudy,
+        1009 => ConditionKind::Overcast,
+        1030 | 1135 | 1147 => ConditionKind::Fog,
+        1063 | 1180 | 1240 | 1243 => ConditionKind::RainShowers,
+        1066 | 1210 | 1255 | 1258 => ConditionKind::SnowShowers,
+        1069 | 1204 | 1207 | 1237 | 1249 | 1252 | 1261 | 1264 => ConditionKind::Sleet,
+        1072 | 1168 | 1171 | 1198 | 1201 => ConditionKind::FreezingRain,
+        1087 | 1273 | 1276 | 1279 | 1282 => ConditionKind::Thunderstorm,
+        1114 | 1213 | 1216 | 1219 => ConditionKind::Snow,
+        1117 | 1222 | 1225 => ConditionKind::HeavySnow,
+        1150 | 1153 => ConditionKind::Drizzle,
+        1183 | 1186 | 1189 => ConditionKind::Rain,
+        1192 | 1195 | 1246 => ConditionKind::HeavyRain,
+        _ => ConditionKind::Unknown,
+    }
 }
@@ -0,0 +1,162 @@
+//! HTTP client for the US National Weather Service (api.weather.gov).
+//!
+//! NWS requires an identifying `User-Agent` and asks clients to cache; gridpoint and
+//! station lookups change rarely, so every request goes through the on-disk `HttpCache`.
+
+use crate::app::WeatherFetcher;
+use crate::domain::models::WeatherData;
+use crate::domain::LocationQuery;
+use crate::infra::api::http_cache::HttpCache;
+use crate::infra::api::nws::models::{
+    AlertsApi, HourlyForecastApi, NwsResponse, ObservationApi, PointsApi, StationsApi,
+};
+use crate::infra::api::openmeteo::Geocoder;
+use crate::infra::api::{http_agent, DEFAULT_TIMEOUT};
+use crate::infra::paths;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+
+const BASE_URL: &str = "https://api.weather.gov";
+
+/// Weather API client for official US forecasts and alerts (no API key required)
+pub struct NwsClient {
+    agent: ureq::Agent,
+    geocoder: Geocoder,
+    cache: HttpCache,
+    base_url: String,
+}
+
+impl NwsClient {
+    /// Service name shown to the user
+    pub const NAME: &'static str = "US National Weather Service";
+
+    /// Create a new NWS client caching responses under the XDG cache directory
+    pub fn new() -> Self {
+        Self::with_agent(http_agent(DEFAULT_TIMEOUT))
+    }
+
+    /// Create a client that issues requests through the given agent, which must send
+    /// an identifying `User-Agent`
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        let cache_dir =
+            paths::cache_dir().unwrap_or_else(|_| std::env::temp_dir().join("waybar_weather"));
+
+        Self {
+            geocoder: Geocoder::new(agent.clone()),
+            agent,
+            cache: HttpCache::new(cache_dir),
+            base_url: BASE_URL.to_string(),
+        }
+    }
+
+    /// Fetch weather data for a location by geocoding it and resolving its gridpoint
+    pub fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        let place = self.geocoder.geocode(location)?;
+        let now = OffsetDateTime::now_utc();
+
+        let point: PointsApi = self
+            .get_json(&self.points_request_url(place.latitude, place.longitude))
+            .context("Failed to resolve NWS gridpoint (NWS only covers the United States)")?;
+        let forecast: HourlyForecastApi = self
+            .get_json(&point.properties.forecast_hourly)
+            .context("Failed to fetch NWS hourly forecast")?;
+
+        let observation = self
+            .latest_observation(&point.properties.observation_stations)
+            .context("Failed to fetch NWS station observation")?;
+
+        // Alerts are supplementary; a failed lookup should not hide the weather
+        let alerts: Option<AlertsApi> = self
+            .get_json(&self.alerts_request_url(place.latitude, place.longitude))
+            .ok();
+
+        NwsResponse {
+            place,
+            point,
+            forecast,
+            observation,
+            alerts,
+            now,
+        }
+        .try_into()
+        .context("Failed to convert API response to domain model")
+    }
+
+    /// Latest observation from the nearest station listed for the gridpoint
+    fn latest_observation(&self, stations_url: &str) -> Result<ObservationApi> {
+        let stations: StationsApi = self.get_json(stations_url)?;
+        let station = stations
+            .observation_stations
+            .first()
+            .context("No observation stations near this location")?;
+        self.get_json(&format!("{}/observations/latest", station))
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let body = self.cache.get(&self.agent, url)?;
+        serde_json::from_str(&body)
+            .with_context(|| format!("Failed to parse JSON response from {}", url))
+    }
+
+    /// Build the points URL; NWS redirects requests with more than four decimals
+    fn points_request_url(&self, latitude: f64, longitude: f64) -> String {
+        format!("{}/points/{:.4},{:.4}", self.base_url, latitude, longitude)
+    }
+
+    fn alerts_request_url(&self, latitude: f64, longitude: f64) -> String {
+        format!(
+            "{}/alerts/active?point={:.4},{:.4}",
+            self.base_url, latitude, longitude
+        )
+    }
+}
+
+impl std::fmt::Debug for NwsClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NwsClient")
+            .field("base_url", &self.base_url)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl Default for NwsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherFetcher for NwsClient {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        self.fetch_weather(location)
+    }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_request_url_truncates_coordinates() {
+        let client = NwsClient::new();
+        assert_eq!(
+            client.points_request_url(39.739236, -104.984862),
+            "https://api.weather.gov/points/39.7392,-104.9849"
+        );
+    }
+
+    #[test]
+    fn test_alerts_request_url() {
+        let client = NwsClient::new();
+        assert_eq!(
+            client.alerts_request_url(39.739236, -104.984862),
+            "https://api.weather.gov/alerts/active?point=39.7392,-104.9849"
+        );
+    }
+}
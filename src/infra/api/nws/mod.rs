@@ -0,0 +1,181 @@
+//! US National Weather Service provider backed by the public api.weather.gov service.
+
+pub mod client;
+pub mod models;
+
+pub use client::*;
+
+#[cfg(test)]
+mod tests {
+    use super::models::*;
+    use crate::domain::models::*;
+    use crate::domain::{AlertSeverity, AlertUrgency, ConditionKind, WindDirection};
+    use crate::infra::api::openmeteo::models::GeocodingResultApi;
+    use time::macros::datetime;
+
+    const POINTS_FIXTURE: &str = include_str!("../../../../tests/fixtures/nws/points_denver.json");
+    const FORECAST_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/nws/forecast_hourly_denver.json");
+    const STATIONS_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/nws/stations_denver.json");
+    const OBSERVATION_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/nws/observation_latest_kden.json");
+    const SPARSE_OBSERVATION_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/nws/observation_sparse_kden.json");
+    const ALERTS_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/nws/alerts_active_denver.json");
+
+    fn denver() -> GeocodingResultApi {
+        GeocodingResultApi {
+            name: "Denver".to_string(),
+            latitude: 39.73915,
+            longitude: -104.9847,
+            timezone: Some("America/Denver".to_string()),
+            admin1: Some("Colorado".to_string()),
+            country: Some("United States".to_string()),
+        }
+    }
+
+    fn fixture_weather_data(observation: &str, alerts: Option<&str>) -> WeatherData {
+        NwsResponse {
+            place: denver(),
+            point: serde_json::from_str(POINTS_FIXTURE).expect("Valid points JSON"),
+            forecast: serde_json::from_str(FORECAST_FIXTURE).expect("Valid forecast JSON"),
+            observation: serde_json::from_str(observation).expect("Valid observation JSON"),
+            alerts: alerts.map(|json| serde_json::from_str(json).expect("Valid alerts JSON")),
+            // 14:30 local time in Denver (MST, UTC-7)
+            now: datetime!(2023-01-13 21:30 UTC),
+        }
+        .try_into()
+        .expect("Valid domain conversion")
+    }
+
+    #[test]
+    fn test_current_conditions_from_station_observation() {
+        let weather_data = fixture_weather_data(OBSERVATION_FIXTURE, None);
+        let current = &weather_data.current;
+
+        assert_eq!(weather_data.location.to_string(), "Denver");
+        assert_eq!(weather_data.location.region(), Some("Colorado"));
+        assert_eq!(weather_data.source, "US National Weather Service");
+        assert_eq!(current.temperature.as_celsius(), 7);
+        assert_eq!(current.humidity.as_int(), 38);
+        // 101590 Pa sea-level pressure
+        assert_eq!(current.pressure.value(), 1016);
+        assert_eq!(current.visibility.map(|v| v.as_km()), Some(16.09));
+        assert_eq!(current.condition.to_string(), "Mostly Sunny");
+        assert_eq!(current.condition.kind(), ConditionKind::Clear);
+        assert_eq!(current.condition.is_day(), Some(true));
+        assert_eq!(current.wind_direction, WindDirection::NW);
+        assert_eq!(current.wind_speed.to_string(), "18 km/h (Gusts: 33 km/h)");
+        // Wind chill reported by the station
+        assert_eq!(current.feels_like.as_celsius(), 4);
+        assert_eq!(current.last_updated.to_string(), "2023-01-13 21:15Z");
+    }
+
+    #[test]
+    fn test_missing_observation_values_fall_back_to_forecast() {
+        let weather_data = fixture_weather_data(SPARSE_OBSERVATION_FIXTURE, None);
+        let current = &weather_data.current;
+
+        // 14:00 period: 45°F, "5 to 15 mph" from NW, 40% humidity
+        assert_eq!(current.temperature.as_celsius(), 7);
+        assert_eq!(current.humidity.as_int(), 40);
+        assert_eq!(current.wind_speed.to_string(), "24 km/h");
+        assert_eq!(current.wind_direction, WindDirection::NW);
+        assert_eq!(current.condition.to_string(), "Sunny");
+        // Station pressure is used when sea-level pressure is missing
+        assert_eq!(current.pressure.value(), 1013);
+    }
+
+    #[test]
+    fn test_hourly_runs_past_local_midnight() {
+        let weather_data = fixture_weather_data(OBSERVATION_FIXTURE, None);
+        let hours = &weather_data.weather_day.as_ref().unwrap().hourly_weather;
+
+        // Every period from 14:00 local time through 13:00 the following day
+        assert_eq!(hours.len(), 24);
+        assert_eq!(hours[0].time, datetime!(2023-01-13 14:00 -7));
+        assert_eq!(hours[0].temperature.as_celsius(), 7);
+        assert_eq!(hours[1].wind_speed.to_string(), "16 km/h");
+        assert_eq!(hours[1].wind_direction, WindDirection::WNW);
+        assert_eq!(hours[6].condition.to_string(), "Chance Light Snow");
+        assert_eq!(hours[6].condition.kind(), ConditionKind::Snow);
+        assert_eq!(hours[6].condition.is_day(), Some(false));
+        assert_eq!(hours[3].chance_of_rain.map(|c| c.as_percent()), Some(20));
+        // Snowy periods count their chance as snow, and carry no amount
+        assert_eq!(hours[6].chance_of_snow.map(|c| c.as_percent()), Some(10));
+        assert!(hours[6].chance_of_rain.is_none());
+        assert!(hours[6].precipitation.is_none());
+        assert_eq!(hours[10].time, datetime!(2023-01-14 00:00 -7));
+        assert_eq!(hours.last().unwrap().time, datetime!(2023-01-14 13:00 -7));
+        assert_eq!(weather_data.local_time, datetime!(2023-01-13 14:30 -7));
+    }
+
+    #[test]
+    fn test_active_alerts() {
+        let weather_data = fixture_weather_data(OBSERVATION_FIXTURE, Some(ALERTS_FIXTURE));
+
+        assert_eq!(weather_data.alerts.len(), 1);
+        let alert = &weather_data.alerts[0];
+        assert_eq!(alert.event, "Winter Weather Advisory");
+        assert_eq!(alert.id, "urn:oid:2.49.0.1.840.0.1a2b3c");
+        assert_eq!(alert.severity, AlertSeverity::Moderate);
+        assert_eq!(alert.urgency, AlertUrgency::Expected);
+        assert_eq!(
+            alert.areas,
+            vec!["Denver", "Central and East Broomfield County"]
+        );
+        assert_eq!(alert.effective, Some(datetime!(2023-01-13 13:42 -7)));
+        assert_eq!(alert.expires, Some(datetime!(2023-01-14 12:00 UTC)));
+    }
+
+    #[test]
+    fn test_expired_alerts_dropped() {
+        let weather_data: WeatherData = NwsResponse {
+            place: denver(),
+            point: serde_json::from_str(POINTS_FIXTURE).expect("Valid points JSON"),
+            forecast: serde_json::from_str(FORECAST_FIXTURE).expect("Valid forecast JSON"),
+            observation: serde_json::from_str(OBSERVATION_FIXTURE).expect("Valid observation JSON"),
+            alerts: Some(serde_json::from_str(ALERTS_FIXTURE).expect("Valid alerts JSON")),
+            // One minute after the advisory expires
+            now: datetime!(2023-01-14 12:01 UTC),
+        }
+        .try_into()
+        .expect("Valid domain conversion");
+
+        assert!(weather_data.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_no_alerts_when_lookup_failed() {
+        let weather_data = fixture_weather_data(OBSERVATION_FIXTURE, None);
+        assert!(weather_data.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_stations_nearest_first() {
+        let stations: StationsApi = serde_json::from_str(STATIONS_FIXTURE).expect("Valid JSON");
+        assert_eq!(
+            stations.observation_stations[0],
+            "https://api.weather.gov/stations/KDEN"
+        );
+    }
+
+    #[test]
+    fn test_parse_wind_mph() {
+        assert_eq!(parse_wind_mph("10 mph").unwrap(), 10.0);
+        assert_eq!(parse_wind_mph("5 to 15 mph").unwrap(), 15.0);
+        assert_eq!(parse_wind_mph("0 mph").unwrap(), 0.0);
+        assert!(parse_wind_mph("calm").is_err());
+    }
+
+    #[test]
+    fn test_icon_kind() {
+        assert_eq!(icon_kind("few"), ConditionKind::Clear);
+        assert_eq!(icon_kind("rain_showers_hi"), ConditionKind::RainShowers);
+        assert_eq!(icon_kind("tsra_sct"), ConditionKind::Thunderstorm);
+        assert_eq!(icon_kind("rain_fzra"), ConditionKind::FreezingRain);
+        assert_eq!(icon_kind("volcano"), ConditionKind::Unknown);
+    }
+}
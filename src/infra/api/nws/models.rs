@@ -0,0 +1,544 @@
+//! API models for deserializing US National Weather Service (api.weather.gov) responses
+//! and converting to domain types.
+//!
+//! NWS data is spread over several GeoJSON documents: `/points` maps coordinates to a
+//! forecast gridpoint and its observation stations, the hourly gridpoint forecast uses
+//! US customary units in display strings ("10 mph", °F), and station observations carry
+//! WMO-coded quantitative values (`{"unitCode": "wmoUnit:degC", "value": 7.2}`) that may
+//! be null when a sensor did not report.
+
+use crate::domain::{
+    AlertSeverity, AlertUrgency, ConditionKind, CurrentWeather, HourlyWeather, Humidity,
+    LastUpdated, Precipitation, Pressure, Probability, Temperature, Visibility, WeatherAlert,
+    WeatherCondition, WeatherData, WeatherDay, WindDirection, WindSpeed,
+};
+use crate::infra::api::nws::NwsClient;
+use crate::infra::api::openmeteo::models::GeocodingResultApi;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use time_tz::{timezones, OffsetDateTimeExt};
+
+/// Root `/points/{lat},{lon}` response
+#[derive(Debug, Deserialize)]
+pub struct PointsApi {
+    pub properties: PointPropertiesApi,
+}
+
+/// Gridpoint metadata for a coordinate
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointPropertiesApi {
+    /// URL of the hourly gridpoint forecast
+    pub forecast_hourly: String,
+    /// URL listing observation stations near the gridpoint, nearest first
+    pub observation_stations: String,
+    /// IANA zone of the gridpoint (e.g., "America/Denver")
+    pub time_zone: String,
+}
+
+/// Root hourly gridpoint forecast response
+#[derive(Debug, Deserialize)]
+pub struct HourlyForecastApi {
+    pub properties: HourlyForecastPropertiesApi,
+}
+
+/// Hourly forecast periods
+#[derive(Debug, Deserialize)]
+pub struct HourlyForecastPropertiesApi {
+    pub periods: Vec<ForecastPeriodApi>,
+}
+
+/// A single one-hour forecast period
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastPeriodApi {
+    /// Local start time with offset (e.g., "2023-01-13T14:00:00-07:00")
+    pub start_time: String,
+    pub temperature: f64,
+    /// "F" or "C"
+    pub temperature_unit: String,
+    pub relative_humidity: Option<QuantitativeValueApi>,
+    /// Chance of any precipitation, in percent
+    pub probability_of_precipitation: Option<QuantitativeValueApi>,
+    /// e.g. "10 mph" or "5 to 15 mph"
+    pub wind_speed: String,
+    /// Compass point (e.g., "NW")
+    pub wind_direction: String,
+    pub short_forecast: String,
+    /// Icon URL naming the condition, e.g. ".../icons/land/night/snow,20?size=small"
+    pub icon: Option<String>,
+}
+
+/// Root observation stations response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationsApi {
+    /// Station URLs, nearest first
+    pub observation_stations: Vec<String>,
+}
+
+/// Root latest-observation response
+#[derive(Debug, Deserialize)]
+pub struct ObservationApi {
+    pub properties: ObservationPropertiesApi,
+}
+
+/// Measurements reported by an observation station
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservationPropertiesApi {
+    /// Observation time, RFC 3339
+    pub timestamp: String,
+    pub text_description: Option<String>,
+    /// Icon URL naming the observed condition; null when the station reports none
+    pub icon: Option<String>,
+    pub temperature: Option<QuantitativeValueApi>,
+    pub wind_direction: Option<QuantitativeValueApi>,
+    pub wind_speed: Option<QuantitativeValueApi>,
+    pub wind_gust: Option<QuantitativeValueApi>,
+    pub barometric_pressure: Option<QuantitativeValueApi>,
+    pub sea_level_pressure: Option<QuantitativeValueApi>,
+    pub relative_humidity: Option<QuantitativeValueApi>,
+    pub wind_chill: Option<QuantitativeValueApi>,
+    pub heat_index: Option<QuantitativeValueApi>,
+    pub precipitation_last_hour: Option<QuantitativeValueApi>,
+    pub visibility: Option<QuantitativeValueApi>,
+}
+
+/// A measurement tagged with its WMO unit code
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuantitativeValueApi {
+    /// e.g. "wmoUnit:degC", "wmoUnit:km_h-1", "wmoUnit:Pa"
+    pub unit_code: String,
+    pub value: Option<f64>,
+}
+
+impl QuantitativeValueApi {
+    /// The value converted to degrees Celsius
+    fn celsius(&self) -> Option<f64> {
+        let value = self.value?;
+        match unit(&self.unit_code) {
+            "degC" => Some(value),
+            "degF" => Some(fahrenheit_to_celsius(value)),
+            "K" => Some(value - 273.15),
+            _ => None,
+        }
+    }
+
+    /// The value converted to kilometres per hour
+    fn kph(&self) -> Option<f64> {
+        let value = self.value?;
+        match unit(&self.unit_code) {
+            "km_h-1" => Some(value),
+            "m_s-1" => Some(value * 3.6),
+            "mi_h-1" => Some(value * MPH_TO_KPH),
+            "kt" => Some(value * 1.852),
+            _ => None,
+        }
+    }
+
+    /// The value converted to millimetres
+    fn millimetres(&self) -> Option<f64> {
+        let value = self.value?;
+        match unit(&self.unit_code) {
+            "mm" => Some(value),
+            "m" => Some(value * 1000.0),
+            _ => None,
+        }
+    }
+
+    /// The value converted to kilometres
+    fn kilometres(&self) -> Option<f64> {
+        let value = self.value?;
+        match unit(&self.unit_code) {
+            "km" => Some(value),
+            "m" => Some(value / 1000.0),
+            _ => None,
+        }
+    }
+
+    /// The value converted to hectopascals
+    fn hectopascals(&self) -> Option<f64> {
+        let value = self.value?;
+        match unit(&self.unit_code) {
+            "Pa" => Some(value / 100.0),
+            "hPa" => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Root active alerts response
+#[derive(Debug, Deserialize)]
+pub struct AlertsApi {
+    pub features: Vec<AlertFeatureApi>,
+}
+
+/// A single alert feature
+#[derive(Debug, Deserialize)]
+pub struct AlertFeatureApi {
+    pub properties: AlertPropertiesApi,
+}
+
+/// CAP alert fields
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertPropertiesApi {
+    pub id: String,
+    pub event: String,
+    pub headline: Option<String>,
+    pub severity: String,
+    pub urgency: Option<String>,
+    pub area_desc: Option<String>,
+    pub effective: Option<String>,
+    pub expires: Option<String>,
+}
+
+impl TryFrom<AlertPropertiesApi> for WeatherAlert {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AlertPropertiesApi) -> Result<Self> {
+        Ok(WeatherAlert {
+            id: value.id,
+            headline: value.headline.unwrap_or_else(|| value.event.clone()),
+            event: value.event,
+            severity: AlertSeverity::from_cap(&value.severity),
+            urgency: value
+                .urgency
+                .as_deref()
+                .map(AlertUrgency::from_cap)
+                .unwrap_or_default(),
+            areas: value
+                .area_desc
+                .as_deref()
+                .map(WeatherAlert::parse_areas)
+                .unwrap_or_default(),
+            effective: value.effective.as_deref().map(parse_rfc3339).transpose()?,
+            expires: value.expires.as_deref().map(parse_rfc3339).transpose()?,
+        })
+    }
+}
+
+/// Everything needed to build domain weather data from NWS responses
+#[derive(Debug)]
+pub struct NwsResponse {
+    pub place: GeocodingResultApi,
+    pub point: PointsApi,
+    pub forecast: HourlyForecastApi,
+    /// Latest station observation; the forecast fills in values the station did not report
+    pub observation: ObservationApi,
+    pub alerts: Option<AlertsApi>,
+    /// Reference time used to pick the current period and upcoming hours
+    pub now: OffsetDateTime,
+}
+
+impl TryFrom<NwsResponse> for WeatherData {
+    type Error = anyhow::Error;
+
+    fn try_from(value: NwsResponse) -> Result<Self> {
+        let to_local = |datetime: OffsetDateTime| match timezones::get_by_name(
+            &value.point.properties.time_zone,
+        ) {
+            Some(tz) => datetime.to_timezone(tz),
+            None => datetime,
+        };
+        let local_now = to_local(value.now);
+
+        let periods = value
+            .forecast
+            .properties
+            .periods
+            .into_iter()
+            .map(|period| Ok((parse_rfc3339(&period.start_time)?, period)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The current period is the latest one that has already started
+        let current_index = periods
+            .iter()
+            .rposition(|(time, _)| *time <= value.now)
+            .unwrap_or(0);
+        let (_, current_period) = periods
+            .get(current_index)
+            .context("Forecast contains no periods")?;
+
+        let current = current_weather(&value.observation.properties, current_period)
+            .context("Failed to parse current conditions")?;
+
+        let hourly_weather = periods
+            .iter()
+            .map(|(time, period)| hourly_weather(to_local(*time), period))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse hourly weather data")?;
+
+        let alerts = value
+            .alerts
+            .map_or_else(Vec::new, |alerts| alerts.features)
+            .into_iter()
+            .map(|feature| WeatherAlert::try_from(feature.properties))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse weather alerts")?
+            .into_iter()
+            // The alerts response may come from cache; drop anything already lapsed
+            .filter(|alert| alert.is_active_at(value.now))
+            .collect();
+
+        Ok(WeatherData {
+            current,
+            location: value.place.location(),
+            weather_day: Some(
+                WeatherDay {
+                    // NWS has no astronomy endpoint
+                    astronomy: None,
+                    hourly_weather,
+                }
+                .filter_future_hours(local_now),
+            ),
+            daily: Vec::new(),
+            alerts,
+            air_quality: None,
+            source: NwsClient::NAME.to_string(),
+            local_time: local_now,
+            time_zone: Some(value.point.properties.time_zone),
+            stale_since: None,
+        })
+    }
+}
+
+/// Build current conditions from the station observation, falling back to the current
+/// forecast period for anything the station did not report
+fn current_weather(
+    observation: &ObservationPropertiesApi,
+    period: &ForecastPeriodApi,
+) -> Result<CurrentWeather> {
+    let observed = |select: fn(&ObservationPropertiesApi) -> &Option<QuantitativeValueApi>| {
+        select(observation).as_ref()
+    };
+
+    let celsius = match observed(|o| &o.temperature).and_then(QuantitativeValueApi::celsius) {
+        Some(celsius) => celsius,
+        None => period_celsius(period)?,
+    };
+    let temperature = temperature(celsius)?;
+
+    let relative_humidity = observed(|o| &o.relative_humidity)
+        .and_then(|humidity| humidity.value)
+        .or_else(|| period.relative_humidity.as_ref()?.value)
+        .context("No relative humidity in observation or forecast")?;
+    let humidity = Humidity::new(relative_humidity as f32)
+        .with_context(|| format!("Humidity out of range: {}", relative_humidity))?;
+
+    let sustained = match observed(|o| &o.wind_speed).and_then(QuantitativeValueApi::kph) {
+        Some(kph) => kph,
+        None => parse_wind_mph(&period.wind_speed)? * MPH_TO_KPH,
+    };
+    let gusts = observed(|o| &o.wind_gust).and_then(QuantitativeValueApi::kph);
+    let wind_speed = wind_speed(sustained, gusts)?;
+
+    let wind_direction = match observed(|o| &o.wind_direction).and_then(|d| d.value) {
+        Some(degrees) => WindDirection::from_degrees(degrees),
+        None => parse_compass(&period.wind_direction)?,
+    };
+
+    // Sea-level pressure is what forecasts quote; station pressure is a last resort
+    let hectopascals = observed(|o| &o.sea_level_pressure)
+        .and_then(QuantitativeValueApi::hectopascals)
+        .or_else(|| observed(|o| &o.barometric_pressure)?.hectopascals())
+        .context("No pressure in station observation")?;
+    let pressure = Pressure::new(hectopascals.round() as u32)
+        .with_context(|| format!("Pressure out of range: {}", hectopascals))?;
+
+    let feels_like = match observed(|o| &o.wind_chill)
+        .and_then(QuantitativeValueApi::celsius)
+        .or_else(|| observed(|o| &o.heat_index)?.celsius())
+    {
+        Some(celsius) => self::temperature(celsius)?,
+        None => temperature.apparent(&humidity, &wind_speed),
+    };
+
+    let precipitation = observed(|o| &o.precipitation_last_hour)
+        .and_then(QuantitativeValueApi::millimetres)
+        .map(|mm| {
+            Precipitation::new(mm).with_context(|| format!("Precipitation out of range: {}", mm))
+        })
+        .transpose()?;
+
+    let visibility = observed(|o| &o.visibility)
+        .and_then(QuantitativeValueApi::kilometres)
+        .map(|km| Visibility::new(km).with_context(|| format!("Visibility out of range: {}", km)))
+        .transpose()?;
+
+    let condition = match observation
+        .text_description
+        .as_deref()
+        .filter(|text| !text.is_empty())
+    {
+        Some(description) => icon_condition(description, observation.icon.as_deref()),
+        None => icon_condition(&period.short_forecast, period.icon.as_deref()),
+    };
+
+    Ok(CurrentWeather {
+        last_updated: LastUpdated::from_epoch(
+            parse_rfc3339(&observation.timestamp)?.unix_timestamp(),
+        )?,
+        temperature,
+        feels_like,
+        condition,
+        humidity,
+        wind_speed,
+        wind_direction,
+        pressure,
+        precipitation,
+        // Stations report neither UV nor cloud cover as a percentage
+        uv_index: None,
+        visibility,
+        cloud_cover: None,
+    })
+}
+
+/// Build an hourly forecast entry from a forecast period
+fn hourly_weather(local_time: OffsetDateTime, period: &ForecastPeriodApi) -> Result<HourlyWeather> {
+    // The chance covers any precipitation; attribute it by the forecast wording
+    let chance = period
+        .probability_of_precipitation
+        .as_ref()
+        .and_then(|probability| probability.value)
+        .map(|percent| {
+            Probability::new(percent.round() as u8)
+                .with_context(|| format!("Probability out of range: {}", percent))
+        })
+        .transpose()?;
+    let (chance_of_rain, chance_of_snow) = if period.short_forecast.contains("Snow") {
+        (None, chance)
+    } else {
+        (chance, None)
+    };
+
+    Ok(HourlyWeather {
+        time: local_time,
+        temperature: temperature(period_celsius(period)?)?,
+        condition: icon_condition(&period.short_forecast, period.icon.as_deref()),
+        wind_speed: wind_speed(parse_wind_mph(&period.wind_speed)? * MPH_TO_KPH, None)?,
+        wind_direction: parse_compass(&period.wind_direction)?,
+        // Hourly periods carry no amount
+        precipitation: None,
+        chance_of_rain,
+        chance_of_snow,
+    })
+}
+
+const MPH_TO_KPH: f64 = 1.609344;
+
+/// Build the domain condition for a description, classified by the NWS icon URL when
+/// there is one; its path names day or night and then the condition
+fn icon_condition(description: &str, icon: Option<&str>) -> WeatherCondition {
+    let condition = WeatherCondition::new(description.to_string());
+    let Some(icon) = icon else {
+        return condition;
+    };
+    let path = icon.split('?').next().unwrap_or_default();
+    let mut segments = path
+        .split('/')
+        .skip_while(|segment| !matches!(*segment, "day" | "night"));
+    let condition = match segments.next() {
+        Some(time_of_day) => condition.with_is_day(time_of_day == "day"),
+        None => return condition,
+    };
+    // Periods that change name two conditions; the first is the one in progress
+    match segments.next() {
+        Some(code) => condition.with_kind(icon_kind(code.split(',').next().unwrap_or_default())),
+        None => condition,
+    }
+}
+
+/// Classify an NWS icon condition code (<https://api.weather.gov/icons>)
+pub fn icon_kind(code: &str) -> ConditionKind {
+    match code {
+        "skc" | "few" | "hot" | "cold" => ConditionKind::Clear,
+        "sct" => ConditionKind::PartlyCloudy,
+        "bkn" => ConditionKind::Cloudy,
+        "ovc" => ConditionKind::Overcast,
+        "wind_skc" | "wind_few" | "wind_sct" | "wind_bkn" | "wind_ovc" | "tornado" => {
+            ConditionKind::Wind
+        }
+        "snow" => ConditionKind::Snow,
+        "blizzard" => ConditionKind::HeavySnow,
+        "rain_snow" | "rain_sleet" | "snow_sleet" | "sleet" => ConditionKind::Sleet,
+        "fzra" | "rain_fzra" | "snow_fzra" => ConditionKind::FreezingRain,
+        "rain" => ConditionKind::Rain,
+        "rain_showers" | "rain_showers_hi" => ConditionKind::RainShowers,
+        "tsra" | "tsra_sct" | "tsra_hi" | "tropical_storm" | "hurricane" => {
+            ConditionKind::Thunderstorm
+        }
+        "fog" | "haze" | "smoke" | "dust" => ConditionKind::Fog,
+        _ => ConditionKind::Unknown,
+    }
+}
+
+/// Strip the "wmoUnit:" namespace from a unit code
+fn unit(unit_code: &str) -> &str {
+    unit_code.rsplit(':').next().unwrap_or(unit_code)
+}
+
+fn fahrenheit_to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// A forecast period's temperature in degrees Celsius
+fn period_celsius(period: &ForecastPeriodApi) -> Result<f64> {
+    match period.temperature_unit.as_str() {
+        "F" => Ok(fahrenheit_to_celsius(period.temperature)),
+        "C" => Ok(period.temperature),
+        other => anyhow::bail!("Unknown temperature unit: {}", other),
+    }
+}
+
+fn temperature(celsius: f64) -> Result<Temperature> {
+    Temperature::new(celsius).with_context(|| format!("Temperature out of range: {}", celsius))
+}
+
+fn wind_speed(sustained_kph: f64, gusts_kph: Option<f64>) -> Result<WindSpeed> {
+    let sustained = sustained_kph.round() as u32;
+
+    match gusts_kph.map(|kph| kph.round() as u32) {
+        Some(gusts) if gusts > sustained => WindSpeed::builder()
+            .sustained(sustained)
+            .with_gusts(gusts)
+            .build()
+            .with_context(|| {
+                format!(
+                    "Invalid wind data: sustained {} km/h, gusts {} km/h",
+                    sustained, gusts
+                )
+            }),
+        _ => WindSpeed::new(sustained)
+            .with_context(|| format!("Wind speed out of range: {}", sustained)),
+    }
+}
+
+/// Parse a forecast wind string such as "10 mph" or "5 to 15 mph", taking the upper
+/// bound of a range
+pub fn parse_wind_mph(value: &str) -> Result<f64> {
+    value
+        .trim()
+        .trim_end_matches("mph")
+        .split("to")
+        .filter_map(|part| part.trim().parse::<f64>().ok())
+        .reduce(f64::max)
+        .with_context(|| format!("Failed to parse wind speed: {}", value))
+}
+
+/// Parse a compass point; NWS leaves the direction empty in calm conditions
+fn parse_compass(value: &str) -> Result<WindDirection> {
+    if value.trim().is_empty() {
+        return Ok(WindDirection::N);
+    }
+    WindDirection::from_compass(value)
+        .with_context(|| format!("Failed to parse wind direction: {}", value))
+}
+
+fn parse_rfc3339(value: &str) -> Result<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339).with_context(|| format!("Invalid timestamp: {}", value))
+}
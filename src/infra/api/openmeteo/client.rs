@@ -0,0 +1,271 @@
+//! HTTP client for fetching weather data from the keyless Open-Meteo API.
+
+use crate::app::{LocationSearcher, WeatherFetcher};
+use crate::domain::models::{PlaceMatch, WeatherData};
+use crate::domain::{Coordinates, LocationQuery};
+use crate::infra::api::openmeteo::models::{
+    ForecastResponseApi, GeocodingResponse, GeocodingResultApi, OpenMeteoResponse,
+};
+use crate::infra::api::{http_agent, DEFAULT_TIMEOUT};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1";
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1";
+
+/// Variables requested for current conditions
+const CURRENT_FIELDS: &str = "temperature_2m,relative_humidity_2m,apparent_temperature,\
+                              precipitation,weather_code,cloud_cover,pressure_msl,\
+                              wind_speed_10m,wind_direction_10m,wind_gusts_10m,visibility,\
+                              uv_index,is_day";
+
+/// Variables requested for the hourly forecast
+const HOURLY_FIELDS: &str = "temperature_2m,precipitation_probability,precipitation,\
+                             weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m,\
+                             is_day";
+
+/// Variables requested for the daily forecast
+const DAILY_FIELDS: &str = "sunrise,sunset,temperature_2m_max,temperature_2m_min,weather_code,\
+                            precipitation_sum,precipitation_probability_max,wind_speed_10m_max,\
+                            wind_gusts_10m_max";
+
+/// Days of forecast requested: a week of daily summaries, whose hourly series also
+/// covers a full day of upcoming hours from any time of day
+const FORECAST_DAYS: u32 = 7;
+
+/// Resolves place names to coordinates using the Open-Meteo geocoding API.
+///
+/// Shared by providers whose forecast endpoints only accept coordinates.
+pub struct Geocoder {
+    agent: ureq::Agent,
+    base_url: String,
+}
+
+impl Geocoder {
+    /// Create a geocoder that issues requests through the given agent
+    pub fn new(agent: ureq::Agent) -> Self {
+        Self {
+            agent,
+            base_url: GEOCODING_URL.to_string(),
+        }
+    }
+
+    /// Resolve a location to coordinates: names and postcodes are looked up, the
+    /// best match winning, while coordinates are used as given
+    pub fn geocode(&self, location: &LocationQuery) -> Result<GeocodingResultApi> {
+        let query = match location {
+            LocationQuery::Coordinates(coordinates) => return Ok(Self::place_at(*coordinates)),
+            LocationQuery::Postcode(query) | LocationQuery::Name(query) => query,
+            // Open-Meteo's place ids are GeoNames ids, unrelated to WeatherAPI.com's
+            LocationQuery::Airport(_) | LocationQuery::Id(_) | LocationQuery::Current => {
+                anyhow::bail!(
+                    "Open-Meteo geocoding cannot look up '{}'; give a name or coordinates",
+                    location
+                )
+            }
+        };
+
+        self.search(query, 1)?
+            .into_iter()
+            .next()
+            .with_context(|| format!("No location found matching '{}'", query))
+    }
+
+    /// An unnamed place at exact coordinates, labelled with the coordinates themselves
+    fn place_at(coordinates: Coordinates) -> GeocodingResultApi {
+        GeocodingResultApi {
+            name: format!(
+                "{:.4}, {:.4}",
+                coordinates.latitude(),
+                coordinates.longitude()
+            ),
+            latitude: coordinates.latitude(),
+            longitude: coordinates.longitude(),
+            timezone: None,
+            admin1: None,
+            country: None,
+        }
+    }
+
+    /// Look up to `count` places matching a name, best match first
+    pub fn search(&self, query: &str, count: usize) -> Result<Vec<GeocodingResultApi>> {
+        let url = self.request_url(query, count);
+        let response: GeocodingResponse = self
+            .agent
+            .get(&url)
+            .call()
+            .with_context(|| format!("Failed to send request to: {}", url))?
+            .into_json()
+            .context("Failed to look up location with Open-Meteo geocoding")?;
+
+        Ok(response.results.unwrap_or_default())
+    }
+
+    /// Build the geocoding URL for a place name
+    fn request_url(&self, location: &str, count: usize) -> String {
+        format!(
+            "{}/search?name={}&count={}&language=en&format=json",
+            self.base_url,
+            urlencoding::encode(location.trim()),
+            count
+        )
+    }
+}
+
+impl LocationSearcher for Geocoder {
+    fn search_places(&self, query: &str, limit: usize) -> Result<Vec<PlaceMatch>> {
+        // Open-Meteo's ids are GeoNames ids, which no provider takes as a query
+        Ok(self
+            .search(query, limit)?
+            .iter()
+            .map(|place| PlaceMatch {
+                location: place.location(),
+                id: None,
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        OpenMeteoClient::NAME
+    }
+}
+
+/// Weather API client for the Open-Meteo service (no API key required)
+pub struct OpenMeteoClient {
+    agent: ureq::Agent,
+    geocoder: Geocoder,
+    forecast_url: String,
+}
+
+impl OpenMeteoClient {
+    /// Service name shown to the user
+    pub const NAME: &'static str = "Open-Meteo";
+
+    /// Create a new Open-Meteo client
+    pub fn new() -> Self {
+        Self::with_agent(http_agent(DEFAULT_TIMEOUT))
+    }
+
+    /// Create a client that issues requests through the given agent
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        Self {
+            geocoder: Geocoder::new(agent.clone()),
+            agent,
+            forecast_url: FORECAST_URL.to_string(),
+        }
+    }
+
+    /// Fetch weather data for a location by geocoding it first
+    pub fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        let place = self.geocoder.geocode(location)?;
+        let forecast: ForecastResponseApi = self
+            .get_json(&self.forecast_request_url(place.latitude, place.longitude))
+            .context("Failed to fetch Open-Meteo forecast")?;
+
+        OpenMeteoResponse { place, forecast }
+            .try_into()
+            .context("Failed to convert API response to domain model")
+    }
+
+    /// Build the forecast URL for a pair of coordinates, with times in the location's zone
+    fn forecast_request_url(&self, latitude: f64, longitude: f64) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&current={}&hourly={}\
+             &daily={}&timezone=auto&forecast_days={}&wind_speed_unit=kmh",
+            self.forecast_url,
+            latitude,
+            longitude,
+            CURRENT_FIELDS,
+            HOURLY_FIELDS,
+            DAILY_FIELDS,
+            FORECAST_DAYS
+        )
+    }
+
+    /// Perform a GET request and deserialize the JSON body
+    fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self
+            .agent
+            .get(url)
+            .call()
+            .with_context(|| format!("Failed to send request to: {}", url))?;
+
+        response
+            .into_json()
+            .context("Failed to parse JSON response from Open-Meteo")
+    }
+}
+
+impl std::fmt::Debug for OpenMeteoClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenMeteoClient")
+            .field("geocoding_url", &self.geocoder.base_url)
+            .field("forecast_url", &self.forecast_url)
+            .finish()
+    }
+}
+
+impl Default for OpenMeteoClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherFetcher for OpenMeteoClient {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        self.fetch_weather(location)
+    }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geocoding_request_url() {
+        let geocoder = Geocoder::new(ureq::agent());
+        assert_eq!(
+            geocoder.request_url(" São Paulo ", 1),
+            "https://geocoding-api.open-meteo.com/v1/search?name=S%C3%A3o%20Paulo&count=1&language=en&format=json"
+        );
+        assert!(geocoder
+            .request_url("Springfield", 10)
+            .contains("name=Springfield&count=10&"));
+    }
+
+    #[test]
+    fn test_geocode_without_lookup() {
+        let geocoder = Geocoder::new(ureq::agent());
+
+        // Coordinates are used as given, without a request
+        let place = geocoder
+            .geocode(&"-41.2866,174.7756".parse().unwrap())
+            .unwrap();
+        assert_eq!(place.name, "-41.2866, 174.7756");
+        assert_eq!((place.latitude, place.longitude), (-41.2866, 174.7756));
+        assert_eq!(place.timezone, None);
+
+        // Airports and WeatherAPI.com ids are left to providers that understand them
+        let error = geocoder.geocode(&"iata:WLG".parse().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("cannot look up 'iata:WLG'"));
+        assert!(geocoder.geocode(&"id:2801268".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_forecast_request_url() {
+        let client = OpenMeteoClient::new();
+        let url = client.forecast_request_url(-41.28664, 174.77557);
+
+        assert!(url.starts_with("https://api.open-meteo.com/v1/forecast?"));
+        assert!(url.contains("latitude=-41.28664&longitude=174.77557"));
+        assert!(url.contains("current=temperature_2m,relative_humidity_2m,"));
+        assert!(url.contains("timezone=auto"));
+        assert!(url.contains("wind_speed_unit=kmh"));
+        assert!(!url.contains(' '));
+    }
+}
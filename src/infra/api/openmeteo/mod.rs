@@ -0,0 +1,210 @@
+//! Open-Meteo provider: a keyless alternative to WeatherAPI.com.
+
+pub mod client;
+pub mod models;
+
+pub use client::*;
+
+#[cfg(test)]
+mod tests {
+    use super::models::*;
+    use crate::domain::models::*;
+    use crate::domain::{ConditionKind, UvCategory, WindDirection};
+
+    const GEOCODING_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/openmeteo/geocoding_wellington.json");
+    const GEOCODING_EMPTY_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/openmeteo/geocoding_empty.json");
+    const FORECAST_FIXTURE: &str =
+        include_str!("../../../../tests/fixtures/openmeteo/forecast_wellington.json");
+
+    fn fixture_place() -> GeocodingResultApi {
+        let geocoding: GeocodingResponse =
+            serde_json::from_str(GEOCODING_FIXTURE).expect("Valid geocoding JSON");
+        geocoding.results.unwrap().remove(0)
+    }
+
+    fn fixture_weather_data() -> WeatherData {
+        let place = fixture_place();
+        let forecast: ForecastResponseApi =
+            serde_json::from_str(FORECAST_FIXTURE).expect("Valid forecast JSON");
+
+        OpenMeteoResponse { place, forecast }
+            .try_into()
+            .expect("Valid domain conversion")
+    }
+
+    #[test]
+    fn test_geocoding_parsing() {
+        let geocoding: GeocodingResponse =
+            serde_json::from_str(GEOCODING_FIXTURE).expect("Valid JSON");
+        let place = &geocoding.results.as_ref().unwrap()[0];
+
+        assert_eq!(place.name, "Wellington");
+        assert_eq!(place.timezone.as_deref(), Some("Pacific/Auckland"));
+        assert_eq!(place.admin1.as_deref(), Some("Wellington"));
+        assert_eq!(place.country.as_deref(), Some("New Zealand"));
+        assert!((place.latitude - -41.28664).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_time_zone_from_forecast_for_coordinates() {
+        let mut place = fixture_place();
+        place.timezone = None;
+        let forecast: ForecastResponseApi =
+            serde_json::from_str(FORECAST_FIXTURE).expect("Valid forecast JSON");
+
+        let weather_data: WeatherData = OpenMeteoResponse { place, forecast }
+            .try_into()
+            .expect("Valid domain conversion");
+        assert_eq!(weather_data.time_zone.as_deref(), Some("Pacific/Auckland"));
+    }
+
+    #[test]
+    fn test_geocoding_no_results() {
+        let geocoding: GeocodingResponse =
+            serde_json::from_str(GEOCODING_EMPTY_FIXTURE).expect("Valid JSON");
+        assert!(geocoding.results.is_none());
+    }
+
+    #[test]
+    fn test_forecast_current_conversion() {
+        let weather_data = fixture_weather_data();
+
+        assert_eq!(weather_data.location.to_string(), "Wellington");
+        assert_eq!(weather_data.location.country(), Some("New Zealand"));
+        assert!(weather_data.location.coordinates().is_some());
+        assert_eq!(weather_data.source, "Open-Meteo");
+        assert_eq!(weather_data.current.temperature.as_celsius(), 20);
+        assert_eq!(weather_data.current.feels_like.as_celsius(), 19);
+        assert_eq!(weather_data.current.humidity.as_int(), 60);
+        assert_eq!(weather_data.current.pressure.value(), 1013);
+        assert_eq!(
+            weather_data.current.cloud_cover.map(|c| c.as_percent()),
+            Some(43)
+        );
+        assert_eq!(
+            weather_data.current.visibility.map(|v| v.as_km()),
+            Some(24.14)
+        );
+        let uv = weather_data.current.uv_index.expect("UV index present");
+        assert_eq!(uv.as_int(), 7);
+        assert_eq!(uv.category(), UvCategory::High);
+        assert_eq!(weather_data.current.condition.to_string(), "Partly cloudy");
+        assert_eq!(weather_data.current.condition.code(), Some(2));
+        assert_eq!(weather_data.current.condition.is_day(), Some(true));
+        assert_eq!(weather_data.current.wind_direction, WindDirection::NW);
+        assert_eq!(
+            weather_data.current.wind_speed.to_string(),
+            "15 km/h (Gusts: 30 km/h)"
+        );
+        // 14:30 NZDT (UTC+13) is 01:30 UTC
+        assert_eq!(
+            weather_data.current.last_updated.to_string(),
+            "2023-01-13 01:30Z"
+        );
+    }
+
+    #[test]
+    fn test_forecast_astronomy_conversion() {
+        let weather_data = fixture_weather_data();
+        let astronomy = weather_data
+            .weather_day
+            .as_ref()
+            .and_then(|day| day.astronomy.as_ref())
+            .expect("Astronomy present");
+
+        assert_eq!(astronomy.sunrise().format_24h(), "05:58");
+        assert_eq!(astronomy.sunset().format_24h(), "20:55");
+    }
+
+    #[test]
+    fn test_forecast_daily_conversion() {
+        let weather_data = fixture_weather_data();
+        assert_eq!(weather_data.daily.len(), 2);
+
+        let today = &weather_data.daily[0];
+        assert_eq!(today.date, time::macros::date!(2023 - 01 - 13));
+        assert_eq!(today.max_temperature.as_celsius(), 23);
+        assert_eq!(today.min_temperature.as_celsius(), 15);
+        assert_eq!(today.condition.to_string(), "Slight rain");
+        assert_eq!(today.condition.is_day(), None);
+        assert_eq!(today.chance_of_rain.map(|c| c.as_percent()), Some(80));
+        assert_eq!(today.precipitation.as_mm(), 3.4);
+        assert_eq!(today.max_wind.to_string(), "28 km/h (Gusts: 53 km/h)");
+
+        let tomorrow = &weather_data.daily[1];
+        assert!(tomorrow.chance_of_rain.is_none());
+        assert_eq!(tomorrow.max_wind.to_string(), "19 km/h");
+    }
+
+    #[test]
+    fn test_forecast_hourly_filtered_from_local_hour() {
+        let weather_data = fixture_weather_data();
+        let hours = &weather_data.weather_day.as_ref().unwrap().hourly_weather;
+
+        // Local time is 14:30, so hours from 14:00 through the end of tomorrow remain
+        assert_eq!(hours.len(), 34);
+        assert_eq!(hours[0].time, time::macros::datetime!(2023-01-13 14:00 +13));
+        assert_eq!(hours[0].temperature.as_celsius(), 21);
+        assert_eq!(hours[0].condition.to_string(), "Mainly clear");
+        assert_eq!(hours[3].condition.to_string(), "Slight rain");
+        assert_eq!(hours[3].condition.kind(), ConditionKind::Rain);
+        // Sunset is at 20:55
+        assert_eq!(hours[6].condition.is_day(), Some(true));
+        assert_eq!(hours[7].condition.is_day(), Some(false));
+        assert_eq!(hours[3].wind_direction, WindDirection::WSW);
+        assert_eq!(hours[3].chance_of_rain.map(|c| c.as_percent()), Some(80));
+        assert_eq!(hours[3].precipitation.map(|p| p.as_mm()), Some(1.2));
+        assert!(hours[3].rain_likely());
+        assert!(!hours[0].rain_likely());
+        assert_eq!(
+            hours[10].time,
+            time::macros::datetime!(2023-01-14 00:00 +13)
+        );
+        assert_eq!(
+            hours.last().unwrap().time,
+            time::macros::datetime!(2023-01-14 23:00 +13)
+        );
+        assert_eq!(
+            weather_data.local_time,
+            time::macros::datetime!(2023-01-13 14:30 +13)
+        );
+    }
+
+    #[test]
+    fn test_hourly_mismatched_lengths_rejected() {
+        let hourly = HourlyApi {
+            time: vec![
+                "2023-01-13T00:00".to_string(),
+                "2023-01-13T01:00".to_string(),
+            ],
+            temperature_2m: vec![15.0],
+            precipitation_probability: vec![None, None],
+            precipitation: vec![0.0, 0.0],
+            weather_code: vec![0, 0],
+            wind_speed_10m: vec![10.0, 10.0],
+            wind_direction_10m: vec![0.0, 0.0],
+            wind_gusts_10m: vec![12.0, 12.0],
+            is_day: vec![1, 1],
+        };
+
+        let offset = time::UtcOffset::UTC;
+        let result: anyhow::Result<Vec<HourlyWeather>> =
+            HourlyWithOffset { hourly, offset }.try_into();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_wmo_description() {
+        assert_eq!(wmo_description(0), "Clear sky");
+        assert_eq!(wmo_description(3), "Overcast");
+        assert_eq!(wmo_description(95), "Thunderstorm");
+        assert_eq!(wmo_description(42), "Unknown");
+
+        assert_eq!(wmo_kind(1), ConditionKind::Clear);
+        assert_eq!(wmo_kind(81), ConditionKind::RainShowers);
+        assert_eq!(wmo_kind(96), ConditionKind::Thunderstorm);
+        assert_eq!(wmo_kind(42), ConditionKind::Unknown);
+    }
+}
@@ -0,0 +1,521 @@
+//! API models for deserializing Open-Meteo JSON responses and converting to domain types.
+//!
+//! Open-Meteo splits location lookup and weather data across two services: the geocoding
+//! API resolves a place name to coordinates, and the forecast API returns current
+//! conditions plus column-oriented hourly and daily series for those coordinates.
+
+use crate::domain::{
+    Astronomy, CloudCover, ConditionKind, Coordinates, CurrentWeather, DailyForecast,
+    HourlyWeather, Humidity, LastUpdated, Location, Precipitation, Pressure, Probability,
+    Temperature, UvIndex, Visibility, WeatherCondition, WeatherData, WeatherDay, WeatherTime,
+    WindDirection, WindSpeed,
+};
+use crate::infra::api::openmeteo::OpenMeteoClient;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use time::{macros::format_description, Date, PrimitiveDateTime, UtcOffset};
+
+/// Root geocoding API response from Open-Meteo
+#[derive(Debug, Deserialize)]
+pub struct GeocodingResponse {
+    /// Absent entirely when nothing matched the query
+    pub results: Option<Vec<GeocodingResultApi>>,
+}
+
+/// Single place match from the Open-Meteo geocoding API
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeocodingResultApi {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// IANA time zone name, e.g. "Pacific/Auckland"
+    pub timezone: Option<String>,
+    /// First-level administrative area, e.g. a state or region
+    pub admin1: Option<String>,
+    pub country: Option<String>,
+}
+
+impl GeocodingResultApi {
+    /// The place as a domain location, with its region, country and coordinates
+    pub fn location(&self) -> Location {
+        Location::new(self.name.clone())
+            .with_region(self.admin1.clone())
+            .with_country(self.country.clone())
+            .with_coordinates(Coordinates::new(self.latitude, self.longitude).ok())
+    }
+}
+
+/// Root forecast API response from Open-Meteo
+#[derive(Debug, Deserialize)]
+pub struct ForecastResponseApi {
+    pub utc_offset_seconds: i32,
+    /// IANA time zone name resolved for the coordinates, e.g. "Pacific/Auckland"
+    pub timezone: Option<String>,
+    pub current: CurrentApi,
+    pub hourly: Option<HourlyApi>,
+    pub daily: Option<DailyApi>,
+}
+
+/// A resolved place together with the forecast fetched for it
+#[derive(Debug)]
+pub struct OpenMeteoResponse {
+    pub place: GeocodingResultApi,
+    pub forecast: ForecastResponseApi,
+}
+
+impl TryFrom<OpenMeteoResponse> for WeatherData {
+    type Error = anyhow::Error;
+
+    fn try_from(value: OpenMeteoResponse) -> Result<Self> {
+        let offset = UtcOffset::from_whole_seconds(value.forecast.utc_offset_seconds)
+            .with_context(|| {
+                format!("Invalid UTC offset: {}", value.forecast.utc_offset_seconds)
+            })?;
+
+        // Times are requested in the location's zone, so the current time is local
+        let local_time = parse_local_datetime(&value.forecast.current.time)?.assume_offset(offset);
+
+        let current = CurrentWithOffset {
+            current: value.forecast.current,
+            offset,
+        }
+        .try_into()
+        .context("Failed to parse current conditions")?;
+
+        let location = value.place.location();
+
+        let daily = value
+            .forecast
+            .daily
+            .as_ref()
+            .map(DailyApi::daily_forecasts)
+            .transpose()
+            .context("Failed to parse daily forecast")?
+            .unwrap_or_default();
+
+        let astronomy = value
+            .forecast
+            .daily
+            .map(Astronomy::try_from)
+            .transpose()
+            .context("Failed to parse astronomy data")?;
+
+        let hourly_weather = value
+            .forecast
+            .hourly
+            .map(|hourly| Vec::<HourlyWeather>::try_from(HourlyWithOffset { hourly, offset }))
+            .transpose()
+            .context("Failed to parse hourly weather data")?
+            .unwrap_or_default();
+
+        let weather_day = Some(
+            WeatherDay {
+                astronomy,
+                hourly_weather,
+            }
+            .filter_future_hours(local_time),
+        );
+
+        Ok(WeatherData {
+            current,
+            location,
+            weather_day,
+            daily,
+            alerts: Vec::new(),
+            air_quality: None,
+            source: OpenMeteoClient::NAME.to_string(),
+            local_time,
+            // Coordinates given directly have no geocoded zone; the forecast resolves one
+            time_zone: value.place.timezone.or(value.forecast.timezone),
+            stale_since: None,
+        })
+    }
+}
+
+/// Current conditions from Open-Meteo
+#[derive(Debug, Deserialize)]
+pub struct CurrentApi {
+    /// Local time of the observation, e.g. "2023-01-13T14:30"
+    pub time: String,
+    pub temperature_2m: f64,
+    pub relative_humidity_2m: f64,
+    pub apparent_temperature: f64,
+    /// Precipitation over the preceding 15 minutes, in mm
+    pub precipitation: f64,
+    pub weather_code: u8,
+    /// Percent
+    pub cloud_cover: f64,
+    pub pressure_msl: f64,
+    pub wind_speed_10m: f64,
+    pub wind_direction_10m: f64,
+    pub wind_gusts_10m: f64,
+    /// Metres; null where the weather model does not forecast it
+    pub visibility: Option<f64>,
+    /// Null where the weather model does not forecast it
+    pub uv_index: Option<f64>,
+    /// 1 during daylight, 0 at night
+    pub is_day: u8,
+}
+
+/// Current conditions paired with the location's UTC offset, needed to resolve the
+/// local observation time to an absolute timestamp
+struct CurrentWithOffset {
+    current: CurrentApi,
+    offset: UtcOffset,
+}
+
+impl TryFrom<CurrentWithOffset> for CurrentWeather {
+    type Error = anyhow::Error;
+
+    fn try_from(value: CurrentWithOffset) -> Result<Self> {
+        let CurrentWithOffset { current, offset } = value;
+
+        let epoch = parse_local_datetime(&current.time)?
+            .assume_offset(offset)
+            .unix_timestamp();
+        let last_updated = LastUpdated::from_epoch(epoch)
+            .with_context(|| format!("Failed to parse observation time: {}", current.time))?;
+
+        let temperature = Temperature::new(current.temperature_2m)
+            .with_context(|| format!("Temperature out of range: {}", current.temperature_2m))?;
+
+        let feels_like = Temperature::new(current.apparent_temperature).with_context(|| {
+            format!(
+                "Feels like temperature out of range: {}",
+                current.apparent_temperature
+            )
+        })?;
+
+        let humidity = Humidity::new(current.relative_humidity_2m as f32)
+            .with_context(|| format!("Humidity out of range: {}", current.relative_humidity_2m))?;
+
+        let wind_speed = wind_speed(current.wind_speed_10m, current.wind_gusts_10m)?;
+
+        let pressure = Pressure::new(current.pressure_msl.round() as u32)
+            .with_context(|| format!("Pressure out of range: {}", current.pressure_msl))?;
+
+        let cloud_cover = CloudCover::new(current.cloud_cover.round() as u8)
+            .with_context(|| format!("Cloud cover out of range: {}", current.cloud_cover))?;
+
+        let visibility = current
+            .visibility
+            .map(|metres| {
+                Visibility::new(metres / 1000.0)
+                    .with_context(|| format!("Visibility out of range: {} m", metres))
+            })
+            .transpose()?;
+
+        let uv_index = current
+            .uv_index
+            .map(|uv| {
+                UvIndex::new(uv as f32).with_context(|| format!("UV index out of range: {}", uv))
+            })
+            .transpose()?;
+
+        Ok(CurrentWeather {
+            last_updated,
+            temperature,
+            feels_like,
+            condition: wmo_condition(current.weather_code, current.is_day),
+            humidity,
+            wind_speed,
+            wind_direction: WindDirection::from_degrees(current.wind_direction_10m),
+            pressure,
+            precipitation: Some(precipitation(current.precipitation)?),
+            uv_index,
+            visibility,
+            cloud_cover: Some(cloud_cover),
+        })
+    }
+}
+
+/// Hourly forecast series from Open-Meteo (one array per variable)
+#[derive(Debug, Deserialize)]
+pub struct HourlyApi {
+    pub time: Vec<String>,
+    pub temperature_2m: Vec<f64>,
+    /// Chance of any precipitation; null where the model has no ensemble
+    pub precipitation_probability: Vec<Option<u8>>,
+    pub precipitation: Vec<f64>,
+    pub weather_code: Vec<u8>,
+    pub wind_speed_10m: Vec<f64>,
+    pub wind_direction_10m: Vec<f64>,
+    pub wind_gusts_10m: Vec<f64>,
+    pub is_day: Vec<u8>,
+}
+
+/// Hourly series paired with the location's UTC offset, needed to resolve local
+/// times to absolute timestamps
+pub struct HourlyWithOffset {
+    pub hourly: HourlyApi,
+    pub offset: UtcOffset,
+}
+
+impl TryFrom<HourlyWithOffset> for Vec<HourlyWeather> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: HourlyWithOffset) -> Result<Self> {
+        let HourlyWithOffset {
+            hourly: value,
+            offset,
+        } = value;
+
+        let len = value.time.len();
+        let lengths = [
+            value.temperature_2m.len(),
+            value.precipitation_probability.len(),
+            value.precipitation.len(),
+            value.weather_code.len(),
+            value.wind_speed_10m.len(),
+            value.wind_direction_10m.len(),
+            value.wind_gusts_10m.len(),
+            value.is_day.len(),
+        ];
+        if lengths.iter().any(|&l| l != len) {
+            anyhow::bail!(
+                "Hourly series have mismatched lengths: {} vs {:?}",
+                len,
+                lengths
+            );
+        }
+
+        (0..len)
+            .map(|i| {
+                let time = parse_local_datetime(&value.time[i])?.assume_offset(offset);
+
+                let temperature = Temperature::new(value.temperature_2m[i]).with_context(|| {
+                    format!("Temperature out of range: {}", value.temperature_2m[i])
+                })?;
+
+                // The chance covers any precipitation; attribute it by the expected weather
+                let chance = value.precipitation_probability[i]
+                    .map(|chance| {
+                        Probability::new(chance)
+                            .with_context(|| format!("Probability out of range: {}", chance))
+                    })
+                    .transpose()?;
+                let (chance_of_rain, chance_of_snow) = if is_snow(value.weather_code[i]) {
+                    (None, chance)
+                } else {
+                    (chance, None)
+                };
+
+                Ok(HourlyWeather {
+                    time,
+                    temperature,
+                    condition: wmo_condition(value.weather_code[i], value.is_day[i]),
+                    wind_speed: wind_speed(value.wind_speed_10m[i], value.wind_gusts_10m[i])?,
+                    wind_direction: WindDirection::from_degrees(value.wind_direction_10m[i]),
+                    precipitation: Some(precipitation(value.precipitation[i])?),
+                    chance_of_rain,
+                    chance_of_snow,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Daily series from Open-Meteo (one array per variable); only the first day's sunrise
+/// and sunset are used
+#[derive(Debug, Deserialize)]
+pub struct DailyApi {
+    /// Local date, e.g. "2023-01-13"
+    pub time: Vec<String>,
+    pub sunrise: Vec<String>,
+    pub sunset: Vec<String>,
+    pub temperature_2m_max: Vec<f64>,
+    pub temperature_2m_min: Vec<f64>,
+    pub weather_code: Vec<u8>,
+    pub precipitation_sum: Vec<f64>,
+    /// Highest chance of any precipitation; null where the model has no ensemble
+    pub precipitation_probability_max: Vec<Option<u8>>,
+    pub wind_speed_10m_max: Vec<f64>,
+    pub wind_gusts_10m_max: Vec<f64>,
+}
+
+impl DailyApi {
+    /// Summarise each day for the multi-day forecast
+    fn daily_forecasts(&self) -> Result<Vec<DailyForecast>> {
+        let len = self.time.len();
+        let lengths = [
+            self.temperature_2m_max.len(),
+            self.temperature_2m_min.len(),
+            self.weather_code.len(),
+            self.precipitation_sum.len(),
+            self.precipitation_probability_max.len(),
+            self.wind_speed_10m_max.len(),
+            self.wind_gusts_10m_max.len(),
+        ];
+        if lengths.iter().any(|&l| l != len) {
+            anyhow::bail!(
+                "Daily series have mismatched lengths: {} vs {:?}",
+                len,
+                lengths
+            );
+        }
+
+        (0..len)
+            .map(|i| {
+                let date = Date::parse(&self.time[i], format_description!("[year]-[month]-[day]"))
+                    .with_context(|| format!("Invalid forecast date: {}", self.time[i]))?;
+
+                let min_temperature =
+                    Temperature::new(self.temperature_2m_min[i]).with_context(|| {
+                        format!("Temperature out of range: {}", self.temperature_2m_min[i])
+                    })?;
+                let max_temperature =
+                    Temperature::new(self.temperature_2m_max[i]).with_context(|| {
+                        format!("Temperature out of range: {}", self.temperature_2m_max[i])
+                    })?;
+
+                // The chance covers any precipitation; a snowy day's is not a chance of rain
+                let chance = self.precipitation_probability_max[i]
+                    .map(|chance| {
+                        Probability::new(chance)
+                            .with_context(|| format!("Probability out of range: {}", chance))
+                    })
+                    .transpose()?;
+                let chance_of_rain = if is_snow(self.weather_code[i]) {
+                    None
+                } else {
+                    chance
+                };
+
+                Ok(DailyForecast {
+                    date,
+                    min_temperature,
+                    max_temperature,
+                    condition: wmo_summary(self.weather_code[i]),
+                    chance_of_rain,
+                    precipitation: precipitation(self.precipitation_sum[i])?,
+                    max_wind: wind_speed(self.wind_speed_10m_max[i], self.wind_gusts_10m_max[i])?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<DailyApi> for Astronomy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DailyApi) -> Result<Self> {
+        let parse = |label: &str, series: &[String]| -> Result<WeatherTime> {
+            let raw = series
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("Missing {} in daily data", label))?;
+            let datetime = parse_local_datetime(raw)?;
+            WeatherTime::parse(&format!("{:02}:{:02}", datetime.hour(), datetime.minute()))
+                .with_context(|| format!("Failed to parse {}: {}", label, raw))
+        };
+
+        Ok(Astronomy::new(
+            parse("sunrise", &value.sunrise)?,
+            parse("sunset", &value.sunset)?,
+        ))
+    }
+}
+
+/// Parse Open-Meteo's ISO 8601 local time format (e.g., "2023-01-13T14:30")
+fn parse_local_datetime(value: &str) -> Result<PrimitiveDateTime> {
+    let format = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+    PrimitiveDateTime::parse(value, &format)
+        .with_context(|| format!("Invalid Open-Meteo time: {}", value))
+}
+
+/// Build a validated wind speed from km/h values, keeping gusts only when they exceed
+/// the sustained wind
+fn wind_speed(sustained_kph: f64, gust_kph: f64) -> Result<WindSpeed> {
+    let sustained = sustained_kph.round() as u32;
+    let gusts = gust_kph.round() as u32;
+
+    if gusts > sustained {
+        WindSpeed::builder()
+            .sustained(sustained)
+            .with_gusts(gusts)
+            .build()
+            .with_context(|| {
+                format!(
+                    "Invalid wind data: sustained {} km/h, gusts {} km/h",
+                    sustained, gusts
+                )
+            })
+    } else {
+        WindSpeed::new(sustained).with_context(|| format!("Wind speed out of range: {}", sustained))
+    }
+}
+
+fn precipitation(mm: f64) -> Result<Precipitation> {
+    Precipitation::new(mm).with_context(|| format!("Precipitation out of range: {}", mm))
+}
+
+/// Whether a WMO weather interpretation code describes snow
+fn is_snow(code: u8) -> bool {
+    matches!(code, 71..=77 | 85 | 86)
+}
+
+/// Build the domain condition for a WMO weather interpretation code and day flag
+fn wmo_condition(code: u8, is_day: u8) -> WeatherCondition {
+    wmo_summary(code).with_is_day(is_day == 1)
+}
+
+/// Build the domain condition for a WMO weather interpretation code covering a whole day
+fn wmo_summary(code: u8) -> WeatherCondition {
+    WeatherCondition::new(wmo_description(code).to_string())
+        .with_code(code.into())
+        .with_kind(wmo_kind(code))
+}
+
+/// Classify a WMO weather interpretation code as used by Open-Meteo
+pub fn wmo_kind(code: u8) -> ConditionKind {
+    match code {
+        0 | 1 => ConditionKind::Clear,
+        2 => ConditionKind::PartlyCloudy,
+        3 => ConditionKind::Overcast,
+        45 | 48 => ConditionKind::Fog,
+        51 | 53 | 55 => ConditionKind::Drizzle,
+        56 | 57 | 66 | 67 => ConditionKind::FreezingRain,
+        61 | 63 => ConditionKind::Rain,
+        65 | 82 => ConditionKind::HeavyRain,
+        71 | 73 | 77 => ConditionKind::Snow,
+        75 => ConditionKind::HeavySnow,
+        80 | 81 => ConditionKind::RainShowers,
+        85 | 86 => ConditionKind::SnowShowers,
+        95 | 96 | 99 => ConditionKind::Thunderstorm,
+        _ => ConditionKind::Unknown,
+    }
+}
+
+/// Describe a WMO weather interpretation code as used by Open-Meteo
+pub fn wmo_description(code: u8) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51 => "Light drizzle",
+        53 => "Moderate drizzle",
+        55 => "Dense drizzle",
+        56 => "Light freezing drizzle",
+        57 => "Dense freezing drizzle",
+        61 => "Slight rain",
+        63 => "Moderate rain",
+        65 => "Heavy rain",
+        66 => "Light freezing rain",
+        67 => "Heavy freezing rain",
+        71 => "Slight snow fall",
+        73 => "Moderate snow fall",
+        75 => "Heavy snow fall",
+        77 => "Snow grains",
+        80 => "Slight rain showers",
+        81 => "Moderate rain showers",
+        82 => "Violent rain showers",
+        85 => "Slight snow showers",
+        86 => "Heavy snow showers",
+        95 => "Thunderstorm",
+        96 => "Thunderstorm with slight hail",
+        99 => "Thunderstorm with heavy hail",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,231 @@
+//! Selection of the weather data provider backing the `WeatherFetcher` port.
+
+use crate::app::{FallbackFetcher, LocationSearcher, WeatherFetcher};
+use crate::infra::api::metno::MetNoClient;
+use crate::infra::api::nws::NwsClient;
+use crate::infra::api::openmeteo::{Geocoder, OpenMeteoClient};
+use crate::infra::api::WeatherClient;
+
+use anyhow::Result;
+use std::fmt;
+use std::str::FromStr;
+
+/// Providers tried, in order, when none are chosen on the command line
+pub const DEFAULT_PROVIDERS: &[Provider] = &[Provider::WeatherApi, Provider::OpenMeteo];
+
+/// Every provider, in the order they are listed to the user
+pub const ALL_PROVIDERS: &[Provider] = &[
+    Provider::WeatherApi,
+    Provider::OpenMeteo,
+    Provider::MetNo,
+    Provider::Nws,
+];
+
+/// Weather data providers that can be chosen on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Provider {
+    /// WeatherAPI.com (requires `WEATHER_API_KEY`)
+    #[default]
+    WeatherApi,
+    /// Open-Meteo (no API key required)
+    OpenMeteo,
+    /// MET Norway locationforecast (no API key required)
+    MetNo,
+    /// US National Weather Service (no API key required; United States only)
+    Nws,
+}
+
+impl Provider {
+    /// Human-readable service name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::WeatherApi => WeatherClient::NAME,
+            Self::OpenMeteo => OpenMeteoClient::NAME,
+            Self::MetNo => MetNoClient::NAME,
+            Self::Nws => NwsClient::NAME,
+        }
+    }
+
+    /// Parse a comma-separated list of providers in order of preference
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        let providers = s
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        if providers.is_empty() {
+            anyhow::bail!("At least one provider is required");
+        }
+        Ok(providers)
+    }
+
+    /// Display name of a provider chain (e.g., "WeatherAPI.com → Open-Meteo")
+    pub fn chain_name(providers: &[Self]) -> String {
+        providers
+            .iter()
+            .map(|provider| provider.name())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    /// Construct a fallback chain trying each provider in order.
+    ///
+    /// Providers that cannot be set up (e.g., WeatherAPI.com without an API key) are
+    /// left out of the chain; it is an error only if none can be set up. All providers
+    /// issue requests through `agent`, sharing its connection pool.
+    pub fn create_chain(providers: &[Self], agent: &ureq::Agent) -> Result<FallbackFetcher> {
+        let mut fetchers = Vec::new();
+        let mut failures = Vec::new();
+
+        for provider in providers {
+            match provider.create_fetcher(agent.clone()) {
+                Ok(fetcher) => fetchers.push(fetcher),
+                Err(e) if providers.len() == 1 => return Err(e),
+                Err(e) => failures.push(format!("• {}: {:#}", provider.name(), e)),
+            }
+        }
+
+        if fetchers.is_empty() {
+            anyhow::bail!(
+                "No weather provider could be set up:\n{}",
+                failures.join("\n")
+            );
+        }
+        Ok(FallbackFetcher::new(fetchers))
+    }
+
+    /// Construct the fetcher for this provider, issuing requests through `agent`
+    pub fn create_fetcher(
+        self,
+        agent: ureq::Agent,
+    ) -> Result<Box<dyn WeatherFetcher + Send + Sync>> {
+        Ok(match self {
+            Self::WeatherApi => Box::new(WeatherClient::with_agent(agent)?),
+            Self::OpenMeteo => Box::new(OpenMeteoClient::with_agent(agent)),
+            Self::MetNo => Box::new(MetNoClient::with_agent(agent)),
+            Self::Nws => Box::new(NwsClient::with_agent(agent)),
+        })
+    }
+
+    /// Construct the searcher that finds places the way this provider resolves names:
+    /// WeatherAPI.com has its own search, the others geocode with Open-Meteo
+    pub fn create_searcher(self, agent: ureq::Agent) -> Result<Box<dyn LocationSearcher>> {
+        Ok(match self {
+            Self::WeatherApi => Box::new(WeatherClient::with_agent(agent)?),
+            Self::OpenMeteo | Self::MetNo | Self::Nws => Box::new(Geocoder::new(agent)),
+        })
+    }
+}
+
+impl FromStr for Provider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "weatherapi" | "weatherapi.com" => Ok(Self::WeatherApi),
+            "openmeteo" | "open-meteo" => Ok(Self::OpenMeteo),
+            "metno" | "met.no" | "yr" => Ok(Self::MetNo),
+            "nws" | "weather.gov" => Ok(Self::Nws),
+            other => anyhow::bail!(
+                "Unknown provider '{}'. Available providers: weatherapi, openmeteo, metno, nws",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::api::{http_agent, DEFAULT_TIMEOUT};
+
+    #[test]
+    fn test_provider_from_str() {
+        assert_eq!(
+            "weatherapi".parse::<Provider>().unwrap(),
+            Provider::WeatherApi
+        );
+        assert_eq!(
+            "WeatherAPI.com".parse::<Provider>().unwrap(),
+            Provider::WeatherApi
+        );
+        assert_eq!(
+            "openmeteo".parse::<Provider>().unwrap(),
+            Provider::OpenMeteo
+        );
+        assert_eq!(
+            " Open-Meteo ".parse::<Provider>().unwrap(),
+            Provider::OpenMeteo
+        );
+        assert_eq!("NWS".parse::<Provider>().unwrap(), Provider::Nws);
+        assert_eq!("weather.gov".parse::<Provider>().unwrap(), Provider::Nws);
+    }
+
+    #[test]
+    fn test_provider_from_str_unknown() {
+        let error = "accuweather".parse::<Provider>().unwrap_err();
+        assert!(error.to_string().contains("Unknown provider 'accuweather'"));
+    }
+
+    #[test]
+    fn test_default_provider_is_weatherapi() {
+        assert_eq!(Provider::default(), Provider::WeatherApi);
+        assert_eq!(Provider::default().to_string(), "WeatherAPI.com");
+    }
+
+    #[test]
+    fn test_provider_parse_list() {
+        assert_eq!(
+            Provider::parse_list("weatherapi,openmeteo").unwrap(),
+            vec![Provider::WeatherApi, Provider::OpenMeteo]
+        );
+        assert_eq!(
+            Provider::parse_list("nws, metno,").unwrap(),
+            vec![Provider::Nws, Provider::MetNo]
+        );
+        assert!(Provider::parse_list("").is_err());
+        assert!(Provider::parse_list("openmeteo,nope").is_err());
+    }
+
+    #[test]
+    fn test_chain_name() {
+        assert_eq!(
+            Provider::chain_name(DEFAULT_PROVIDERS),
+            "WeatherAPI.com → Open-Meteo"
+        );
+    }
+
+    #[test]
+    fn test_create_chain_keeps_order() {
+        let chain = Provider::create_chain(
+            &[Provider::MetNo, Provider::OpenMeteo],
+            &http_agent(DEFAULT_TIMEOUT),
+        )
+        .unwrap();
+        assert_eq!(chain.name(), "MET Norway → Open-Meteo");
+    }
+
+    #[test]
+    fn test_open_meteo_needs_no_api_key() {
+        assert!(Provider::OpenMeteo
+            .create_fetcher(http_agent(DEFAULT_TIMEOUT))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_keyless_providers_search_with_open_meteo() {
+        for provider in [Provider::OpenMeteo, Provider::MetNo, Provider::Nws] {
+            let searcher = provider
+                .create_searcher(http_agent(DEFAULT_TIMEOUT))
+                .unwrap();
+            assert_eq!(searcher.name(), "Open-Meteo");
+        }
+    }
+}
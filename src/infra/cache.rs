@@ -0,0 +1,306 @@
+//! Caching decorator for any `WeatherFetcher`.
+//!
+//! The last successful result for each location is persisted to disk. While it is
+//! younger than the TTL it is served without a network request; once it is older, a
+//! fresh fetch is attempted and, if that fails, the old data is returned marked as
+//! stale so the bar keeps showing weather through short outages (e.g., after resume
+//! on flaky Wi-Fi). Cached data is rolled forward to the current time so hours that
+//! have since passed are not shown as upcoming.
+
+use crate::app::WeatherFetcher;
+use crate::domain::{LocationQuery, WeatherData};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use time::{Duration, OffsetDateTime};
+
+/// Default age below which cached data is served without refetching
+pub const DEFAULT_TTL: Duration = Duration::minutes(10);
+
+/// Data older than this is no longer shown in place of an error
+const MAX_STALE_AGE: Duration = Duration::days(1);
+
+/// Persisted result of a successful fetch
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    fetched_at: OffsetDateTime,
+    data: WeatherData,
+}
+
+/// Decorator that caches weather data per location and serves stale data on error
+pub struct CachedFetcher<F> {
+    inner: F,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl<F: WeatherFetcher> CachedFetcher<F> {
+    /// Wrap `inner`, storing cache entries in `dir` and trusting them for `ttl`
+    pub fn new(inner: F, dir: PathBuf, ttl: Duration) -> Self {
+        Self { inner, dir, ttl }
+    }
+
+    fn fetch_weather_at(
+        &self,
+        location: &LocationQuery,
+        now: OffsetDateTime,
+    ) -> Result<WeatherData> {
+        let cached = self.load(location);
+
+        if let Some(entry) = cached {
+            if now - entry.fetched_at < self.ttl {
+                return Ok(entry.data.roll_forward(now));
+            }
+
+            return match self.inner.fetch_weather(location) {
+                Ok(data) => Ok(self.store_and_return(location, now, data)),
+                Err(_) if now - entry.fetched_at < MAX_STALE_AGE => Ok(WeatherData {
+                    stale_since: Some(entry.fetched_at),
+                    ..entry.data.roll_forward(now)
+                }),
+                Err(e) => Err(e),
+            };
+        }
+
+        let data = self.inner.fetch_weather(location)?;
+        Ok(self.store_and_return(location, now, data))
+    }
+
+    fn store_and_return(
+        &self,
+        location: &LocationQuery,
+        now: OffsetDateTime,
+        data: WeatherData,
+    ) -> WeatherData {
+        let entry = CacheEntry {
+            fetched_at: now,
+            data,
+        };
+        // Caching is best effort: a read-only or full disk must not break fetching
+        let _ = self.store(location, &entry);
+        entry.data
+    }
+
+    /// Path of the cache file for a location
+    fn entry_path(&self, location: &LocationQuery) -> PathBuf {
+        let key: String = location
+            .to_string()
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("weather-{}.json", key))
+    }
+
+    fn load(&self, location: &LocationQuery) -> Option<CacheEntry> {
+        let contents = std::fs::read_to_string(self.entry_path(location)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn store(&self, location: &LocationQuery, entry: &CacheEntry) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create cache directory {}", self.dir.display()))?;
+        std::fs::write(self.entry_path(location), serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+impl<F: WeatherFetcher> WeatherFetcher for CachedFetcher<F> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        self.fetch_weather_at(location, OffsetDateTime::now_utc())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Temperature, WindSpeed};
+    use crate::test_support::weather_data;
+    use std::cell::Cell;
+    use time::macros::datetime;
+
+    /// Fetcher returning a fixed temperature, or failing once `fail` is set
+    struct CountingFetcher {
+        calls: Cell<u32>,
+        temperature: i32,
+        fail: Cell<bool>,
+    }
+
+    impl CountingFetcher {
+        fn new(temperature: i32) -> Self {
+            Self {
+                calls: Cell::new(0),
+                temperature,
+                fail: Cell::new(false),
+            }
+        }
+    }
+
+    impl WeatherFetcher for CountingFetcher {
+        fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+            self.calls.set(self.calls.get() + 1);
+            if self.fail.get() {
+                anyhow::bail!("network unreachable");
+            }
+            let mut data = weather_data(&location.to_string());
+            data.current.temperature = Temperature::new(f64::from(self.temperature)).unwrap();
+            data.current.wind_speed = WindSpeed::with_gusts(15, Some(30)).unwrap();
+            Ok(data)
+        }
+
+        fn name(&self) -> &str {
+            "Counting"
+        }
+    }
+
+    fn place(name: &str) -> LocationQuery {
+        LocationQuery::Name(name.to_string())
+    }
+
+    const NOW: OffsetDateTime = datetime!(2023-01-13 01:30 UTC);
+
+    #[test]
+    fn test_fresh_entry_served_without_fetching() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        let data = cache
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(5))
+            .unwrap();
+
+        assert_eq!(cache.inner.calls.get(), 1);
+        assert_eq!(data.current.temperature.as_celsius(), 20);
+        assert_eq!(data.current.wind_speed.gusts_value(), Some(30));
+        assert!(data.stale_since.is_none());
+    }
+
+    #[test]
+    fn test_expired_entry_refetched() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        let data = cache
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(11))
+            .unwrap();
+
+        assert_eq!(cache.inner.calls.get(), 2);
+        assert!(data.stale_since.is_none());
+    }
+
+    #[test]
+    fn test_stale_entry_served_when_fetch_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        cache.inner.fail.set(true);
+        let data = cache
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::hours(2))
+            .unwrap();
+
+        assert_eq!(data.stale_since, Some(NOW));
+        assert_eq!(data.current.temperature.as_celsius(), 20);
+    }
+
+    #[test]
+    fn test_error_when_stale_entry_too_old() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        cache.inner.fail.set(true);
+        let result = cache.fetch_weather_at(&place("Wellington"), NOW + Duration::days(2));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("network unreachable"));
+    }
+
+    #[test]
+    fn test_error_without_cache_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+        cache.inner.fail.set(true);
+
+        assert!(cache.fetch_weather_at(&place("Wellington"), NOW).is_err());
+    }
+
+    #[test]
+    fn test_entries_are_per_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        let data = cache.fetch_weather_at(&place("New York"), NOW).unwrap();
+
+        assert_eq!(cache.inner.calls.get(), 2);
+        assert_eq!(data.location.to_string(), "New York");
+        assert_eq!(
+            cache.entry_path(&place("New York")),
+            dir.path().join("weather-new_york.json")
+        );
+    }
+
+    #[test]
+    fn test_invalid_cache_entry_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CachedFetcher::new(
+            CountingFetcher::new(20),
+            dir.path().to_path_buf(),
+            DEFAULT_TTL,
+        );
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        // Tamper with the stored temperature so it fails domain validation
+        let path = cache.entry_path(&place("Wellington"));
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(
+            &path,
+            contents.replace("\"temperature\":20", "\"temperature\":999"),
+        )
+        .unwrap();
+
+        cache.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        assert_eq!(cache.inner.calls.get(), 2);
+    }
+}
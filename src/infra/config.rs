@@ -0,0 +1,886 @@
+//! User configuration loaded from `$XDG_CONFIG_HOME/waybar_weather/config.toml`.
+//!
+//! The file holds named profiles, each overriding some of the built-in defaults:
+//!
+//! ```toml
+//! default_profile = "home"
+//!
+//! [profiles.home]
+//! location = "Wellington"
+//! extra_locations = ["Auckland", "Christchurch"]
+//! provider = "metno,openmeteo"
+//! units = "uk"
+//! hours = 8
+//!
+//! [profiles.home.wind_colors]
+//! calm = "#AAAAAA"
+//! ```
+//!
+//! Command-line options in turn override the selected profile. The `search` command
+//! writes the place the user picks back into a profile with [`save_location`].
+
+use crate::app::DEFAULT_REFRESH_INTERVAL;
+use crate::domain::{
+    DistanceUnit, LocationQuery, PlaceMatch, PrecipitationUnit, PressureUnit, SpeedUnit,
+    TemperatureUnit, UnitSystem, WindThresholds,
+};
+use crate::infra::api::provider::DEFAULT_PROVIDERS;
+use crate::infra::api::{Provider, DEFAULT_TIMEOUT};
+use crate::infra::cache;
+use crate::infra::display::formatting::{ClockZone, Icons, WindColors, WindStyle};
+use crate::infra::display::template::Template;
+use crate::infra::display::{BarField, PercentageSource, DEFAULT_HOURS, TEMPLATE_SCHEMA};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Name of the configuration file within the configuration directory
+pub const FILE_NAME: &str = "config.toml";
+
+/// Location used when neither the profile nor the command line names one
+const DEFAULT_LOCATION: &str = "Wellington";
+
+/// Profile a searched place is saved to when no profile is selected
+const SAVED_PROFILE: &str = "default";
+
+/// Most upcoming hours a profile may ask for (every provider forecasts at least this far)
+const MAX_HOURS: u32 = 24;
+
+/// Highest US EPA air quality index level a profile may badge above
+const MAX_AQI_THRESHOLD: u8 = 5;
+
+/// Longest request timeout a profile may set, in seconds
+const MAX_TIMEOUT: u64 = 120;
+
+/// Parsed configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile used when `--profile` is not given
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// One named profile as written in the file; omitted keys keep their defaults
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    location: Option<String>,
+    /// Name shown for the location instead of the one the provider resolves, e.g. for
+    /// coordinates saved by the `search` command
+    location_name: Option<String>,
+    /// Ask GeoClue where the computer is when no location is given
+    auto_location: Option<bool>,
+    /// Further places fetched alongside the location and summarised in the tooltip
+    extra_locations: Option<Vec<String>>,
+    /// Show each location in the bar in turn instead of always the first
+    rotate_locations: Option<bool>,
+    /// Comma-separated providers, as for `--provider`
+    provider: Option<String>,
+    /// Unit system preset: "metric", "imperial" or "uk"
+    units: Option<String>,
+    /// Overrides of the preset for single quantities
+    temperature_unit: Option<String>,
+    wind_unit: Option<String>,
+    pressure_unit: Option<String>,
+    precipitation_unit: Option<String>,
+    distance_unit: Option<String>,
+    /// Upcoming hours listed in the tooltip
+    hours: Option<u32>,
+    /// Badge the bar text when rain is likely within this many hours; 0 disables it
+    umbrella_hours: Option<u32>,
+    /// Badge the bar text when the US EPA air quality index exceeds this level; 0 disables it
+    aqi_threshold: Option<u8>,
+    /// Current conditions appended to the bar text: "uv", "visibility", "cloud"
+    bar_fields: Option<Vec<String>>,
+    /// Send a desktop notification the first time each weather alert is seen
+    notify_alerts: Option<bool>,
+    /// What Waybar's `percentage` field reports: "humidity", "rain" or "temperature"
+    percentage: Option<String>,
+    /// Temperatures at 0 and 100 percent, in the profile's temperature unit
+    comfort_range: Option<[f64; 2]>,
+    /// Layout of the bar text for current conditions, e.g. "{icon} {temp} {location}"
+    text_template: Option<String>,
+    /// Layout of the tooltip
+    tooltip_template: Option<String>,
+    /// Built-in icons: "emoji", "nerdfont", "weather-icons" or "text"
+    icon_set: Option<String>,
+    /// Replacements for single icons of the set, e.g. `clear_night = "★"`
+    icons: Option<BTreeMap<String, String>>,
+    /// Zone times are shown in: "location" or "system"
+    time_zone: Option<String>,
+    /// Request timeout in seconds
+    timeout: Option<u64>,
+    /// Seconds a cached result is served without refetching
+    cache_ttl: Option<u32>,
+    /// Seconds between refreshes in daemon mode
+    interval: Option<u32>,
+    wind_thresholds: Option<WindThresholdsProfile>,
+    wind_colors: Option<WindColorsProfile>,
+}
+
+/// Lowest wind speed of each category above calm, in the profile's wind unit
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WindThresholdsProfile {
+    moderate: Option<u32>,
+    gale: Option<u32>,
+    storm: Option<u32>,
+    hurricane: Option<u32>,
+}
+
+/// Pango colours for each wind speed category
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WindColorsProfile {
+    calm: Option<String>,
+    moderate: Option<String>,
+    gale: Option<String>,
+    storm: Option<String>,
+    hurricane: Option<String>,
+}
+
+/// Fully resolved settings for a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub location: LocationQuery,
+    /// Name shown for the location; `None` keeps the provider's
+    pub location_name: Option<String>,
+    /// Further places fetched alongside the location and summarised with it
+    pub extra_locations: Vec<LocationQuery>,
+    /// Whether the bar shows each location in turn instead of always the first
+    pub rotate_locations: bool,
+    /// Providers in order of preference; later ones are tried when earlier ones fail
+    pub providers: Vec<Provider>,
+    /// Upcoming hours listed in the tooltip
+    pub hours: usize,
+    /// Units values are displayed in
+    pub units: UnitSystem,
+    /// Hours ahead checked for likely rain before badging the bar text; 0 disables it
+    pub umbrella_hours: u32,
+    /// US EPA air quality index level above which the bar text is badged; 0 disables it
+    pub aqi_threshold: u8,
+    /// Current conditions appended to the bar text
+    pub bar_fields: Vec<BarField>,
+    /// Whether new weather alerts trigger a desktop notification
+    pub notify_alerts: bool,
+    /// What Waybar's `percentage` field reports; `None` leaves it out
+    pub percentage: Option<PercentageSource>,
+    /// Custom layout of the bar text for current conditions; `None` keeps the default
+    pub text_template: Option<Template>,
+    /// Custom layout of the tooltip; `None` keeps the default
+    pub tooltip_template: Option<Template>,
+    /// Icons for conditions, moon phases, tooltip lines and badges
+    pub icons: Icons,
+    /// Time zone sunrise, hours and update times are shown in
+    pub time_zone: ClockZone,
+    /// Timeout for each HTTP request
+    pub timeout: std::time::Duration,
+    /// How long a cached result is served without refetching
+    pub cache_ttl: time::Duration,
+    /// How often daemon mode refetches
+    pub interval: time::Duration,
+    pub wind_style: WindStyle,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            location: LocationQuery::Name(DEFAULT_LOCATION.to_string()),
+            location_name: None,
+            extra_locations: Vec::new(),
+            rotate_locations: false,
+            providers: DEFAULT_PROVIDERS.to_vec(),
+            hours: DEFAULT_HOURS,
+            units: UnitSystem::default(),
+            umbrella_hours: 0,
+            aqi_threshold: 0,
+            bar_fields: Vec::new(),
+            notify_alerts: false,
+            percentage: None,
+            text_template: None,
+            tooltip_template: None,
+            icons: Icons::default(),
+            time_zone: ClockZone::default(),
+            timeout: DEFAULT_TIMEOUT,
+            cache_ttl: cache::DEFAULT_TTL,
+            interval: DEFAULT_REFRESH_INTERVAL,
+            wind_style: WindStyle::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration file; a missing file is an empty configuration
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .with_context(|| format!("Invalid configuration file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Parse configuration from TOML text
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Resolve the settings of the named profile, or of the default profile when
+    /// none is named; with neither, the built-in defaults apply
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings> {
+        let Some(name) = profile.or(self.default_profile.as_deref()) else {
+            return Ok(Settings::default());
+        };
+
+        let profile = self.profiles.get(name).with_context(|| {
+            let available = self.profiles.keys().cloned().collect::<Vec<_>>();
+            if available.is_empty() {
+                format!("Unknown profile '{}'. No profiles are configured", name)
+            } else {
+                format!(
+                    "Unknown profile '{}'. Available profiles: {}",
+                    name,
+                    available.join(", ")
+                )
+            }
+        })?;
+
+        profile
+            .resolve()
+            .with_context(|| format!("Invalid profile '{}'", name))
+    }
+}
+
+/// Save a place found by `search` as the location of a profile, keeping the rest of
+/// the file as written.
+///
+/// The place is saved by its coordinates, which every provider accepts, together with
+/// its name to show. Without a named profile the default one is used, and one is
+/// created if the file has none. Returns the name of the profile written to.
+pub fn save_location(path: &Path, profile: Option<&str>, place: &PlaceMatch) -> Result<String> {
+    // Refuse to rewrite a file that is already invalid
+    let config = Config::load(path)?;
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut document: toml_edit::DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+
+    let name = match profile.or(config.default_profile.as_deref()) {
+        Some(name) => name.to_string(),
+        None => {
+            document.insert("default_profile", toml_edit::value(SAVED_PROFILE));
+            SAVED_PROFILE.to_string()
+        }
+    };
+
+    let profiles = document
+        .entry("profiles")
+        .or_insert_with(|| {
+            let mut profiles = toml_edit::Table::new();
+            profiles.set_implicit(true);
+            toml_edit::Item::Table(profiles)
+        })
+        .as_table_like_mut()
+        .context("profiles must be a table")?;
+    let table = profiles
+        .entry(&name)
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .with_context(|| format!("Profile '{}' must be a table", name))?;
+    table.insert("location", toml_edit::value(place.query().to_string()));
+    table.insert("location_name", toml_edit::value(place.location.name()));
+
+    let contents = document.to_string();
+    Config::parse(&contents)?
+        .settings(Some(&name))
+        .with_context(|| format!("Invalid profile '{}'", name))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(name)
+}
+
+impl Profile {
+    /// Validate the profile and apply it on top of the defaults
+    fn resolve(&self) -> Result<Settings> {
+        let mut settings = Settings::default();
+
+        if let Some(location) = &self.location {
+            settings.location = location.parse()?;
+        } else if self.auto_location == Some(true) {
+            settings.location = LocationQuery::Current;
+        }
+        if let Some(name) = &self.location_name {
+            if name.trim().is_empty() {
+                anyhow::bail!("location_name must not be empty");
+            }
+            settings.location_name = Some(name.trim().to_string());
+        }
+        if let Some(locations) = &self.extra_locations {
+            settings.extra_locations = locations
+                .iter()
+                .map(|location| location.parse())
+                .collect::<Result<_, _>>()
+                .context("Invalid extra_locations")?;
+        }
+        if let Some(rotate_locations) = self.rotate_locations {
+            settings.rotate_locations = rotate_locations;
+        }
+        if let Some(provider) = &self.provider {
+            settings.providers = Provider::parse_list(provider)?;
+        }
+        if let Some(units) = &self.units {
+            settings.units = units.parse()?;
+        }
+        if let Some(unit) = &self.temperature_unit {
+            settings.units.temperature = unit.parse::<TemperatureUnit>()?;
+        }
+        if let Some(unit) = &self.wind_unit {
+            settings.units.speed = unit.parse::<SpeedUnit>()?;
+        }
+        if let Some(unit) = &self.pressure_unit {
+            settings.units.pressure = unit.parse::<PressureUnit>()?;
+        }
+        if let Some(unit) = &self.precipitation_unit {
+            settings.units.precipitation = unit.parse::<PrecipitationUnit>()?;
+        }
+        if let Some(unit) = &self.distance_unit {
+            settings.units.distance = unit.parse::<DistanceUnit>()?;
+        }
+        if let Some(hours) = self.hours {
+            if !(1..=MAX_HOURS).contains(&hours) {
+                anyhow::bail!("hours out of range (1 to {}): {}", MAX_HOURS, hours);
+            }
+            settings.hours = hours as usize;
+        }
+        if let Some(hours) = self.umbrella_hours {
+            if hours > MAX_HOURS {
+                anyhow::bail!(
+                    "umbrella_hours out of range (0 to {}): {}",
+                    MAX_HOURS,
+                    hours
+                );
+            }
+            settings.umbrella_hours = hours;
+        }
+        if let Some(level) = self.aqi_threshold {
+            if level > MAX_AQI_THRESHOLD {
+                anyhow::bail!(
+                    "aqi_threshold out of range (0 to {}): {}",
+                    MAX_AQI_THRESHOLD,
+                    level
+                );
+            }
+            settings.aqi_threshold = level;
+        }
+        if let Some(fields) = &self.bar_fields {
+            settings.bar_fields = fields
+                .iter()
+                .map(|field| field.parse())
+                .collect::<Result<_>>()?;
+        }
+        if let Some(notify_alerts) = self.notify_alerts {
+            settings.notify_alerts = notify_alerts;
+        }
+        if let Some(percentage) = &self.percentage {
+            settings.percentage = Some(percentage.parse()?);
+        }
+        if let Some([min, max]) = self.comfort_range {
+            if min >= max {
+                anyhow::bail!(
+                    "comfort_range must run from low to high: [{}, {}]",
+                    min,
+                    max
+                );
+            }
+            let Some(PercentageSource::Temperature { .. }) = settings.percentage else {
+                anyhow::bail!("comfort_range only applies with percentage = \"temperature\"");
+            };
+            let unit = settings.units.temperature;
+            settings.percentage = Some(PercentageSource::Temperature {
+                min: unit.to_celsius(min),
+                max: unit.to_celsius(max),
+            });
+        }
+        if let Some(template) = &self.text_template {
+            let template =
+                Template::parse(template, &TEMPLATE_SCHEMA).context("Invalid text_template")?;
+            settings.text_template = Some(template);
+        }
+        if let Some(template) = &self.tooltip_template {
+            let template =
+                Template::parse(template, &TEMPLATE_SCHEMA).context("Invalid tooltip_template")?;
+            settings.tooltip_template = Some(template);
+        }
+        if let Some(set) = &self.icon_set {
+            settings.icons = Icons::new(set.parse()?);
+        }
+        if let Some(icons) = &self.icons {
+            for (key, icon) in icons {
+                settings.icons = settings.icons.with_override(key, icon.as_str())?;
+            }
+        }
+        if let Some(zone) = &self.time_zone {
+            settings.time_zone = zone.parse()?;
+        }
+        if let Some(timeout) = self.timeout {
+            if !(1..=MAX_TIMEOUT).contains(&timeout) {
+                anyhow::bail!("timeout out of range (1 to {}): {}", MAX_TIMEOUT, timeout);
+            }
+            settings.timeout = std::time::Duration::from_secs(timeout);
+        }
+        if let Some(cache_ttl) = self.cache_ttl {
+            settings.cache_ttl = time::Duration::seconds(cache_ttl.into());
+        }
+        if let Some(interval) = self.interval {
+            if interval == 0 {
+                anyhow::bail!("interval must be at least one second");
+            }
+            settings.interval = time::Duration::seconds(interval.into());
+        }
+        if let Some(thresholds) = &self.wind_thresholds {
+            settings.wind_style.thresholds = thresholds.resolve(settings.units.speed)?;
+        }
+        if let Some(colors) = &self.wind_colors {
+            settings.wind_style.colors = colors.resolve()?;
+        }
+
+        Ok(settings)
+    }
+}
+
+impl WindThresholdsProfile {
+    /// Convert thresholds given in `unit` to km/h, filling in the defaults
+    fn resolve(&self, unit: SpeedUnit) -> Result<WindThresholds> {
+        let defaults = WindThresholds::default();
+        let kmh = |value: Option<u32>, default: u32| match value {
+            Some(value) => unit.to_kmh(value as f64).round() as u32,
+            None => default,
+        };
+
+        WindThresholds::new(
+            kmh(self.moderate, defaults.moderate()),
+            kmh(self.gale, defaults.gale()),
+            kmh(self.storm, defaults.storm()),
+            kmh(self.hurricane, defaults.hurricane()),
+        )
+        .context("Invalid wind_thresholds")
+    }
+}
+
+impl WindColorsProfile {
+    fn resolve(&self) -> Result<WindColors> {
+        let defaults = WindColors::default();
+        let color = |value: &Option<String>, default: String| match value {
+            Some(value) => validate_color(value),
+            None => Ok(default),
+        };
+
+        Ok(WindColors {
+            calm: color(&self.calm, defaults.calm)?,
+            moderate: color(&self.moderate, defaults.moderate)?,
+            gale: color(&self.gale, defaults.gale)?,
+            storm: color(&self.storm, defaults.storm)?,
+            hurricane: color(&self.hurricane, defaults.hurricane)?,
+        })
+    }
+}
+
+/// Check that a colour is a Pango colour: `#` with 3, 4, 6, 8, 9 or 12 hex digits, or
+/// a colour name such as "orange"
+fn validate_color(value: &str) -> Result<String> {
+    let value = value.trim();
+    let valid = match value.strip_prefix('#') {
+        Some(hex) => {
+            [3, 4, 6, 8, 9, 12].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic()),
+    };
+
+    if !valid {
+        anyhow::bail!("Invalid colour '{}'. Use #RRGGBB or a colour name", value);
+    }
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Coordinates, Location, WindSpeedCategory};
+    use crate::infra::display::formatting::IconSet;
+
+    const EXAMPLE: &str = r##"
+        default_profile = "home"
+
+        [profiles.home]
+        location = "Wellington"
+        provider = "metno,openmeteo"
+        units = "metric"
+        hours = 8
+        umbrella_hours = 3
+        aqi_threshold = 2
+        bar_fields = ["uv", "cloud"]
+        notify_alerts = true
+        percentage = "humidity"
+        text_template = "{icon} {temp} {?uv}UV {uv}{/uv}"
+        icon_set = "nerdfont"
+        time_zone = "system"
+        timeout = 5
+        cache_ttl = 300
+        interval = 900
+
+        [profiles.home.wind_thresholds]
+        moderate = 15
+
+        [profiles.home.wind_colors]
+        calm = "#AAAAAA"
+        gale = "orange"
+
+        [profiles.home.icons]
+        clear_night = "★"
+        umbrella_badge = ""
+
+        [profiles.office]
+        location = "Denver"
+        provider = "nws"
+    "##;
+
+    #[test]
+    fn test_default_profile_applied() {
+        let settings = Config::parse(EXAMPLE).unwrap().settings(None).unwrap();
+
+        assert_eq!(settings.location.to_string(), "Wellington");
+        assert_eq!(
+            settings.providers,
+            vec![Provider::MetNo, Provider::OpenMeteo]
+        );
+        assert_eq!(settings.hours, 8);
+        assert_eq!(settings.umbrella_hours, 3);
+        assert_eq!(settings.aqi_threshold, 2);
+        assert_eq!(
+            settings.bar_fields,
+            vec![BarField::Uv, BarField::CloudCover]
+        );
+        assert!(settings.notify_alerts);
+        assert_eq!(settings.percentage, Some(PercentageSource::Humidity));
+        assert!(settings.text_template.is_some());
+        assert_eq!(settings.tooltip_template, None);
+        assert_eq!(
+            settings.icons,
+            Icons::new(IconSet::NerdFont)
+                .with_override("clear_night", "★")
+                .unwrap()
+                .with_override("umbrella_badge", "")
+                .unwrap()
+        );
+        assert_eq!(settings.time_zone, ClockZone::System);
+        assert_eq!(settings.timeout, std::time::Duration::from_secs(5));
+        assert_eq!(settings.cache_ttl, time::Duration::minutes(5));
+        assert_eq!(settings.interval, time::Duration::minutes(15));
+
+        let style = &settings.wind_style;
+        assert_eq!(
+            style.thresholds.categorize(15),
+            WindSpeedCategory::ModerateBreezes
+        );
+        assert_eq!(style.thresholds.gale(), 51);
+        assert_eq!(style.colors.calm, "#AAAAAA");
+        assert_eq!(style.colors.gale, "orange");
+        assert_eq!(style.colors.storm, WindColors::default().storm);
+    }
+
+    #[test]
+    fn test_named_profile_keeps_other_defaults() {
+        let settings = Config::parse(EXAMPLE)
+            .unwrap()
+            .settings(Some("office"))
+            .unwrap();
+
+        assert_eq!(settings.location.to_string(), "Denver");
+        assert_eq!(settings.providers, vec![Provider::Nws]);
+        assert_eq!(settings.hours, DEFAULT_HOURS);
+        assert_eq!(settings.time_zone, ClockZone::Location);
+        assert_eq!(settings.wind_style, WindStyle::default());
+    }
+
+    #[test]
+    fn test_auto_location() {
+        let config = Config::parse(
+            r#"
+            [profiles.laptop]
+            auto_location = true
+
+            [profiles.pinned]
+            location = "Denver"
+            auto_location = true
+            "#,
+        )
+        .unwrap();
+
+        let laptop = config.settings(Some("laptop")).unwrap();
+        assert_eq!(laptop.location, LocationQuery::Current);
+        // A location given in the profile is used as it is
+        let pinned = config.settings(Some("pinned")).unwrap();
+        assert_eq!(pinned.location.to_string(), "Denver");
+    }
+
+    #[test]
+    fn test_extra_locations() {
+        let config = Config::parse(
+            r#"
+            [profiles.team]
+            location = "Wellington"
+            extra_locations = ["Auckland", "-43.53,172.63"]
+            rotate_locations = true
+            "#,
+        )
+        .unwrap();
+
+        let settings = config.settings(Some("team")).unwrap();
+        assert_eq!(settings.location.to_string(), "Wellington");
+        let extra = settings
+            .extra_locations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(extra, ["Auckland", "-43.53,172.63"]);
+        assert!(settings.rotate_locations);
+    }
+
+    #[test]
+    fn test_no_profile_uses_builtin_defaults() {
+        let settings = Config::default().settings(None).unwrap();
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.location.to_string(), "Wellington");
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let error = Config::parse(EXAMPLE)
+            .unwrap()
+            .settings(Some("cabin"))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown profile 'cabin'. Available profiles: home, office"
+        );
+        assert!(Config::default().settings(Some("cabin")).is_err());
+    }
+
+    #[test]
+    fn test_units() {
+        let config = Config::parse(
+            r#"
+            [profiles.us]
+            units = "imperial"
+            percentage = "temperature"
+            comfort_range = [50, 86]
+
+            [profiles.uk]
+            units = "uk"
+
+            [profiles.sailing]
+            wind_unit = "knots"
+            pressure_unit = "mmHg"
+            precipitation_unit = "inches"
+            distance_unit = "km"
+            wind_thresholds = { moderate = 11, gale = 28 }
+            "#,
+        )
+        .unwrap();
+
+        let settings = config.settings(Some("us")).unwrap();
+        assert_eq!(settings.units, UnitSystem::IMPERIAL);
+        // The comfort range is given in °F and kept in °C
+        assert_eq!(
+            settings.percentage,
+            Some(PercentageSource::Temperature {
+                min: 10.0,
+                max: 30.0
+            })
+        );
+        assert_eq!(config.settings(Some("uk")).unwrap().units, UnitSystem::UK);
+
+        let settings = config.settings(Some("sailing")).unwrap();
+        assert_eq!(settings.units.temperature, TemperatureUnit::Celsius);
+        assert_eq!(settings.units.speed, SpeedUnit::Knots);
+        assert_eq!(settings.units.pressure, PressureUnit::MillimetresOfMercury);
+        assert_eq!(settings.units.precipitation, PrecipitationUnit::Inches);
+        assert_eq!(settings.units.distance, DistanceUnit::Kilometres);
+        // Thresholds are given in knots and kept in km/h
+        assert_eq!(settings.wind_style.thresholds.moderate(), 20);
+        assert_eq!(settings.wind_style.thresholds.gale(), 52);
+        assert_eq!(settings.wind_style.thresholds.storm(), 89);
+    }
+
+    #[test]
+    fn test_invalid_profiles_rejected() {
+        let invalid = [
+            "provider = \"accuweather\"",
+            "units = \"nautical\"",
+            "wind_unit = \"furlongs\"",
+            "hours = 0",
+            "hours = 25",
+            "umbrella_hours = 25",
+            "aqi_threshold = 6",
+            "precipitation_unit = \"cubits\"",
+            "distance_unit = \"leagues\"",
+            "bar_fields = [\"pollen\"]",
+            "percentage = \"pressure\"",
+            "percentage = \"temperature\"\ncomfort_range = [25, 15]",
+            "percentage = \"humidity\"\ncomfort_range = [15, 25]",
+            "text_template = \"{icon} {tmp}\"",
+            "tooltip_template = \"{#hours}{time}\"",
+            "icon_set = \"wingdings\"",
+            "icons = { sunny_afternoon = \"x\" }",
+            "time_zone = \"mars\"",
+            "timeout = 0",
+            "interval = 0",
+            "location = \" \"",
+            "location = \"95,174\"",
+            "extra_locations = [\"Auckland\", \" \"]",
+            "location = \"iata:wellington\"",
+            "location_name = \" \"",
+            "wind_thresholds = { gale = 200 }",
+            "wind_colors = { calm = \"#GGGGGG\" }",
+            "wind_colors = { calm = \"light blue\" }",
+        ];
+
+        for line in invalid {
+            let config = Config::parse(&format!("[profiles.bad]\n{}", line)).unwrap();
+            let error = config.settings(Some("bad")).unwrap_err();
+            assert!(
+                format!("{:#}", error).starts_with("Invalid profile 'bad'"),
+                "{}: {:#}",
+                line,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_template_error_names_the_key_and_position() {
+        let config = Config::parse("[profiles.bad]\ntext_template = \"{icon}\\n{tmp}\"").unwrap();
+        let error = config.settings(Some("bad")).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Invalid profile 'bad': Invalid text_template: Unknown placeholder '{tmp}' at line 2, column 1"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        assert!(Config::parse("[profiles.home]\nlocaton = \"Wellington\"").is_err());
+        assert!(Config::parse("default = \"home\"").is_err());
+        assert!(Config::parse("[profiles.home]\nhours = \"many\"").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load(&dir.path().join(FILE_NAME)).unwrap();
+        assert_eq!(config.settings(None).unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn test_load_reports_path_of_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(&path, "[profiles.home\n").unwrap();
+
+        let error = Config::load(&path).unwrap_err();
+        assert!(error.to_string().contains(&path.display().to_string()));
+    }
+
+    fn richmond() -> PlaceMatch {
+        PlaceMatch {
+            location: Location::new("Richmond".to_string())
+                .with_region(Some("Virginia".to_string()))
+                .with_coordinates(Coordinates::new(37.55, -77.46).ok()),
+            id: Some(2588521),
+        }
+    }
+
+    #[test]
+    fn test_save_location_creates_default_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("waybar_weather").join(FILE_NAME);
+
+        let profile = save_location(&path, None, &richmond()).unwrap();
+        assert_eq!(profile, "default");
+
+        let settings = Config::load(&path).unwrap().settings(None).unwrap();
+        assert_eq!(settings.location.to_string(), "37.55,-77.46");
+        assert_eq!(settings.location_name.as_deref(), Some("Richmond"));
+    }
+
+    #[test]
+    fn test_save_location_keeps_rest_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(
+            &path,
+            "# Chosen by hand\n\
+             default_profile = \"home\"\n\
+             \n\
+             [profiles.home]\n\
+             location = \"Richmond\" # the wrong one\n\
+             units = \"imperial\"\n\
+             \n\
+             [profiles.work]\n\
+             location = \"London\"\n",
+        )
+        .unwrap();
+
+        // The default profile is the one written to unless another is selected
+        assert_eq!(save_location(&path, None, &richmond()).unwrap(), "home");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# Chosen by hand\n"));
+        assert!(contents.contains("location = \"37.55,-77.46\""));
+
+        let config = Config::load(&path).unwrap();
+        let home = config.settings(None).unwrap();
+        assert_eq!(home.location_name.as_deref(), Some("Richmond"));
+        assert_eq!(home.units, UnitSystem::IMPERIAL);
+        assert_eq!(
+            config.settings(Some("work")).unwrap().location.to_string(),
+            "London"
+        );
+
+        assert_eq!(
+            save_location(&path, Some("travel"), &richmond()).unwrap(),
+            "travel"
+        );
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config
+                .settings(Some("travel"))
+                .unwrap()
+                .location
+                .to_string(),
+            "37.55,-77.46"
+        );
+    }
+
+    #[test]
+    fn test_save_location_refuses_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(&path, "[profiles.home]\nlocaton = \"Wellington\"\n").unwrap();
+
+        assert!(save_location(&path, None, &richmond()).is_err());
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, "[profiles.home]\nlocaton = \"Wellington\"\n");
+    }
+}
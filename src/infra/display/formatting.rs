@@ -1,68 +1,946 @@
 //! Presentation formatting functions for domain types.
 //!
-//! These functions produce Pango markup and emoji — concerns that belong
+//! These functions produce Pango markup and icons — concerns that belong
 //! in the display layer rather than the domain.
 
-use crate::domain::{WeatherCondition, WindSpeed, WindSpeedCategory};
+use crate::domain::{
+    AirQuality, AirQualityCategory, AlertUrgency, ConditionKind, DistanceUnit, HourlyWeather,
+    MoonPhase, Precipitation, PrecipitationUnit, Pressure, PressureUnit, SpeedUnit, Temperature,
+    TemperatureUnit, UvCategory, UvIndex, Visibility, WeatherAlert, WeatherCondition, WeatherData,
+    WeatherTime, WindSpeed, WindSpeedCategory, WindThresholds, ZonedDateTime,
+};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use time::{macros::format_description, Date, OffsetDateTime, UtcOffset};
+use time_tz::{system, Tz};
 
-/// Get the Pango color string for a wind speed category.
-fn category_color(category: &WindSpeedCategory) -> &'static str {
+/// Pango colour strings for each wind speed category
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindColors {
+    pub calm: String,
+    pub moderate: String,
+    pub gale: String,
+    pub storm: String,
+    pub hurricane: String,
+}
+
+impl WindColors {
+    /// Get the Pango color string for a wind speed category.
+    pub fn get(&self, category: WindSpeedCategory) -> &str {
+        match category {
+            WindSpeedCategory::Calm => &self.calm,
+            WindSpeedCategory::ModerateBreezes => &self.moderate,
+            WindSpeedCategory::Gales => &self.gale,
+            WindSpeedCategory::Storms => &self.storm,
+            WindSpeedCategory::Hurricane => &self.hurricane,
+        }
+    }
+}
+
+impl Default for WindColors {
+    fn default() -> Self {
+        Self {
+            calm: "#FFFFFF".to_string(),
+            moderate: "#00AA00".to_string(),
+            gale: "#FFA500".to_string(),
+            storm: "#FF0000".to_string(),
+            hurricane: "#9B30FF".to_string(),
+        }
+    }
+}
+
+/// How wind speeds are categorised and coloured
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WindStyle {
+    pub thresholds: WindThresholds,
+    pub colors: WindColors,
+}
+
+impl WindStyle {
+    /// Colour for a raw wind speed in km/h
+    fn color(&self, speed: u32) -> &str {
+        self.colors.get(self.thresholds.categorize(speed))
+    }
+}
+
+/// Format a temperature in the given unit, rounded to a whole degree (e.g., "68°F")
+pub fn format_temperature(temperature: &Temperature, unit: TemperatureUnit) -> String {
+    let value = unit.convert_celsius(temperature.value()).round() as i64;
+    format!("{}{}", value, unit.suffix())
+}
+
+/// Format a pressure in the given unit (e.g., "29.91 inHg")
+pub fn format_pressure(pressure: &Pressure, unit: PressureUnit) -> String {
+    format!(
+        "{:.*} {}",
+        unit.precision(),
+        unit.convert_hpa(pressure.value() as f64),
+        unit.symbol()
+    )
+}
+
+/// Format a precipitation amount in the given unit (e.g., "0.12 in")
+pub fn format_precipitation(precipitation: &Precipitation, unit: PrecipitationUnit) -> String {
+    format!(
+        "{:.*} {}",
+        unit.precision(),
+        unit.convert_mm(precipitation.as_mm()),
+        unit.symbol()
+    )
+}
+
+/// Summarise the precipitation expected in an hour (e.g., "☔ 60% 1.2 mm"), or `None`
+/// when there is no chance of it and no measurable amount
+pub fn format_precipitation_outlook(
+    hourly: &HourlyWeather,
+    unit: PrecipitationUnit,
+    icons: &Icons,
+) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(chance) = hourly.chance_of_rain.filter(|c| c.as_percent() > 0) {
+        parts.push(icons.prefix(LineIcon::ChanceOfRain, &chance.to_string()));
+    }
+    if let Some(chance) = hourly.chance_of_snow.filter(|c| c.as_percent() > 0) {
+        parts.push(icons.prefix(LineIcon::ChanceOfSnow, &chance.to_string()));
+    }
+    if let Some(amount) = hourly.precipitation.filter(Precipitation::is_measurable) {
+        parts.push(format_precipitation(&amount, unit));
+    }
+
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// Format a visibility in the given unit, with a decimal place below ten (e.g., "2.5 mi")
+pub fn format_visibility(visibility: &Visibility, unit: DistanceUnit) -> String {
+    let value = unit.convert_km(visibility.as_km());
+    let precision = if value < 10.0 { 1 } else { 0 };
+    format!("{:.*} {}", precision, value, unit.symbol())
+}
+
+/// Get the Pango colour for a UV category, following the WHO UV index colours
+fn uv_category_color(category: UvCategory) -> &'static str {
+    match category {
+        UvCategory::Low => "#289500",
+        UvCategory::Moderate => "#F7E400",
+        UvCategory::High => "#F85900",
+        UvCategory::VeryHigh => "#D8001D",
+        UvCategory::Extreme => "#6B49C8",
+    }
+}
+
+/// Format a UV index with its category, colouring the number (e.g., "7 (High)")
+pub fn format_uv_colored(uv: &UvIndex) -> String {
+    format!("{} ({})", format_uv_colored_compact(uv), uv.category())
+}
+
+/// Format a UV index compactly for the bar text, colouring the number by category
+pub fn format_uv_colored_compact(uv: &UvIndex) -> String {
+    format!(
+        "<span foreground=\"{}\">{}</span>",
+        uv_category_color(uv.category()),
+        uv.as_int()
+    )
+}
+
+/// Get the Pango colour for an air quality category, following the US EPA AQI colours
+fn air_quality_color(category: AirQualityCategory) -> &'static str {
     match category {
-        WindSpeedCategory::Calm => "#FFFFFF",
-        WindSpeedCategory::ModerateBreezes => "#00AA00",
-        WindSpeedCategory::Gales => "#FFA500",
-        WindSpeedCategory::Storms => "#FF0000",
-        WindSpeedCategory::Hurricane => "#9B30FF",
+        AirQualityCategory::Good => "#00E400",
+        AirQualityCategory::Moderate => "#FFFF00",
+        AirQualityCategory::UnhealthyForSensitiveGroups => "#FF7E00",
+        AirQualityCategory::Unhealthy => "#FF0000",
+        AirQualityCategory::VeryUnhealthy => "#8F3F97",
+        AirQualityCategory::Hazardous => "#7E0023",
+    }
+}
+
+/// Format the air quality indices without markup (e.g., "Moderate (US EPA 2), UK DEFRA 3
+/// (Low)"), or `None` when the provider gave neither index
+pub fn format_air_quality(air_quality: &AirQuality) -> Option<String> {
+    air_quality_summary(air_quality, |category| category.to_string())
+}
+
+/// Format the air quality indices with the US EPA category coloured for the tooltip
+pub fn format_air_quality_colored(air_quality: &AirQuality) -> Option<String> {
+    air_quality_summary(air_quality, |category| {
+        format!(
+            "<span foreground=\"{}\">{}</span>",
+            air_quality_color(category),
+            category
+        )
+    })
+}
+
+fn air_quality_summary(
+    air_quality: &AirQuality,
+    category: impl Fn(AirQualityCategory) -> String,
+) -> Option<String> {
+    let epa = air_quality
+        .us_epa_index
+        .map(|index| format!("{} (US EPA {})", category(index.category()), index));
+    let defra = air_quality
+        .gb_defra_index
+        .map(|index| format!("UK DEFRA {}", index));
+
+    let parts = [epa, defra].into_iter().flatten().collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// List the measured pollutant concentrations (e.g., "PM2.5 8.6, PM10 12.3 µg/m³"), or
+/// `None` when none were measured
+pub fn format_pollutants(air_quality: &AirQuality) -> Option<String> {
+    let pollutants = [
+        ("PM2.5", air_quality.pm2_5),
+        ("PM10", air_quality.pm10),
+        ("O₃", air_quality.ozone),
+        ("NO₂", air_quality.nitrogen_dioxide),
+        ("SO₂", air_quality.sulphur_dioxide),
+        ("CO", air_quality.carbon_monoxide),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{} {:.1}", name, value.as_micrograms())))
+    .collect::<Vec<_>>();
+
+    (!pollutants.is_empty()).then(|| format!("{} µg/m³", pollutants.join(", ")))
+}
+
+/// Format an alert's event, severity and headline (e.g., "⚠️ Wind Advisory (Moderate):
+/// Wind Advisory until 6PM")
+pub fn format_alert(alert: &WeatherAlert, icons: &Icons) -> String {
+    let summary = format!("{} ({}): {}", alert.event, alert.severity, alert.headline);
+    icons.prefix(LineIcon::Alert, &summary)
+}
+
+/// Describe when and where an alert applies (e.g., "Expected · 13:42 – Sat 05:00 ·
+/// Denver, Boulder"), or `None` when the issuer said none of it
+pub fn format_alert_details(alert: &WeatherAlert, clock: &Clock) -> Option<String> {
+    let urgency = (alert.urgency != AlertUrgency::Unknown).then(|| alert.urgency.to_string());
+    let timing = match (alert.effective, alert.expires) {
+        (Some(effective), Some(expires)) => Some(format!(
+            "{} – {}",
+            clock.hour(effective),
+            clock.hour(expires)
+        )),
+        (Some(effective), None) => Some(format!("from {}", clock.hour(effective))),
+        (None, Some(expires)) => Some(format!("until {}", clock.hour(expires))),
+        (None, None) => None,
+    };
+    let areas = (!alert.areas.is_empty()).then(|| alert.areas.join(", "));
+
+    let parts = [urgency, timing, areas]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// Convert a speed in km/h to the given unit, rounded to a whole number
+fn speed_value(kmh: u32, unit: SpeedUnit) -> i64 {
+    unit.convert_kmh(kmh as f64).round() as i64
+}
+
+/// Format wind speed without markup (e.g., "9 mph (Gusts: 19 mph)")
+pub fn format_wind(wind: &WindSpeed, unit: SpeedUnit) -> String {
+    let sustained = speed_value(wind.sustained_value(), unit);
+    match wind.gusts_value() {
+        Some(gusts) => format!(
+            "{} {} (Gusts: {} {})",
+            sustained,
+            unit.symbol(),
+            speed_value(gusts, unit),
+            unit.symbol()
+        ),
+        None => format!("{} {}", sustained, unit.symbol()),
     }
 }
 
 /// Format wind speed with Pango color markup for Waybar tooltip.
-/// Only colors the numbers, not the units.
-pub fn format_wind_colored(wind: &WindSpeed) -> String {
-    let sustained_color = category_color(&wind.category());
+/// Only colors the numbers, not the units; colours follow the km/h categories
+/// whatever unit is shown.
+pub fn format_wind_colored(wind: &WindSpeed, style: &WindStyle, unit: SpeedUnit) -> String {
     let sustained_colored = format!(
         "<span foreground=\"{}\">{}</span>",
-        sustained_color,
-        wind.sustained_value()
+        style.color(wind.sustained_value()),
+        speed_value(wind.sustained_value(), unit)
     );
 
-    match (wind.gusts_value(), wind.gust_category()) {
-        (Some(gusts), Some(gust_cat)) => {
-            let gust_color = category_color(&gust_cat);
-            let gust_colored = format!("<span foreground=\"{}\">{}</span>", gust_color, gusts);
+    match wind.gusts_value() {
+        Some(gusts) => {
+            let gust_colored = format!(
+                "<span foreground=\"{}\">{}</span>",
+                style.color(gusts),
+                speed_value(gusts, unit)
+            );
 
             format!(
-                "{} km/h (Gusts: {} km/h)",
-                sustained_colored, gust_colored
+                "{} {} (Gusts: {} {})",
+                sustained_colored,
+                unit.symbol(),
+                gust_colored,
+                unit.symbol()
             )
         }
-        _ => format!("{} km/h", sustained_colored),
+        None => format!("{} {}", sustained_colored, unit.symbol()),
     }
 }
 
 /// Format wind speed compactly for title bar display (e.g., "43 km/h").
 /// Only shows sustained wind speed, colored by category.
-pub fn format_wind_colored_compact(wind: &WindSpeed) -> String {
-    let sustained_color = category_color(&wind.category());
+pub fn format_wind_colored_compact(wind: &WindSpeed, style: &WindStyle, unit: SpeedUnit) -> String {
     format!(
-        "<span foreground=\"{}\">{}</span> km/h",
-        sustained_color,
-        wind.sustained_value()
+        "<span foreground=\"{}\">{}</span> {}",
+        style.color(wind.sustained_value()),
+        speed_value(wind.sustained_value(), unit),
+        unit.symbol()
     )
 }
 
-/// Get appropriate weather icon for a condition.
-pub fn condition_icon(condition: &WeatherCondition) -> &'static str {
-    let condition_lower = condition.description().to_lowercase();
-    match condition_lower.as_str() {
-        c if c.contains("sunny") || c.contains("clear") => "☀️",
-        c if c.contains("partly") || c.contains("partial") => "⛅",
-        c if c.contains("cloudy") || c.contains("overcast") => "☁️",
-        c if c.contains("rain") || c.contains("drizzle") => "🌧️",
-        c if c.contains("storm") || c.contains("thunder") => "⛈️",
-        c if c.contains("snow") || c.contains("blizzard") => "🌨️",
-        c if c.contains("fog") || c.contains("mist") => "🌫️",
-        c if c.contains("wind") => "💨",
-        _ => "🌤️",
+/// Built-in collection of icons for conditions, moon phases and line prefixes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IconSet {
+    /// Unicode emoji
+    #[default]
+    Emoji,
+    /// Nerd Font glyphs (`nf-weather-*`, plus a few Font Awesome ones)
+    NerdFont,
+    /// Erik Flowers' Weather Icons font; lines it has no glyph for keep their emoji
+    WeatherIcons,
+    /// Plain ASCII words, for fonts without any of the above
+    Text,
+}
+
+impl FromStr for IconSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "emoji" => Ok(Self::Emoji),
+            "nerd" | "nerdfont" | "nerd-font" => Ok(Self::NerdFont),
+            "weather-icons" | "weathericons" => Ok(Self::WeatherIcons),
+            "text" | "ascii" => Ok(Self::Text),
+            other => anyhow::bail!(
+                "Unknown icon set '{}'. Expected one of: emoji, nerdfont, weather-icons, text",
+                other
+            ),
+        }
+    }
+}
+
+impl IconSet {
+    /// Icon for a kind of weather, by day or by night
+    fn condition(self, kind: ConditionKind, night: bool) -> &'static str {
+        let (day, night_icon) = match self {
+            Self::Emoji => emoji_condition(kind),
+            Self::NerdFont => nerd_font_condition(kind),
+            Self::WeatherIcons => weather_icons_condition(kind),
+            Self::Text => {
+                let word = text_condition(kind);
+                (word, word)
+            }
+        };
+        if night {
+            night_icon
+        } else {
+            day
+        }
+    }
+
+    /// Icon for a phase of the moon
+    fn moon(self, phase: MoonPhase) -> &'static str {
+        let icons = match self {
+            Self::Emoji => ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"],
+            Self::NerdFont => [
+                "\u{e38d}", "\u{e390}", "\u{e394}", "\u{e397}", "\u{e39b}", "\u{e39e}", "\u{e3a2}",
+                "\u{e3a5}",
+            ],
+            Self::WeatherIcons => [
+                "\u{f095}", "\u{f098}", "\u{f09c}", "\u{f09f}", "\u{f0a3}", "\u{f0a6}", "\u{f0aa}",
+                "\u{f0ad}",
+            ],
+            Self::Text => ["NM", "WXC", "FQ", "WXG", "FM", "WNG", "LQ", "WNC"],
+        };
+        icons[phase as usize]
+    }
+
+    /// Icon for a line prefix or badge; empty when the set has none
+    fn line(self, line: LineIcon) -> &'static str {
+        match self {
+            Self::Emoji => emoji_line(line),
+            Self::NerdFont => nerd_font_line(line),
+            Self::WeatherIcons => weather_icons_line(line).unwrap_or_else(|| emoji_line(line)),
+            Self::Text => text_line(line),
+        }
+    }
+}
+
+/// Line prefixes and badges of the bar text, tooltip and terminal report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineIcon {
+    Location,
+    Temperature,
+    Condition,
+    FeelsLike,
+    Humidity,
+    Wind,
+    Pressure,
+    Precipitation,
+    Uv,
+    Visibility,
+    CloudCover,
+    AirQuality,
+    Sunrise,
+    SolarNoon,
+    Sunset,
+    DayLength,
+    /// Heading of the upcoming hours
+    Hours,
+    /// Heading of the next days
+    Days,
+    Updated,
+    Source,
+    /// Notice that the data is from a failed fetch
+    Stale,
+    Alert,
+    /// Chance of rain in an hour's precipitation outlook
+    ChanceOfRain,
+    /// Chance of snow in an hour's precipitation outlook
+    ChanceOfSnow,
+    /// Bar text badge for rain likely soon
+    UmbrellaBadge,
+    /// Bar text badge for poor air quality
+    AirQualityBadge,
+}
+
+impl LineIcon {
+    /// Every line icon, in the order they are documented
+    pub const ALL: [Self; 26] = [
+        Self::Location,
+        Self::Temperature,
+        Self::Condition,
+        Self::FeelsLike,
+        Self::Humidity,
+        Self::Wind,
+        Self::Pressure,
+        Self::Precipitation,
+        Self::Uv,
+        Self::Visibility,
+        Self::CloudCover,
+        Self::AirQuality,
+        Self::Sunrise,
+        Self::SolarNoon,
+        Self::Sunset,
+        Self::DayLength,
+        Self::Hours,
+        Self::Days,
+        Self::Updated,
+        Self::Source,
+        Self::Stale,
+        Self::Alert,
+        Self::ChanceOfRain,
+        Self::ChanceOfSnow,
+        Self::UmbrellaBadge,
+        Self::AirQualityBadge,
+    ];
+
+    /// Key naming this icon in the configuration's `icons` table
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Location => "location",
+            Self::Temperature => "temperature",
+            Self::Condition => "condition",
+            Self::FeelsLike => "feels_like",
+            Self::Humidity => "humidity",
+            Self::Wind => "wind",
+            Self::Pressure => "pressure",
+            Self::Precipitation => "precipitation",
+            Self::Uv => "uv",
+            Self::Visibility => "visibility",
+            Self::CloudCover => "cloud_cover",
+            Self::AirQuality => "air_quality",
+            Self::Sunrise => "sunrise",
+            Self::SolarNoon => "solar_noon",
+            Self::Sunset => "sunset",
+            Self::DayLength => "day_length",
+            Self::Hours => "hours",
+            Self::Days => "days",
+            Self::Updated => "updated",
+            Self::Source => "source",
+            Self::Stale => "stale",
+            Self::Alert => "alert",
+            Self::ChanceOfRain => "chance_of_rain",
+            Self::ChanceOfSnow => "chance_of_snow",
+            Self::UmbrellaBadge => "umbrella_badge",
+            Self::AirQualityBadge => "air_quality_badge",
+        }
+    }
+}
+
+/// Every kind of weather, for validating icon overrides
+pub(super) const CONDITION_KINDS: [ConditionKind; 17] = [
+    ConditionKind::Clear,
+    ConditionKind::PartlyCloudy,
+    ConditionKind::Cloudy,
+    ConditionKind::Overcast,
+    ConditionKind::Fog,
+    ConditionKind::Drizzle,
+    ConditionKind::Rain,
+    ConditionKind::HeavyRain,
+    ConditionKind::FreezingRain,
+    ConditionKind::RainShowers,
+    ConditionKind::Sleet,
+    ConditionKind::Snow,
+    ConditionKind::HeavySnow,
+    ConditionKind::SnowShowers,
+    ConditionKind::Thunderstorm,
+    ConditionKind::Wind,
+    ConditionKind::Unknown,
+];
+
+/// Key naming a kind of weather in the configuration's `icons` table
+pub(super) fn condition_kind_key(kind: ConditionKind) -> &'static str {
+    match kind {
+        ConditionKind::Clear => "clear",
+        ConditionKind::PartlyCloudy => "partly_cloudy",
+        ConditionKind::Cloudy => "cloudy",
+        ConditionKind::Overcast => "overcast",
+        ConditionKind::Fog => "fog",
+        ConditionKind::Drizzle => "drizzle",
+        ConditionKind::Rain => "rain",
+        ConditionKind::HeavyRain => "heavy_rain",
+        ConditionKind::FreezingRain => "freezing_rain",
+        ConditionKind::RainShowers => "rain_showers",
+        ConditionKind::Sleet => "sleet",
+        ConditionKind::Snow => "snow",
+        ConditionKind::HeavySnow => "heavy_snow",
+        ConditionKind::SnowShowers => "snow_showers",
+        ConditionKind::Thunderstorm => "thunderstorm",
+        ConditionKind::Wind => "windy",
+        ConditionKind::Unknown => "unknown",
+    }
+}
+
+/// Key naming a moon phase in the configuration's `icons` table
+pub(super) fn moon_phase_key(phase: MoonPhase) -> &'static str {
+    match phase {
+        MoonPhase::New => "moon_new",
+        MoonPhase::WaxingCrescent => "moon_waxing_crescent",
+        MoonPhase::FirstQuarter => "moon_first_quarter",
+        MoonPhase::WaxingGibbous => "moon_waxing_gibbous",
+        MoonPhase::Full => "moon_full",
+        MoonPhase::WaningGibbous => "moon_waning_gibbous",
+        MoonPhase::LastQuarter => "moon_last_quarter",
+        MoonPhase::WaningCrescent => "moon_waning_crescent",
+    }
+}
+
+/// An icon set with the user's replacements for some of its icons
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Icons {
+    set: IconSet,
+    overrides: BTreeMap<String, String>,
+}
+
+impl Icons {
+    /// Use the icons of a built-in set
+    pub fn new(set: IconSet) -> Self {
+        Self {
+            set,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Replace one icon. Conditions are keyed by kind (e.g., "rain_showers"), optionally
+    /// for day or night only ("clear_night"); moon phases as "moon_full"; line prefixes
+    /// and badges by their [`LineIcon::key`]. An empty icon hides it.
+    pub fn with_override(mut self, key: &str, icon: impl Into<String>) -> Result<Self> {
+        let kind = key
+            .strip_suffix("_day")
+            .or_else(|| key.strip_suffix("_night"))
+            .unwrap_or(key);
+        let known = CONDITION_KINDS
+            .iter()
+            .any(|&k| condition_kind_key(k) == kind)
+            || MoonPhase::ALL
+                .iter()
+                .any(|&phase| moon_phase_key(phase) == key)
+            || LineIcon::ALL.iter().any(|line| line.key() == key);
+        if !known {
+            let lines = LineIcon::ALL.map(LineIcon::key).join(", ");
+            anyhow::bail!(
+                "Unknown icon '{}'. Expected a condition such as clear_night, a moon phase \
+                 such as moon_full, or one of: {}",
+                key,
+                lines
+            );
+        }
+        self.overrides.insert(key.to_string(), icon.into());
+        Ok(self)
+    }
+
+    /// Icon for a condition; conditions the provider did not mark as night get the
+    /// daytime icon
+    pub fn condition(&self, condition: &WeatherCondition) -> &str {
+        let kind = condition.kind();
+        let night = condition.is_day() == Some(false);
+        let key = condition_kind_key(kind);
+        let variant = format!("{}_{}", key, if night { "night" } else { "day" });
+        self.overrides
+            .get(&variant)
+            .or_else(|| self.overrides.get(key))
+            .map(String::as_str)
+            .unwrap_or_else(|| self.set.condition(kind, night))
+    }
+
+    /// Icon for a phase of the moon
+    pub fn moon(&self, phase: MoonPhase) -> &str {
+        self.overrides
+            .get(moon_phase_key(phase))
+            .map(String::as_str)
+            .unwrap_or_else(|| self.set.moon(phase))
+    }
+
+    /// Icon for a line prefix or badge; may be empty
+    pub fn line(&self, line: LineIcon) -> &str {
+        self.overrides
+            .get(line.key())
+            .map(String::as_str)
+            .unwrap_or_else(|| self.set.line(line))
+    }
+
+    /// Prefix text with a line icon and a space, or leave it bare when the icon is empty
+    pub fn prefix(&self, line: LineIcon, text: &str) -> String {
+        match self.line(line) {
+            "" => text.to_string(),
+            icon => format!("{} {}", icon, text),
+        }
+    }
+}
+
+/// Emoji for a kind of weather by day and by night
+fn emoji_condition(kind: ConditionKind) -> (&'static str, &'static str) {
+    match kind {
+        ConditionKind::Clear => ("☀️", "🌙"),
+        ConditionKind::PartlyCloudy => ("⛅", "☁️"),
+        ConditionKind::Cloudy | ConditionKind::Overcast => ("☁️", "☁️"),
+        ConditionKind::Fog => ("🌫️", "🌫️"),
+        ConditionKind::RainShowers => ("🌦️", "🌧️"),
+        ConditionKind::Drizzle
+        | ConditionKind::Rain
+        | ConditionKind::HeavyRain
+        | ConditionKind::FreezingRain => ("🌧️", "🌧️"),
+        ConditionKind::Sleet
+        | ConditionKind::Snow
+        | ConditionKind::HeavySnow
+        | ConditionKind::SnowShowers => ("🌨️", "🌨️"),
+        ConditionKind::Thunderstorm => ("⛈️", "⛈️"),
+        ConditionKind::Wind => ("💨", "💨"),
+        ConditionKind::Unknown => ("🌤️", "🌤️"),
+    }
+}
+
+/// Nerd Font `nf-weather-*` glyphs for a kind of weather by day and by night
+fn nerd_font_condition(kind: ConditionKind) -> (&'static str, &'static str) {
+    match kind {
+        ConditionKind::Clear => ("\u{e30d}", "\u{e32b}"),
+        ConditionKind::PartlyCloudy => ("\u{e302}", "\u{e379}"),
+        ConditionKind::Cloudy | ConditionKind::Overcast => ("\u{e312}", "\u{e312}"),
+        ConditionKind::Fog => ("\u{e303}", "\u{e346}"),
+        ConditionKind::Drizzle => ("\u{e30b}", "\u{e328}"),
+        ConditionKind::Rain => ("\u{e308}", "\u{e325}"),
+        ConditionKind::HeavyRain => ("\u{e318}", "\u{e318}"),
+        ConditionKind::FreezingRain => ("\u{e316}", "\u{e316}"),
+        ConditionKind::RainShowers => ("\u{e309}", "\u{e326}"),
+        ConditionKind::Sleet => ("\u{e3aa}", "\u{e3ac}"),
+        ConditionKind::Snow | ConditionKind::SnowShowers => ("\u{e30a}", "\u{e327}"),
+        ConditionKind::HeavySnow => ("\u{e31a}", "\u{e31a}"),
+        ConditionKind::Thunderstorm => ("\u{e30f}", "\u{e32a}"),
+        ConditionKind::Wind => ("\u{e34b}", "\u{e34b}"),
+        ConditionKind::Unknown => ("\u{e374}", "\u{e374}"),
+    }
+}
+
+/// Weather Icons glyphs for a kind of weather by day and by night
+fn weather_icons_condition(kind: ConditionKind) -> (&'static str, &'static str) {
+    match kind {
+        ConditionKind::Clear => ("\u{f00d}", "\u{f02e}"),
+        ConditionKind::PartlyCloudy => ("\u{f002}", "\u{f081}"),
+        ConditionKind::Cloudy | ConditionKind::Overcast => ("\u{f013}", "\u{f013}"),
+        ConditionKind::Fog => ("\u{f003}", "\u{f04a}"),
+        ConditionKind::Drizzle => ("\u{f00b}", "\u{f02b}"),
+        ConditionKind::Rain => ("\u{f008}", "\u{f028}"),
+        ConditionKind::HeavyRain => ("\u{f019}", "\u{f019}"),
+        ConditionKind::FreezingRain => ("\u{f017}", "\u{f017}"),
+        ConditionKind::RainShowers => ("\u{f009}", "\u{f029}"),
+        ConditionKind::Sleet => ("\u{f0b2}", "\u{f0b4}"),
+        ConditionKind::Snow | ConditionKind::SnowShowers => ("\u{f00a}", "\u{f02a}"),
+        ConditionKind::HeavySnow => ("\u{f01b}", "\u{f01b}"),
+        ConditionKind::Thunderstorm => ("\u{f010}", "\u{f02d}"),
+        ConditionKind::Wind => ("\u{f050}", "\u{f050}"),
+        ConditionKind::Unknown => ("\u{f07b}", "\u{f07b}"),
+    }
+}
+
+/// Word for a kind of weather
+fn text_condition(kind: ConditionKind) -> &'static str {
+    match kind {
+        ConditionKind::Clear => "Clear",
+        ConditionKind::PartlyCloudy => "Partly cloudy",
+        ConditionKind::Cloudy => "Cloudy",
+        ConditionKind::Overcast => "Overcast",
+        ConditionKind::Fog => "Fog",
+        ConditionKind::Drizzle => "Drizzle",
+        ConditionKind::Rain => "Rain",
+        ConditionKind::HeavyRain => "Heavy rain",
+        ConditionKind::FreezingRain => "Freezing rain",
+        ConditionKind::RainShowers => "Showers",
+        ConditionKind::Sleet => "Sleet",
+        ConditionKind::Snow => "Snow",
+        ConditionKind::HeavySnow => "Heavy snow",
+        ConditionKind::SnowShowers => "Snow showers",
+        ConditionKind::Thunderstorm => "Thunder",
+        ConditionKind::Wind => "Windy",
+        ConditionKind::Unknown => "?",
+    }
+}
+
+fn emoji_line(line: LineIcon) -> &'static str {
+    match line {
+        LineIcon::Location => "📍",
+        LineIcon::Temperature => "🌡️",
+        LineIcon::Condition => "🌤️",
+        LineIcon::FeelsLike => "🤚",
+        LineIcon::Humidity => "💧",
+        LineIcon::Wind => "💨",
+        LineIcon::Pressure => "📊",
+        LineIcon::Precipitation => "🌧️",
+        LineIcon::Uv => "😎",
+        LineIcon::Visibility => "👁️",
+        LineIcon::CloudCover => "☁️",
+        LineIcon::AirQuality => "🫁",
+        LineIcon::Sunrise => "🌅",
+        LineIcon::SolarNoon => "🌞",
+        LineIcon::Sunset => "🌇",
+        LineIcon::DayLength => "⏳",
+        LineIcon::Hours => "⏰",
+        LineIcon::Days => "📅",
+        LineIcon::Updated => "🕐",
+        LineIcon::Source => "📡",
+        LineIcon::Stale => "⏸️",
+        LineIcon::Alert => "⚠️",
+        LineIcon::ChanceOfRain => "☔",
+        LineIcon::ChanceOfSnow => "❄️",
+        LineIcon::UmbrellaBadge => "☂️",
+        LineIcon::AirQualityBadge => "😷",
+    }
+}
+
+fn nerd_font_line(line: LineIcon) -> &'static str {
+    match line {
+        LineIcon::Location => "\u{f041}",
+        LineIcon::Temperature => "\u{e350}",
+        LineIcon::Condition => "\u{e302}",
+        LineIcon::FeelsLike => "\u{e34e}",
+        LineIcon::Humidity => "\u{e373}",
+        LineIcon::Wind => "\u{e34b}",
+        LineIcon::Pressure => "\u{e372}",
+        LineIcon::Precipitation => "\u{e318}",
+        LineIcon::Uv => "\u{e36b}",
+        LineIcon::Visibility => "\u{f06e}",
+        LineIcon::CloudCover => "\u{e33d}",
+        LineIcon::AirQuality | LineIcon::AirQualityBadge => "\u{e36d}",
+        LineIcon::Sunrise => "\u{e34c}",
+        LineIcon::SolarNoon => "\u{e30d}",
+        LineIcon::Sunset => "\u{e34d}",
+        LineIcon::DayLength => "\u{e343}",
+        LineIcon::Hours => "\u{e384}",
+        LineIcon::Days => "\u{f073}",
+        LineIcon::Updated => "\u{e348}",
+        LineIcon::Source => "\u{f09e}",
+        LineIcon::Stale => "\u{f04c}",
+        LineIcon::Alert => "\u{f071}",
+        LineIcon::ChanceOfRain => "\u{e371}",
+        LineIcon::ChanceOfSnow => "\u{e36f}",
+        LineIcon::UmbrellaBadge => "\u{e37c}",
+    }
+}
+
+/// Weather Icons glyph for a line, or `None` where the font has nothing fitting
+fn weather_icons_line(line: LineIcon) -> Option<&'static str> {
+    let icon = match line {
+        LineIcon::Temperature => "\u{f055}",
+        LineIcon::Condition => "\u{f002}",
+        LineIcon::FeelsLike => "\u{f053}",
+        LineIcon::Humidity => "\u{f07a}",
+        LineIcon::Wind => "\u{f050}",
+        LineIcon::Pressure => "\u{f079}",
+        LineIcon::Precipitation => "\u{f019}",
+        LineIcon::Uv => "\u{f072}",
+        LineIcon::CloudCover => "\u{f041}",
+        LineIcon::AirQuality | LineIcon::AirQualityBadge => "\u{f074}",
+        LineIcon::Sunrise => "\u{f051}",
+        LineIcon::SolarNoon => "\u{f00d}",
+        LineIcon::Sunset => "\u{f052}",
+        LineIcon::DayLength => "\u{f047}",
+        LineIcon::Hours => "\u{f08c}",
+        LineIcon::Updated => "\u{f04c}",
+        LineIcon::Alert => "\u{f0ce}",
+        LineIcon::ChanceOfRain => "\u{f078}",
+        LineIcon::ChanceOfSnow => "\u{f076}",
+        LineIcon::UmbrellaBadge => "\u{f084}",
+        LineIcon::Location
+        | LineIcon::Visibility
+        | LineIcon::Days
+        | LineIcon::Source
+        | LineIcon::Stale => return None,
+    };
+    Some(icon)
+}
+
+/// ASCII for a line: most lines go without, since their labels say enough
+fn text_line(line: LineIcon) -> &'static str {
+    match line {
+        LineIcon::Alert => "!",
+        LineIcon::ChanceOfRain => "rain",
+        LineIcon::ChanceOfSnow => "snow",
+        LineIcon::UmbrellaBadge => "[rain]",
+        LineIcon::AirQualityBadge => "[air]",
+        _ => "",
+    }
+}
+
+/// Whose clocks times are shown on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClockZone {
+    /// The location's time zone
+    #[default]
+    Location,
+    /// The time zone of the computer showing the weather
+    System,
+}
+
+impl FromStr for ClockZone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "location" => Ok(Self::Location),
+            "system" | "local" => Ok(Self::System),
+            other => anyhow::bail!(
+                "Unknown time zone '{}'. Expected one of: location, system",
+                other
+            ),
+        }
+    }
+}
+
+/// Shows the instants of one set of weather data on the clocks of the chosen zone, so
+/// that every time in the output reads the same way
+pub struct Clock<'a> {
+    weather_data: &'a WeatherData,
+    zone: ResolvedZone,
+}
+
+/// The zone a clock shows, once the system zone has been looked up
+enum ResolvedZone {
+    Location,
+    System(&'static Tz),
+    /// The system zone could not be determined
+    Utc,
+}
+
+impl<'a> Clock<'a> {
+    /// A clock for the given weather, looking up the system zone once if it is chosen
+    pub fn new(zone: ClockZone, weather_data: &'a WeatherData) -> Self {
+        let zone = match zone {
+            ClockZone::Location => ResolvedZone::Location,
+            ClockZone::System => match system::get_timezone() {
+                Ok(zone) => ResolvedZone::System(zone),
+                Err(_) => ResolvedZone::Utc,
+            },
+        };
+        Self { weather_data, zone }
+    }
+
+    /// An instant on this clock
+    pub fn zoned(&self, instant: OffsetDateTime) -> ZonedDateTime {
+        match self.zone {
+            ResolvedZone::Location => self.weather_data.zoned(instant),
+            ResolvedZone::System(zone) => ZonedDateTime::in_zone(instant, zone),
+            ResolvedZone::Utc => ZonedDateTime::at_offset(instant.to_offset(UtcOffset::UTC)),
+        }
+    }
+
+    /// The date on this clock when the data was fetched or last rolled forward
+    pub fn today(&self) -> Date {
+        self.zoned(self.weather_data.local_time).datetime().date()
+    }
+
+    /// Short name of the zone (e.g., "NZDT" or "UTC+5:30")
+    pub fn label(&self) -> String {
+        self.zoned(self.weather_data.local_time).zone_label()
+    }
+
+    /// Format an instant as HH:MM, prefixed with the short weekday when it falls on
+    /// another day than today (e.g., "Sat 01:00")
+    pub fn hour(&self, instant: OffsetDateTime) -> String {
+        format_hour(self.zoned(instant).datetime(), self.today())
+    }
+
+    /// Format today's wall-clock time at the location (e.g., sunrise) as HH:MM
+    pub fn time_of_day(&self, time: WeatherTime) -> String {
+        self.zoned(self.weather_data.today_at(time))
+            .datetime()
+            .format(format_description!("[hour]:[minute]"))
+            .unwrap_or_else(|_| "--:--".to_string())
+    }
+
+    /// Format an instant with its date and zone (e.g., "2023-01-14 03:30 NZDT")
+    pub fn date_time(&self, instant: OffsetDateTime) -> String {
+        format_date_time(&self.zoned(instant))
+    }
+}
+
+/// Format an instant on the system's clocks with its date and zone, for output with
+/// no weather data to give the location's zone (UTC when the system zone is unknown)
+pub fn format_system_date_time(instant: OffsetDateTime) -> String {
+    let zoned = match system::get_timezone() {
+        Ok(zone) => ZonedDateTime::in_zone(instant, zone),
+        Err(_) => ZonedDateTime::at_offset(instant.to_offset(UtcOffset::UTC)),
+    };
+    format_date_time(&zoned)
+}
+
+fn format_date_time(zoned: &ZonedDateTime) -> String {
+    let datetime = zoned
+        .datetime()
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+        .unwrap_or_else(|_| "Invalid date".to_string());
+    format!("{} {}", datetime, zoned.zone_label())
+}
+
+/// Label a forecast day relative to today: "Today", "Tomorrow", or the short
+/// weekday and day of the month (e.g., "Sat 14")
+pub fn format_day_label(date: Date, today: Date) -> String {
+    if date == today {
+        "Today".to_string()
+    } else if today.next_day() == Some(date) {
+        "Tomorrow".to_string()
+    } else {
+        date.format(format_description!(
+            "[weekday repr:short] [day padding:none]"
+        ))
+        .unwrap_or_else(|_| date.to_string())
     }
 }
+
+/// Format the start of a forecast hour as HH:MM in the location's time, prefixed with
+/// the short weekday when it falls after `today` (e.g., "Sat 01:00")
+pub fn format_hour(time: OffsetDateTime, today: Date) -> String {
+    let format = if time.date() == today {
+        format_description!("[hour]:[minute]")
+    } else {
+        format_description!("[weekday repr:short] [hour]:[minute]")
+    };
+    time.format(format).unwrap_or_else(|_| "--:--".to_string())
+}
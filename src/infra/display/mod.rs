@@ -1,6 +1,9 @@
-//! Display module for formatting weather data as Waybar JSON output.
+//! Display module for formatting weather data as Waybar JSON output or a terminal report.
 pub mod formatting;
+pub mod template;
+pub mod terminal;
 pub mod waybar;
+pub use terminal::TerminalFormatter;
 pub use waybar::*;
 
 #[cfg(test)]
@@ -8,10 +11,14 @@ mod tests {
     use super::formatting::*;
     use super::*;
     use crate::app::WeatherFormatter;
+    use crate::domain::models::LocationWeather;
     use crate::domain::{
-        Astronomy, CurrentWeather, Humidity, HourlyWeather, LastUpdated, Location, Pressure,
-        Temperature, WeatherCondition, WeatherData, WeatherDay, WeatherTime, WindDirection,
-        WindSpeed, WindSpeedCategory,
+        AirQuality, AlertSeverity, AlertUrgency, Astronomy, CloudCover, Concentration,
+        ConditionKind, CurrentWeather, DailyForecast, DefraIndex, DistanceUnit, HourlyWeather,
+        Humidity, LastUpdated, Location, MoonPhase, Precipitation, PrecipitationUnit, Pressure,
+        PressureUnit, Probability, SpeedUnit, Temperature, TemperatureUnit, UnitSystem, UsEpaIndex,
+        UvIndex, Visibility, WeatherAlert, WeatherCondition, WeatherData, WeatherDay, WeatherTime,
+        WindDirection, WindSpeed, WindSpeedCategory,
     };
 
     #[test]
@@ -60,16 +67,411 @@ mod tests {
             .contains("<span foreground=\"#FFFFFF\">10</span> km/h (Gusts: <span foreground=\"#FFFFFF\">18</span> km/h) N"));
     }
 
+    #[test]
+    fn test_precipitation_in_hourly_entries_and_umbrella_badge() {
+        let mut weather_data = create_mock_weather_data();
+        weather_data.current.precipitation = Some(Precipitation::new(0.2).unwrap());
+        let hour = |time, rain: Option<u8>, snow: Option<u8>, amount: f64| HourlyWeather {
+            time,
+            temperature: Temperature::new(16.0).unwrap(),
+            condition: WeatherCondition::new("Showers".to_string()),
+            wind_speed: WindSpeed::new(20).unwrap(),
+            wind_direction: WindDirection::from_compass("S").unwrap(),
+            precipitation: Some(Precipitation::new(amount).unwrap()),
+            chance_of_rain: rain.map(|chance| Probability::new(chance).unwrap()),
+            chance_of_snow: snow.map(|chance| Probability::new(chance).unwrap()),
+        };
+        weather_data.weather_day = Some(WeatherDay {
+            astronomy: None,
+            hourly_weather: vec![
+                hour(at(13, 14), Some(0), None, 0.0),
+                hour(at(13, 15), Some(20), None, 0.0),
+                hour(at(13, 16), Some(70), None, 1.2),
+                hour(at(13, 17), None, Some(30), 0.5),
+            ],
+        });
+
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.tooltip.contains("🌧️ Precipitation: 0.2 mm"));
+        assert!(output.tooltip.contains("• 14:00 - 16°C Showers\n"));
+        assert!(output.tooltip.contains("• 15:00 - 16°C Showers ☔ 20%\n"));
+        assert!(output
+            .tooltip
+            .contains("• 16:00 - 16°C Showers ☔ 70% 1.2 mm"));
+        assert!(output
+            .tooltip
+            .contains("• 17:00 - 16°C Showers ❄️ 30% 0.5 mm"));
+        // The badge is off by default
+        assert!(!output.text.contains("☂️"));
+
+        let badged = |hours| {
+            WaybarFormatter::new()
+                .with_umbrella_hours(hours)
+                .format(&weather_data)
+                .unwrap()
+                .text
+        };
+        assert!(!badged(2).starts_with("☂️"));
+        assert!(badged(3).starts_with("☂️ "));
+
+        let output = WaybarFormatter::new()
+            .with_units(UnitSystem::IMPERIAL)
+            .format(&weather_data)
+            .unwrap();
+        assert!(output.tooltip.contains("☔ 70% 0.05 in"));
+    }
+
+    #[test]
+    fn test_hours_after_midnight_labelled() {
+        let mut weather_data = create_mock_weather_data_with_hourly();
+        weather_data.local_time = at(13, 23) + time::Duration::minutes(30);
+        let hours = &mut weather_data.weather_day.as_mut().unwrap().hourly_weather;
+        let template = hours[0].clone();
+        *hours = [at(13, 23), at(14, 0), at(14, 1)]
+            .into_iter()
+            .map(|time| HourlyWeather {
+                time,
+                ..template.clone()
+            })
+            .collect();
+
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.tooltip.contains("• 23:00 - 22°C Sunny"));
+        assert!(output.tooltip.contains("• Sat 00:00 - 22°C Sunny"));
+        assert!(output.tooltip.contains("• Sat 01:00 - 22°C Sunny"));
+
+        let mut formatter = WaybarFormatter::new();
+        formatter.cycle_mode();
+        let text = formatter.format(&weather_data).unwrap().text;
+        assert!(text.starts_with("☀️ Sat 00:00 22°C/"));
+
+        let report = TerminalFormatter::new().format(&weather_data).unwrap();
+        assert!(report.contains("Upcoming hours (NZDT)\n      23:00   22°C  Sunny"));
+        assert!(report.contains("\n  Sat 00:00   22°C  Sunny"));
+    }
+
+    #[test]
+    fn test_waybar_output_with_alerts() {
+        let mut weather_data = create_mock_weather_data();
+        let alert = WeatherAlert {
+            id: "wind".to_string(),
+            event: "Wind Advisory".to_string(),
+            headline: "Wind Advisory until 6PM".to_string(),
+            severity: AlertSeverity::Moderate,
+            urgency: AlertUrgency::Unknown,
+            areas: Vec::new(),
+            effective: None,
+            expires: None,
+        };
+        weather_data.alerts.push(alert.clone());
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+
+        assert!(output.tooltip.starts_with(
+            "⚠️ Wind Advisory (Moderate): Wind Advisory until 6PM\n\n📍 Location: Wellington"
+        ));
+        assert!(output
+            .class
+            .ends_with(&["alert".to_string(), "alert-moderate".to_string()]));
+
+        // Known urgency, timing and areas go on a second line; the class follows the
+        // most severe alert
+        let today = weather_data.local_time.date();
+        let expires = weather_data
+            .local_time
+            .replace_date(today.next_day().unwrap());
+        weather_data.alerts.push(WeatherAlert {
+            id: "storm".to_string(),
+            event: "Storm Warning".to_string(),
+            headline: "Storm Warning until tomorrow".to_string(),
+            severity: AlertSeverity::Severe,
+            urgency: AlertUrgency::Immediate,
+            areas: vec!["Wellington".to_string(), "Kapiti Coast".to_string()],
+            effective: None,
+            expires: Some(expires.replace_time(time::macros::time!(6:00))),
+        });
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        let details = "\n          Immediate · until Sat 06:00 · Wellington, Kapiti Coast\n";
+        assert!(output
+            .tooltip
+            .contains(&format!("Storm Warning until tomorrow{}", details)));
+        assert!(output
+            .class
+            .ends_with(&["alert".to_string(), "alert-severe".to_string()]));
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains(r#""alert","alert-severe"]"#));
+
+        let report = TerminalFormatter::new().format(&weather_data).unwrap();
+        assert!(report.starts_with(
+            "⚠️ Wind Advisory (Moderate): Wind Advisory until 6PM\n\
+             ⚠️ Storm Warning (Severe): Storm Warning until tomorrow\n   Immediate"
+        ));
+    }
+
+    #[test]
+    fn test_tooltip_escapes_markup() {
+        let mut weather_data = create_mock_weather_data();
+        weather_data.alerts.push(WeatherAlert {
+            id: "surf".to_string(),
+            event: "Wind & Surf Warning".to_string(),
+            headline: "Waves <4m> expected".to_string(),
+            severity: AlertSeverity::Moderate,
+            urgency: AlertUrgency::Unknown,
+            areas: vec!["Hutt & Wairarapa".to_string()],
+            effective: None,
+            expires: None,
+        });
+        let summary = [LocationWeather {
+            query: "Lyttelton & Sumner".parse().unwrap(),
+            weather: None,
+            shown: false,
+        }];
+
+        let output = WaybarFormatter::new()
+            .format_with_summary(&weather_data, &summary)
+            .unwrap();
+        assert!(output
+            .tooltip
+            .starts_with("⚠️ Wind &amp; Surf Warning (Moderate): Waves &lt;4m&gt; expected\n"));
+        assert!(output.tooltip.contains("Hutt &amp; Wairarapa"));
+        assert!(output
+            .tooltip
+            .ends_with("• Lyttelton &amp; Sumner: unavailable"));
+
+        // Names and conditions from the provider, in the bar text and the tooltip
+        weather_data.location = Location::new("Brown & <Co>".to_string());
+        weather_data.current.condition = WeatherCondition::new("Rain & fog".to_string());
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.text.ends_with(" Brown &amp; &lt;Co&gt;"));
+        assert!(output.tooltip.contains("Location: Brown &amp; &lt;Co&gt;"));
+        assert!(output.tooltip.contains("Rain &amp; fog"));
+        assert!(!output.tooltip.contains("<Co>"));
+    }
+
+    #[test]
+    fn test_tooltip_without_alerts_starts_with_location() {
+        let output = WaybarFormatter::new()
+            .format(&create_mock_weather_data())
+            .unwrap();
+        assert!(output.tooltip.starts_with("📍 Location: Wellington"));
+        assert!(!output.class.iter().any(|class| class.starts_with("alert")));
+    }
+
+    #[test]
+    fn test_location_shown_with_region_and_country() {
+        let mut weather_data = create_mock_weather_data();
+        weather_data.location = Location::new("Richmond".to_string())
+            .with_region(Some("Virginia".to_string()))
+            .with_country(Some("United States of America".to_string()));
+
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output
+            .tooltip
+            .starts_with("📍 Location: Richmond, Virginia, United States of America\n"));
+        // The bar text keeps to the short name
+        assert!(output.text.ends_with(" Richmond"));
+
+        let report = TerminalFormatter::new().format(&weather_data).unwrap();
+        assert!(report.contains(" Richmond, Virginia, United States of America, Clear\n"));
+
+        // A region repeating the name is left out
+        weather_data.location = Location::new("Wellington".to_string())
+            .with_region(Some("Wellington".to_string()))
+            .with_country(Some("New Zealand".to_string()));
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output
+            .tooltip
+            .starts_with("📍 Location: Wellington, New Zealand\n"));
+    }
+
+    #[test]
+    fn test_waybar_custom_templates() {
+        let template =
+            |source: &str| Some(template::Template::parse(source, &TEMPLATE_SCHEMA).unwrap());
+        let formatter = WaybarFormatter::new()
+            .with_bar_fields(vec![BarField::CloudCover])
+            .with_text_template(template("{temp} {condition}{?uv} UV {uv}{/uv}"))
+            .with_tooltip_template(template(
+                "{location}{#hours}\n{time} {icon} {temp} (now {feels_like}){/hours}{!alerts}\nNo alerts{/alerts}",
+            ));
+
+        let mut weather_data = create_mock_weather_data_with_hourly();
+        weather_data.current.cloud_cover = Some(CloudCover::new(40).unwrap());
+        let output = formatter.format(&weather_data).unwrap();
+        // Bar fields are still appended to the custom text
+        assert_eq!(output.text, "20°C Clear ☁️ 40%");
+        assert_eq!(
+            output.tooltip,
+            "Wellington\n12:00 ☀️ 22°C (now 22°C)\nNo alerts"
+        );
+
+        // Other display modes keep their own text
+        let mut formatter = formatter;
+        formatter.cycle_mode();
+        formatter.cycle_mode();
+        assert_eq!(
+            formatter.format(&weather_data).unwrap().text,
+            "☀️ ↑22°C ↓20°C Wellington ☁️ 40%"
+        );
+
+        // The default templates reproduce the built-in layout
+        let output = WaybarFormatter::new()
+            .with_text_template(template(DEFAULT_TEXT_TEMPLATE))
+            .format(&weather_data)
+            .unwrap();
+        assert_eq!(
+            output.text,
+            WaybarFormatter::new().format(&weather_data).unwrap().text
+        );
+    }
+
+    #[test]
+    fn test_waybar_class_alt_and_percentage() {
+        let mut weather_data = create_mock_weather_data_with_astronomy();
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert_eq!(output.class, vec!["clear", "day", "wind-calm"]);
+        assert_eq!(output.alt, "clear-day");
+        assert_eq!(output.percentage, None);
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains(r#""class":["clear","day","wind-calm"],"alt":"clear-day""#));
+        assert!(!json.contains("percentage"));
+
+        // After sunset, in a gale, shown from the cache after a failed fetch
+        weather_data.local_time = weather_data
+            .local_time
+            .replace_time(time::macros::time!(21:00));
+        weather_data.current.condition = WeatherCondition::new("Partly cloudy".to_string());
+        weather_data.current.wind_speed = WindSpeed::new(60).unwrap();
+        weather_data.stale_since = Some(time::macros::datetime!(2023-01-13 01:30 UTC));
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert_eq!(
+            output.class,
+            vec!["partly-cloudy", "night", "wind-gale", "stale"]
+        );
+        assert_eq!(output.alt, "partly-cloudy-night");
+
+        // Without sunrise and sunset there is no day or night
+        let output = WaybarFormatter::new()
+            .format(&create_mock_weather_data())
+            .unwrap();
+        assert_eq!(output.class, vec!["clear", "wind-calm"]);
+        assert_eq!(output.alt, "clear");
+
+        // The provider's day flag wins over sunrise and sunset
+        let mut weather_data = create_mock_weather_data_with_astronomy();
+        weather_data.current.condition =
+            WeatherCondition::new("Clear".to_string()).with_is_day(false);
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert_eq!(output.alt, "clear-night");
+        assert!(output.text.starts_with("🌙"));
+
+        let percentage = |source: PercentageSource, data: &WeatherData| {
+            WaybarFormatter::new()
+                .with_percentage(Some(source))
+                .format(data)
+                .unwrap()
+                .percentage
+        };
+        let mut weather_data = create_mock_weather_data_with_hourly();
+        assert_eq!(
+            percentage(PercentageSource::Humidity, &weather_data),
+            Some(60)
+        );
+        // 20°C sits two thirds of the way through the default 0-30°C range
+        assert_eq!(
+            percentage("temperature".parse().unwrap(), &weather_data),
+            Some(67)
+        );
+        let range = PercentageSource::Temperature {
+            min: 22.0,
+            max: 30.0,
+        };
+        assert_eq!(percentage(range, &weather_data), Some(0));
+        assert_eq!(
+            percentage(PercentageSource::ChanceOfRain, &weather_data),
+            None
+        );
+        weather_data.weather_day.as_mut().unwrap().hourly_weather[0].chance_of_rain =
+            Some(Probability::new(35).unwrap());
+        assert_eq!(
+            percentage(PercentageSource::ChanceOfRain, &weather_data),
+            Some(35)
+        );
+        assert!("pressure".parse::<PercentageSource>().is_err());
+    }
+
     #[test]
     fn test_error_output_formatting() {
-        let error_output =
-            WaybarFormatter::create_error_output("Wellington", anyhow::anyhow!("Network error"));
+        let error_output = WaybarFormatter::create_error_output(
+            Some("Wellington"),
+            "WeatherAPI.com → Open-Meteo",
+            anyhow::anyhow!("Network error"),
+        );
 
         assert!(error_output.text.contains("Weather unavailable"));
         assert!(error_output
             .tooltip
             .contains("Unable to fetch weather data for Wellington"));
         assert!(error_output.tooltip.contains("Network error"));
+        assert!(error_output
+            .tooltip
+            .contains("Service: WeatherAPI.com → Open-Meteo"));
+        // The attempt is on the system's clocks, labelled with its zone
+        let now = format_system_date_time(time::OffsetDateTime::now_utc());
+        let zone = now.rsplit(' ').next().unwrap();
+        let last_attempt = error_output.tooltip.lines().last().unwrap();
+        assert!(last_attempt.starts_with("Last attempt: 20"));
+        assert!(last_attempt.ends_with(&format!(" {}", zone)));
+        assert_eq!(error_output.class, vec!["error"]);
+        assert_eq!(error_output.alt, "error");
+    }
+
+    #[test]
+    fn test_error_output_lists_every_cause() {
+        let error = anyhow::anyhow!("Unknown placeholder '{{tmep}}' at 8")
+            .context("Invalid text_template")
+            .context("Invalid profile 'a'");
+        let error_output = WaybarFormatter::create_error_output(None, "Open-Meteo", error);
+
+        assert!(error_output
+            .tooltip
+            .starts_with("Unable to show the weather\n"));
+        assert!(error_output.tooltip.contains(
+            "Error: Invalid profile 'a': Invalid text_template: Unknown placeholder '{tmep}' at 8"
+        ));
+
+        // Request URLs join their query with '&', which Pango would read as an entity
+        let error = anyhow::anyhow!("timed out")
+            .context("Failed to send request to: https://example.com/?q=Wellington&days=3");
+        let error_output =
+            WaybarFormatter::create_error_output(Some("Wellington"), "WeatherAPI.com", error);
+        assert!(error_output
+            .tooltip
+            .contains("?q=Wellington&amp;days=3: timed out"));
+    }
+
+    #[test]
+    fn test_tooltip_shows_source() {
+        let mut weather_data = create_mock_weather_data();
+        weather_data.source = "Open-Meteo".to_string();
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+
+        assert!(output.tooltip.ends_with("📡 Source: Open-Meteo"));
+    }
+
+    #[test]
+    fn test_tooltip_marks_stale_data() {
+        let mut weather_data = create_mock_weather_data();
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(!output.tooltip.contains("Stale since"));
+
+        weather_data.stale_since = Some(time::macros::datetime!(2023-01-13 01:30 UTC));
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        let stale_line = output
+            .tooltip
+            .lines()
+            .find(|line| line.contains("Stale since"))
+            .expect("Stale marker present");
+        assert!(stale_line.ends_with("(latest fetch failed)"));
     }
 
     #[test]
@@ -87,64 +489,220 @@ mod tests {
         let weather_data = create_mock_weather_data();
         let output = WaybarFormatter::new().format(&weather_data).unwrap();
 
-        // Test that WeatherAPI's last updated timestamp is displayed with UTC timezone
-        // Mock data uses epoch 1673620200 which corresponds to "2023-01-13 14:30Z"
-        assert!(output.tooltip.contains("🕐 Updated: 2023-01-13 14:30Z"));
-
-        // Ensure we're showing the full date-time format with timezone, not just time
-        assert!(output.tooltip.contains("2023-01-13 14:30Z"));
+        // Mock data uses epoch 1673620200 (2023-01-13 14:30Z), shown on Auckland's clocks
+        assert!(output.tooltip.contains("🕐 Updated: 2023-01-14 03:30 NZDT"));
 
-        // Ensure we're NOT showing just time format (HH:MM:SS without date)
+        // Exactly one line with the full date-time and its zone
         let lines_with_updated: Vec<&str> = output
             .tooltip
             .lines()
             .filter(|line| line.contains("🕐 Updated:"))
             .collect();
         assert_eq!(lines_with_updated.len(), 1);
-        assert!(lines_with_updated[0].contains("2023-01-13 14:30Z"));
+        assert!(lines_with_updated[0].ends_with("NZDT"));
+    }
+
+    #[test]
+    fn test_times_shown_in_chosen_zone() {
+        let mut weather_data = create_mock_weather_data_with_hourly();
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.tooltip.contains("Upcoming Hours (NZDT):"));
+        assert!(output.tooltip.contains("• 12:00 - 22°C Sunny"));
+
+        // Without a zone name only the UTC offset is known
+        weather_data.time_zone = None;
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.tooltip.contains("Upcoming Hours (UTC+13):"));
+        assert!(output
+            .tooltip
+            .contains("🕐 Updated: 2023-01-14 03:30 UTC+13"));
+
+        // On the system's clocks every time carries the system zone's label
+        let clock = Clock::new(ClockZone::System, &weather_data);
+        let label = clock.label();
+        let output = WaybarFormatter::new()
+            .with_clock_zone(ClockZone::System)
+            .format(&weather_data)
+            .unwrap();
+        assert!(output
+            .tooltip
+            .contains(&format!("Upcoming Hours ({}):", label)));
+        assert!(output.tooltip.contains(&format!(
+            "🕐 Updated: {}",
+            clock.date_time(weather_data.current.last_updated.instant())
+        )));
+        let report = TerminalFormatter::new()
+            .with_clock_zone(ClockZone::System)
+            .format(&weather_data)
+            .unwrap();
+        assert!(report.contains(&format!("Upcoming hours ({})\n", label)));
+        assert!(report.contains(&format!(
+            "Updated {} from",
+            clock.date_time(weather_data.current.last_updated.instant())
+        )));
     }
 
     #[test]
     fn test_condition_icon() {
+        let icons = Icons::default();
         let condition = WeatherCondition::new("Clear".to_string());
-        assert_eq!(condition_icon(&condition), "☀️");
+        assert_eq!(icons.condition(&condition), "☀️");
 
         let cloudy = WeatherCondition::new("Partly cloudy".to_string());
-        assert_eq!(condition_icon(&cloudy), "⛅");
+        assert_eq!(icons.condition(&cloudy), "⛅");
 
         let rainy = WeatherCondition::new("Light rain".to_string());
-        assert_eq!(condition_icon(&rainy), "🌧️");
+        assert_eq!(icons.condition(&rainy), "🌧️");
+
+        // Thunder wins over the rain mentioned first
+        let thundery = WeatherCondition::new("Patchy light rain with thunder".to_string());
+        assert_eq!(icons.condition(&thundery), "⛈️");
+
+        // Night variants where the sky shows
+        let night = |description: &str| {
+            icons.condition(&WeatherCondition::new(description.to_string()).with_is_day(false))
+        };
+        assert_eq!(night("Clear"), "🌙");
+        assert_eq!(night("Partly cloudy"), "☁️");
+        assert_eq!(night("Light rain shower"), "🌧️");
+        assert_eq!(night("Fog"), "🌫️");
+
+        // The provider's classification wins over the description
+        let coded = WeatherCondition::new("Sky is clear".to_string())
+            .with_code(1000)
+            .with_kind(ConditionKind::Clear)
+            .with_is_day(true);
+        assert_eq!(icons.condition(&coded), "☀️");
+        let showers = WeatherCondition::new("Patchy rain nearby".to_string())
+            .with_kind(ConditionKind::RainShowers);
+        assert_eq!(icons.condition(&showers), "🌦️");
+    }
+
+    #[test]
+    fn test_icon_sets_and_overrides() {
+        let clear_night = WeatherCondition::new("Clear".to_string()).with_is_day(false);
+        let rain = WeatherCondition::new("Light rain".to_string());
+
+        let nerd = Icons::new("nerdfont".parse().unwrap());
+        assert_eq!(nerd.condition(&clear_night), "\u{e32b}");
+        assert_eq!(nerd.moon(MoonPhase::Full), "\u{e39b}");
+        assert_eq!(nerd.line(LineIcon::Sunrise), "\u{e34c}");
+
+        // Weather Icons has no location glyph, so that line keeps its emoji
+        let weather_icons = Icons::new(IconSet::WeatherIcons);
+        assert_eq!(weather_icons.condition(&rain), "\u{f008}");
+        assert_eq!(weather_icons.line(LineIcon::Location), "📍");
+
+        let text = Icons::new(IconSet::Text);
+        assert_eq!(text.condition(&clear_night), "Clear");
+        assert_eq!(text.moon(MoonPhase::WaxingGibbous), "WXG");
+        assert_eq!(text.prefix(LineIcon::Humidity, "60%"), "60%");
+
+        // Night-only overrides win over ones for the whole kind
+        let icons = Icons::default()
+            .with_override("clear", "*")
+            .unwrap()
+            .with_override("clear_night", "☾")
+            .unwrap()
+            .with_override("moon_full", "○")
+            .unwrap()
+            .with_override("alert", "")
+            .unwrap();
+        assert_eq!(icons.condition(&clear_night), "☾");
+        assert_eq!(
+            icons.condition(&WeatherCondition::new("Sunny".to_string())),
+            "*"
+        );
+        assert_eq!(icons.moon(MoonPhase::Full), "○");
+        assert_eq!(icons.prefix(LineIcon::Alert, "Gale"), "Gale");
+
+        let error = Icons::default()
+            .with_override("cloudy_evening", "x")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Unknown icon 'cloudy_evening'"));
+        assert!("wingdings".parse::<IconSet>().is_err());
+    }
+
+    #[test]
+    fn test_icon_keys_are_distinct() {
+        let mut keys = CONDITION_KINDS
+            .iter()
+            .flat_map(|&kind| {
+                let key = condition_kind_key(kind);
+                [
+                    key.to_string(),
+                    format!("{}_day", key),
+                    format!("{}_night", key),
+                ]
+            })
+            .chain(MoonPhase::ALL.map(|phase| moon_phase_key(phase).to_string()))
+            .chain(LineIcon::ALL.map(|line| line.key().to_string()))
+            .collect::<Vec<_>>();
+        let count = keys.len();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+
+        // The windy condition and the wind line are overridden separately
+        let icons = Icons::default()
+            .with_override("windy", "~")
+            .unwrap()
+            .with_override("wind", "w")
+            .unwrap();
+        assert_eq!(
+            icons.condition(&WeatherCondition::new("Windy".to_string())),
+            "~"
+        );
+        assert_eq!(icons.line(LineIcon::Wind), "w");
+    }
+
+    #[test]
+    fn test_waybar_text_icon_set() {
+        let weather_data = create_mock_weather_data();
+        let output = WaybarFormatter::new()
+            .with_icons(Icons::new(IconSet::Text))
+            .format(&weather_data)
+            .unwrap();
+
+        assert!(output.text.starts_with("Clear 20°C"));
+        assert!(output
+            .tooltip
+            .starts_with("Location: Wellington\nTemperature: 20°C"));
+        assert!(output.tooltip.contains(" Moon: "));
+        assert!(!output.tooltip.contains('🕐'));
     }
 
     #[test]
     fn test_wind_speed_format_colored() {
         let calm = WindSpeed::new(10).unwrap();
         assert_eq!(
-            format_wind_colored(&calm),
+            colored_kmh(&calm),
             "<span foreground=\"#FFFFFF\">10</span> km/h"
         );
 
         let moderate = WindSpeed::new(30).unwrap();
         assert_eq!(
-            format_wind_colored(&moderate),
+            colored_kmh(&moderate),
             "<span foreground=\"#00AA00\">30</span> km/h"
         );
 
         let gale = WindSpeed::new(60).unwrap();
         assert_eq!(
-            format_wind_colored(&gale),
+            colored_kmh(&gale),
             "<span foreground=\"#FFA500\">60</span> km/h"
         );
 
         let storm = WindSpeed::new(100).unwrap();
         assert_eq!(
-            format_wind_colored(&storm),
+            colored_kmh(&storm),
             "<span foreground=\"#FF0000\">100</span> km/h"
         );
 
         let hurricane = WindSpeed::new(150).unwrap();
         assert_eq!(
-            format_wind_colored(&hurricane),
+            colored_kmh(&hurricane),
             "<span foreground=\"#9B30FF\">150</span> km/h"
         );
     }
@@ -154,14 +712,14 @@ mod tests {
         let calm_with_moderate_gusts = WindSpeed::with_gusts(15, Some(45)).unwrap();
         assert_eq!(calm_with_moderate_gusts.category(), WindSpeedCategory::Calm);
         assert_eq!(
-            format_wind_colored(&calm_with_moderate_gusts),
+            colored_kmh(&calm_with_moderate_gusts),
             "<span foreground=\"#FFFFFF\">15</span> km/h (Gusts: <span foreground=\"#00AA00\">45</span> km/h)"
         );
 
         let moderate_with_gale_gusts = WindSpeed::with_gusts(25, Some(60)).unwrap();
         assert_eq!(moderate_with_gale_gusts.category(), WindSpeedCategory::ModerateBreezes);
         assert_eq!(
-            format_wind_colored(&moderate_with_gale_gusts),
+            colored_kmh(&moderate_with_gale_gusts),
             "<span foreground=\"#00AA00\">25</span> km/h (Gusts: <span foreground=\"#FFA500\">60</span> km/h)"
         );
     }
@@ -171,43 +729,465 @@ mod tests {
     #[test]
     fn test_wind_speed_calm_color() {
         let calm = WindSpeed::new(10).unwrap();
-        assert_eq!(format_wind_colored(&calm), "<span foreground=\"#FFFFFF\">10</span> km/h");
+        assert_eq!(
+            colored_kmh(&calm),
+            "<span foreground=\"#FFFFFF\">10</span> km/h"
+        );
     }
 
     #[test]
     fn test_wind_speed_moderate_color() {
         let moderate = WindSpeed::new(35).unwrap();
-        assert_eq!(format_wind_colored(&moderate), "<span foreground=\"#00AA00\">35</span> km/h");
+        assert_eq!(
+            colored_kmh(&moderate),
+            "<span foreground=\"#00AA00\">35</span> km/h"
+        );
     }
 
     #[test]
     fn test_wind_speed_gale_color() {
         let gale = WindSpeed::new(70).unwrap();
-        assert_eq!(format_wind_colored(&gale), "<span foreground=\"#FFA500\">70</span> km/h");
+        assert_eq!(
+            colored_kmh(&gale),
+            "<span foreground=\"#FFA500\">70</span> km/h"
+        );
     }
 
     #[test]
     fn test_wind_speed_storm_color() {
         let storm = WindSpeed::new(100).unwrap();
-        assert_eq!(format_wind_colored(&storm), "<span foreground=\"#FF0000\">100</span> km/h");
+        assert_eq!(
+            colored_kmh(&storm),
+            "<span foreground=\"#FF0000\">100</span> km/h"
+        );
     }
 
     #[test]
     fn test_wind_speed_hurricane_color() {
         let hurricane = WindSpeed::new(150).unwrap();
-        assert_eq!(format_wind_colored(&hurricane), "<span foreground=\"#9B30FF\">150</span> km/h");
+        assert_eq!(
+            colored_kmh(&hurricane),
+            "<span foreground=\"#9B30FF\">150</span> km/h"
+        );
+    }
+
+    #[test]
+    fn test_display_mode_cycle_wraps() {
+        let mut mode = DisplayMode::default();
+        assert_eq!(mode, DisplayMode::Current);
+        for expected in [
+            DisplayMode::NextHour,
+            DisplayMode::HighLow,
+            DisplayMode::SunCountdown,
+            DisplayMode::Current,
+        ] {
+            mode = mode.next();
+            assert_eq!(mode, expected);
+        }
+    }
+
+    #[test]
+    fn test_display_text_per_mode() {
+        let mut weather_data = create_mock_weather_data_with_astronomy();
+        let hour = |time, temperature: i32| HourlyWeather {
+            time,
+            temperature: Temperature::new(f64::from(temperature)).unwrap(),
+            condition: WeatherCondition::new("Light rain".to_string()),
+            wind_speed: WindSpeed::new(25).unwrap(),
+            wind_direction: WindDirection::from_compass("S").unwrap(),
+            precipitation: None,
+            chance_of_rain: None,
+            chance_of_snow: None,
+        };
+        weather_data.weather_day.as_mut().unwrap().hourly_weather = vec![
+            hour(at(13, 14), 21),
+            hour(at(13, 15), 23),
+            hour(at(13, 16), 17),
+            hour(at(14, 1), 30),
+        ];
+
+        let mut formatter = WaybarFormatter::new();
+        let text = |formatter: &WaybarFormatter| formatter.format(&weather_data).unwrap().text;
+
+        assert!(text(&formatter).starts_with("☀️ 20°C/"));
+
+        formatter.cycle_mode();
+        assert_eq!(
+            text(&formatter),
+            "🌧️ 15:00 23°C/ <span foreground=\"#00AA00\">25</span> km/h Wellington"
+        );
+
+        // Tomorrow's hours are not part of today's range
+        formatter.cycle_mode();
+        assert_eq!(text(&formatter), "☀️ ↑23°C ↓17°C Wellington");
+
+        // Local time is 14:30 and sunset 18:30
+        formatter.cycle_mode();
+        assert_eq!(text(&formatter), "🌇 Sunset in 4h 00m Wellington");
+
+        formatter.cycle_mode();
+        assert!(text(&formatter).starts_with("☀️ 20°C/"));
+    }
+
+    #[test]
+    fn test_display_mode_falls_back_without_data() {
+        let weather_data = create_mock_weather_data();
+        let mut formatter = WaybarFormatter::new();
+        let current = formatter.format(&weather_data).unwrap().text;
+
+        // No hourly forecast for the next hour, and no astronomy for a countdown
+        formatter.cycle_mode();
+        assert_eq!(formatter.format(&weather_data).unwrap().text, current);
+        formatter.cycle_mode();
+        formatter.cycle_mode();
+        assert_eq!(formatter.format(&weather_data).unwrap().text, current);
+    }
+
+    #[test]
+    fn test_waybar_output_in_imperial_units() {
+        let weather_data = create_mock_weather_data_with_hourly();
+        let output = WaybarFormatter::new()
+            .with_units(UnitSystem::IMPERIAL)
+            .format(&weather_data)
+            .unwrap();
+
+        // 20°C and 15 km/h; the colour still follows the km/h category
+        assert!(output
+            .text
+            .starts_with("☀️ 68°F/ <span foreground=\"#FFFFFF\">9</span> mph"));
+        assert!(output.tooltip.contains("Temperature: 68°F"));
+        assert!(output.tooltip.contains("Feels like: 72°F"));
+        assert!(output.tooltip.contains("Pressure: 29.91 inHg"));
+        assert!(output.tooltip.contains("• 12:00 - 72°F Sunny"));
+        assert!(output.tooltip.contains("<span foreground=\"#FFFFFF\">6</span> mph (Gusts: <span foreground=\"#FFFFFF\">11</span> mph) N"));
+    }
+
+    #[test]
+    fn test_waybar_output_in_mixed_units() {
+        let units = UnitSystem {
+            temperature: TemperatureUnit::Kelvin,
+            speed: SpeedUnit::Beaufort,
+            pressure: PressureUnit::MillimetresOfMercury,
+            precipitation: PrecipitationUnit::Millimetres,
+            distance: DistanceUnit::Kilometres,
+        };
+        let output = WaybarFormatter::new()
+            .with_units(units)
+            .format(&create_mock_weather_data())
+            .unwrap();
+
+        assert!(output
+            .text
+            .starts_with("☀️ 293 K/ <span foreground=\"#FFFFFF\">3</span> Bft"));
+        assert!(output.tooltip.contains("Pressure: 760 mmHg"));
+    }
+
+    #[test]
+    fn test_unit_formatting() {
+        let temperature = Temperature::new(-3.0).unwrap();
+        assert_eq!(
+            format_temperature(&temperature, TemperatureUnit::Celsius),
+            "-3°C"
+        );
+        assert_eq!(
+            format_temperature(&temperature, TemperatureUnit::Fahrenheit),
+            "27°F"
+        );
+        // A reading in °F comes back unchanged; through whole °C (23°C) it would be 73°F
+        let fahrenheit = Temperature::new((74.0 - 32.0) * 5.0 / 9.0).unwrap();
+        assert_eq!(
+            format_temperature(&fahrenheit, TemperatureUnit::Fahrenheit),
+            "74°F"
+        );
+        assert_eq!(
+            format_temperature(&fahrenheit, TemperatureUnit::Celsius),
+            "23°C"
+        );
+
+        let pressure = Pressure::new(1013).unwrap();
+        assert_eq!(
+            format_pressure(&pressure, PressureUnit::Hectopascals),
+            "1013 hPa"
+        );
+
+        let wind = WindSpeed::with_gusts(36, Some(54)).unwrap();
+        assert_eq!(
+            format_wind(&wind, SpeedUnit::MetresPerSecond),
+            "10 m/s (Gusts: 15 m/s)"
+        );
+        assert_eq!(format_wind(&wind, SpeedUnit::Knots), "19 kn (Gusts: 29 kn)");
+
+        let visibility = Visibility::new(24.14).unwrap();
+        assert_eq!(
+            format_visibility(&visibility, DistanceUnit::Kilometres),
+            "24 km"
+        );
+        assert_eq!(format_visibility(&visibility, DistanceUnit::Miles), "15 mi");
+        let fog = Visibility::new(0.4).unwrap();
+        assert_eq!(format_visibility(&fog, DistanceUnit::Kilometres), "0.4 km");
+
+        let uv = UvIndex::new(7.35).unwrap();
+        assert_eq!(
+            format_uv_colored(&uv),
+            "<span foreground=\"#F85900\">7</span> (High)"
+        );
+    }
+
+    #[test]
+    fn test_air_quality_line_and_badge() {
+        let mut weather_data = create_mock_weather_data();
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(!output.tooltip.contains("Air Quality"));
+
+        weather_data.air_quality = Some(AirQuality {
+            pm2_5: Some(Concentration::new(38.2).unwrap()),
+            pm10: Some(Concentration::new(51.0).unwrap()),
+            ozone: None,
+            nitrogen_dioxide: None,
+            sulphur_dioxide: None,
+            carbon_monoxide: None,
+            us_epa_index: Some(UsEpaIndex::new(3).unwrap()),
+            gb_defra_index: Some(DefraIndex::new(5).unwrap()),
+        });
+
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output.tooltip.contains(
+            "🫁 Air Quality: <span foreground=\"#FF7E00\">Unhealthy for Sensitive Groups</span> \
+             (US EPA 3), UK DEFRA 5 (Moderate)\n          PM2.5 38.2, PM10 51.0 µg/m³"
+        ));
+        assert!(!output.text.contains("😷"));
+
+        let badged = |level| {
+            WaybarFormatter::new()
+                .with_aqi_threshold(level)
+                .format(&weather_data)
+                .unwrap()
+                .text
+        };
+        assert!(badged(2).starts_with("😷 ☀️"));
+        assert!(!badged(3).contains("😷"));
+
+        let report = TerminalFormatter::new().format(&weather_data).unwrap();
+        assert!(report.contains(
+            "Air quality  Unhealthy for Sensitive Groups (US EPA 3), UK DEFRA 5 (Moderate)\n\
+             \x20            PM2.5 38.2, PM10 51.0 µg/m³"
+        ));
+    }
+
+    #[test]
+    fn test_uv_visibility_and_cloud_cover() {
+        let mut weather_data = create_mock_weather_data();
+        weather_data.current.uv_index = Some(UvIndex::new(3.0).unwrap());
+        weather_data.current.visibility = Some(Visibility::new(10.0).unwrap());
+        weather_data.current.cloud_cover = Some(CloudCover::new(40).unwrap());
+
+        let output = WaybarFormatter::new().format(&weather_data).unwrap();
+        assert!(output
+            .tooltip
+            .contains("😎 UV Index: <span foreground=\"#F7E400\">3</span> (Moderate)"));
+        assert!(output.tooltip.contains("👁️ Visibility: 10 km"));
+        assert!(output.tooltip.contains("☁️ Cloud Cover: 40%"));
+        // Bar fields are opt-in
+        assert!(output.text.ends_with("Wellington"));
+
+        let output = WaybarFormatter::new()
+            .with_units(UnitSystem::UK)
+            .with_bar_fields(vec![
+                BarField::CloudCover,
+                BarField::Uv,
+                BarField::Visibility,
+            ])
+            .format(&weather_data)
+            .unwrap();
+        assert!(output
+            .text
+            .ends_with("Wellington ☁️ 40% UV <span foreground=\"#F7E400\">3</span> 👁️ 6.2 mi"));
+
+        // Fields the provider did not report are left out
+        weather_data.current.uv_index = None;
+        let output = WaybarFormatter::new()
+            .with_bar_fields(vec![BarField::Uv, BarField::CloudCover])
+            .format(&weather_data)
+            .unwrap();
+        assert!(output.text.ends_with("Wellington ☁️ 40%"));
+        assert!(!output.tooltip.contains("UV Index"));
+
+        assert_eq!("clouds".parse::<BarField>().unwrap(), BarField::CloudCover);
+        assert!("pollen".parse::<BarField>().is_err());
+    }
+
+    #[test]
+    fn test_terminal_report() {
+        let report = TerminalFormatter::new()
+            .format(&create_mock_weather_data_with_astronomy())
+            .unwrap();
+
+        assert!(report.starts_with("☀️ Wellington, Clear\n"));
+        assert!(report.contains("Temperature  20°C (feels like 22°C)"));
+        assert!(report.contains("Wind         15 km/h NW"));
+        assert!(report.contains("Sunset       18:30 (day length 12:00)"));
+        assert!(report.ends_with("from WeatherAPI.com"));
+        // Plain text: no Pango markup
+        assert!(!report.contains("<span"));
+    }
+
+    #[test]
+    fn test_location_summary() {
+        let weather_data = create_mock_weather_data();
+        let mut stale = create_mock_weather_data();
+        stale.location = Location::new("Auckland".to_string());
+        stale.stale_since = Some(time::macros::datetime!(2023-01-13 00:00 UTC));
+        let summary = [
+            LocationWeather {
+                query: "Wellington".parse().unwrap(),
+                weather: Some(weather_data.clone()),
+                shown: true,
+            },
+            LocationWeather {
+                query: "Auckland".parse().unwrap(),
+                weather: Some(stale),
+                shown: false,
+            },
+            LocationWeather {
+                query: "-43.53,172.63".parse().unwrap(),
+                weather: None,
+                shown: false,
+            },
+        ];
+
+        let output = WaybarFormatter::new()
+            .format_with_summary(&weather_data, &summary)
+            .unwrap();
+        assert!(output.tooltip.ends_with(
+            "\n\n📍 Locations:\n\
+             • <b>☀️ Wellington: 20°C, Clear</b>\n\
+             • ☀️ Auckland: 20°C, Clear (stale)\n\
+             • -43.53,172.63: unavailable"
+        ));
+
+        let report = TerminalFormatter::new()
+            .format_with_summary(&weather_data, &summary)
+            .unwrap();
+        assert!(report.ends_with(
+            "\n\nLocations\n\
+             ▸ Wellington                20°C  Clear\n  \
+             Auckland                  20°C  Clear (stale)\n  \
+             -43.53,172.63            unavailable"
+        ));
+    }
+
+    #[test]
+    fn test_terminal_report_hours() {
+        let weather_data = create_mock_weather_data_with_hourly();
+
+        let report = TerminalFormatter::new().format(&weather_data).unwrap();
+        assert!(report.contains("Upcoming hours (NZDT)\n      12:00   22°C  Sunny"));
+        assert!(report.contains("10 km/h (Gusts: 18 km/h) N"));
+
+        let report = TerminalFormatter::new()
+            .with_hours(0)
+            .format(&weather_data)
+            .unwrap();
+        assert!(!report.contains("Upcoming hours"));
+    }
+
+    #[test]
+    fn test_terminal_report_units() {
+        let report = TerminalFormatter::new()
+            .with_units(UnitSystem::UK)
+            .format(&create_mock_weather_data())
+            .unwrap();
+
+        assert!(report.contains("Temperature  20°C (feels like 22°C)"));
+        assert!(report.contains("Wind         9 mph NW"));
+        assert!(report.contains("Pressure     1013 hPa"));
+    }
+
+    #[test]
+    fn test_waybar_output_with_daily_forecast() {
+        let output = WaybarFormatter::new()
+            .format(&create_mock_weather_data_with_daily())
+            .unwrap();
+
+        assert!(output.tooltip.contains(&format!(
+            "📅 Next Days:\n\
+             • Today ☀️ ↑23°C ↓14°C Sunny\n          🌧️ 0% 0.0 mm, 💨 {}\n\
+             • Tomorrow 🌧️ ↑18°C ↓12°C Moderate rain\n          🌧️ 89% 8.3 mm, 💨 {}\n\
+             • Sun 15 ⛅ ↑21°C ↓13°C Partly cloudy\n          🌧️ 1.2 mm, 💨 {}",
+            compact_kmh(25),
+            compact_kmh(52),
+            compact_kmh(30)
+        )));
+        // The daily forecast precedes the update footer
+        let tooltip = &output.tooltip;
+        assert!(tooltip.find("Next Days").unwrap() < tooltip.find("Updated").unwrap());
+
+        let output = WaybarFormatter::new()
+            .format(&create_mock_weather_data())
+            .unwrap();
+        assert!(!output.tooltip.contains("Next Days"));
+    }
+
+    #[test]
+    fn test_terminal_report_daily_forecast() {
+        let report = TerminalFormatter::new()
+            .with_units(UnitSystem::IMPERIAL)
+            .format(&create_mock_weather_data_with_daily())
+            .unwrap();
+
+        assert!(report.contains("Next days\n  Today      73°F / 57°F   Sunny"));
+        assert!(report.contains("  Tomorrow   64°F / 54°F   Moderate rain             89% 0.33 in"));
+        assert!(report.contains("89% 0.33 in  32 mph"));
+    }
+
+    #[test]
+    fn test_day_label() {
+        let today = january(13);
+        assert_eq!(format_day_label(today, today), "Today");
+        assert_eq!(format_day_label(january(14), today), "Tomorrow");
+        assert_eq!(format_day_label(january(15), today), "Sun 15");
+        let next_month = today.replace_month(time::Month::February).unwrap();
+        assert_eq!(format_day_label(next_month, today), "Mon 13");
+    }
+
+    /// Coloured wind speed in km/h with the default style
+    fn colored_kmh(wind: &WindSpeed) -> String {
+        format_wind_colored(wind, &WindStyle::default(), SpeedUnit::KilometresPerHour)
+    }
+
+    /// Compact coloured wind speed in km/h with the default style
+    fn compact_kmh(speed: u32) -> String {
+        let wind = WindSpeed::new(speed).unwrap();
+        format_wind_colored_compact(&wind, &WindStyle::default(), SpeedUnit::KilometresPerHour)
+    }
+
+    /// A day in January 2023, the month of the mock data
+    fn january(day: u8) -> time::Date {
+        time::Date::from_calendar_date(2023, time::Month::January, day).unwrap()
+    }
+
+    /// The start of an hour on a day in January 2023, in Wellington's summer time
+    fn at(day: u8, hour: u8) -> time::OffsetDateTime {
+        january(day)
+            .with_hms(hour, 0, 0)
+            .unwrap()
+            .assume_offset(time::macros::offset!(+13))
     }
 
     fn create_mock_weather_data() -> WeatherData {
         let current = CurrentWeather {
             last_updated: LastUpdated::from_epoch(1673620200).unwrap(),
-            temperature: Temperature::new(20).unwrap(),
-            feels_like: Temperature::new(22).unwrap(),
+            temperature: Temperature::new(20.0).unwrap(),
+            feels_like: Temperature::new(22.0).unwrap(),
             condition: WeatherCondition::new("Clear".to_string()),
             humidity: Humidity::new(60.0).unwrap(),
             wind_speed: WindSpeed::new(15).unwrap(),
             wind_direction: WindDirection::from_compass("NW").unwrap(),
             pressure: Pressure::new(1013).unwrap(),
+            precipitation: None,
+            uv_index: None,
+            visibility: None,
+            cloud_cover: None,
         };
 
         let location = Location::new("Wellington".to_string());
@@ -216,6 +1196,13 @@ mod tests {
             current,
             location,
             weather_day: None,
+            daily: vec![],
+            alerts: vec![],
+            air_quality: None,
+            source: "WeatherAPI.com".to_string(),
+            local_time: time::macros::datetime!(2023-01-13 14:30 +13),
+            time_zone: Some("Pacific/Auckland".to_string()),
+            stale_since: None,
         }
     }
 
@@ -239,8 +1226,8 @@ mod tests {
         let mut weather_data = create_mock_weather_data();
 
         let hourly = HourlyWeather {
-            time: WeatherTime::parse("12:00").unwrap(),
-            temperature: Temperature::new(22).unwrap(),
+            time: at(13, 12),
+            temperature: Temperature::new(22.0).unwrap(),
             condition: WeatherCondition::new("Sunny".to_string()),
             wind_speed: WindSpeed::builder()
                 .sustained(10)
@@ -248,6 +1235,9 @@ mod tests {
                 .build()
                 .unwrap(),
             wind_direction: WindDirection::from_compass("N").unwrap(),
+            precipitation: None,
+            chance_of_rain: None,
+            chance_of_snow: None,
         };
 
         weather_data.weather_day = Some(WeatherDay {
@@ -257,4 +1247,33 @@ mod tests {
 
         weather_data
     }
+
+    fn create_mock_weather_data_with_daily() -> WeatherData {
+        let mut weather_data = create_mock_weather_data();
+
+        let day = |date,
+                   (min, max): (i32, i32),
+                   condition: &str,
+                   chance_of_rain: Option<u8>,
+                   precipitation,
+                   max_wind| {
+            DailyForecast {
+                date: january(date),
+                min_temperature: Temperature::new(f64::from(min)).unwrap(),
+                max_temperature: Temperature::new(f64::from(max)).unwrap(),
+                condition: WeatherCondition::new(condition.to_string()),
+                chance_of_rain: chance_of_rain.map(|chance| Probability::new(chance).unwrap()),
+                precipitation: Precipitation::new(precipitation).unwrap(),
+                max_wind: WindSpeed::new(max_wind).unwrap(),
+            }
+        };
+
+        weather_data.daily = vec![
+            day(13, (14, 23), "Sunny", Some(0), 0.0, 25),
+            day(14, (12, 18), "Moderate rain", Some(89), 8.3, 52),
+            day(15, (13, 21), "Partly cloudy", None, 1.2, 30),
+        ];
+
+        weather_data
+    }
 }
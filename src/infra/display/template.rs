@@ -0,0 +1,427 @@
+//! A small template language for laying out the bar text and tooltip.
+//!
+//! - `{name}` inserts a value, or nothing when it is missing
+//! - `{?name}...{/name}` renders its body only when the value is present (for a list,
+//!   when the list is not empty); `{!name}...{/name}` only when it is not
+//! - `{#name}...{/name}` renders its body once per entry of a list; inside, names
+//!   refer to the entry first, then to the values outside, and `last` is present on
+//!   the final entry (e.g., `{!last}, {/last}` separates entries)
+//! - `{{` and `}}` stand for literal braces
+//!
+//! Templates are checked against a `Schema` when parsed, so a misspelt name is
+//! reported up front instead of silently rendering nothing.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Name present on the last entry of every list
+const LAST: &str = "last";
+
+/// Names a template may use: plain values, and lists with the names of their entries
+#[derive(Debug)]
+pub struct Schema {
+    pub fields: &'static [&'static str],
+    pub lists: &'static [(&'static str, &'static [&'static str])],
+}
+
+impl Schema {
+    fn list(&self, name: &str) -> Option<&'static [&'static str]> {
+        self.lists
+            .iter()
+            .find(|(list, _)| *list == name)
+            .map(|(_, fields)| *fields)
+    }
+}
+
+/// A parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value(String),
+    /// Body rendered when the name is present, or absent if `inverted`
+    Section {
+        name: String,
+        inverted: bool,
+        body: Vec<Node>,
+    },
+    Loop {
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+/// Kind of tag opening a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Opening {
+    Section,
+    InvertedSection,
+    Loop,
+}
+
+/// A block being parsed: its opening tag and position, and the nodes so far
+struct Block {
+    opening: Opening,
+    name: String,
+    position: usize,
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parse a template, checking every name against `schema`
+    pub fn parse(source: &str, schema: &Schema) -> Result<Self> {
+        let mut blocks = vec![Block {
+            opening: Opening::Section,
+            name: String::new(),
+            position: 0,
+            nodes: Vec::new(),
+        }];
+        let mut text = String::new();
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, next)| next == '{').is_some() => text.push('{'),
+                '}' if chars.next_if(|&(_, next)| next == '}').is_some() => text.push('}'),
+                '}' => anyhow::bail!(
+                    "Unmatched '}}' at {} (write '}}}}' for a literal brace)",
+                    location(source, position)
+                ),
+                '{' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => tag.push(c),
+                            None => anyhow::bail!(
+                                "Unclosed '{{' at {} (write '{{{{' for a literal brace)",
+                                location(source, position)
+                            ),
+                        }
+                    }
+                    if !text.is_empty() {
+                        let nodes = &mut blocks.last_mut().expect("root block").nodes;
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    Self::parse_tag(&tag, position, source, schema, &mut blocks)?;
+                }
+                c => text.push(c),
+            }
+        }
+
+        if blocks.len() > 1 {
+            let block = blocks.last().expect("unclosed block");
+            anyhow::bail!(
+                "'{}' at {} is never closed with {{/{}}}",
+                block.tag(),
+                location(source, block.position),
+                block.name
+            );
+        }
+        let mut nodes = blocks.pop().expect("root block").nodes;
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Handle the tag `{<tag>}` found at byte `position`
+    fn parse_tag(
+        tag: &str,
+        position: usize,
+        source: &str,
+        schema: &Schema,
+        blocks: &mut Vec<Block>,
+    ) -> Result<()> {
+        let (sigil, name) = match tag.chars().next() {
+            Some(sigil @ ('?' | '!' | '#' | '/')) => (Some(sigil), &tag[1..]),
+            _ => (None, tag),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            anyhow::bail!(
+                "Invalid placeholder '{{{}}}' at {}",
+                tag,
+                location(source, position)
+            );
+        }
+
+        // Lists whose entries are in scope, innermost last
+        let lists = blocks
+            .iter()
+            .filter(|block| block.opening == Opening::Loop)
+            .filter_map(|block| schema.list(&block.name))
+            .collect::<Vec<_>>();
+        let is_field = schema.fields.contains(&name)
+            || lists
+                .iter()
+                .any(|fields| name == LAST || fields.contains(&name));
+        let is_list = schema.list(name).is_some();
+
+        let opening = match sigil {
+            Some('/') => {
+                let block = blocks.pop().filter(|_| !blocks.is_empty());
+                return match block {
+                    Some(block) if block.name == name => {
+                        let node = match block.opening {
+                            Opening::Loop => Node::Loop {
+                                name: block.name,
+                                body: block.nodes,
+                            },
+                            opening => Node::Section {
+                                name: block.name,
+                                inverted: opening == Opening::InvertedSection,
+                                body: block.nodes,
+                            },
+                        };
+                        blocks.last_mut().expect("root block").nodes.push(node);
+                        Ok(())
+                    }
+                    Some(block) => anyhow::bail!(
+                        "'{{/{}}}' at {} does not match '{}' at {}",
+                        name,
+                        location(source, position),
+                        block.tag(),
+                        location(source, block.position)
+                    ),
+                    None => anyhow::bail!(
+                        "'{{/{}}}' at {} closes nothing",
+                        name,
+                        location(source, position)
+                    ),
+                };
+            }
+            Some('#') if is_list => Opening::Loop,
+            Some('#') => anyhow::bail!(
+                "'{{#{}}}' at {} is not a list",
+                name,
+                location(source, position)
+            ),
+            Some(sigil) if is_field || is_list => {
+                if sigil == '?' {
+                    Opening::Section
+                } else {
+                    Opening::InvertedSection
+                }
+            }
+            None if is_field => {
+                let nodes = &mut blocks.last_mut().expect("root block").nodes;
+                nodes.push(Node::Value(name.to_string()));
+                return Ok(());
+            }
+            None if is_list => anyhow::bail!(
+                "'{{{}}}' at {} is a list; repeat a section for each entry with {{#{}}}...{{/{}}}",
+                name,
+                location(source, position),
+                name,
+                name
+            ),
+            _ => anyhow::bail!(
+                "Unknown placeholder '{{{}}}' at {}",
+                tag,
+                location(source, position)
+            ),
+        };
+
+        blocks.push(Block {
+            opening,
+            name: name.to_string(),
+            position,
+            nodes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Render the template with the given values
+    pub fn render(&self, values: &Values) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, &[values], &mut output);
+        output
+    }
+}
+
+impl Block {
+    /// The tag that opened this block, e.g. "{?uv}"
+    fn tag(&self) -> String {
+        let sigil = match self.opening {
+            Opening::Section => '?',
+            Opening::InvertedSection => '!',
+            Opening::Loop => '#',
+        };
+        format!("{{{}{}}}", sigil, self.name)
+    }
+}
+
+/// Describe a byte position as "line L, column C"
+fn location(source: &str, position: usize) -> String {
+    let before = &source[..position];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    format!("line {}, column {}", line, column)
+}
+
+fn render_nodes(nodes: &[Node], scopes: &[&Values], output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value(name) => {
+                if let Some(value) = lookup_value(scopes, name) {
+                    output.push_str(value);
+                }
+            }
+            Node::Section {
+                name,
+                inverted,
+                body,
+            } => {
+                let present = lookup_value(scopes, name).is_some_and(|value| !value.is_empty())
+                    || lookup_list(scopes, name).is_some_and(|list| !list.is_empty());
+                if present != *inverted {
+                    render_nodes(body, scopes, output);
+                }
+            }
+            Node::Loop { name, body } => {
+                for entry in lookup_list(scopes, name).unwrap_or_default() {
+                    let mut inner = scopes.to_vec();
+                    inner.push(entry);
+                    render_nodes(body, &inner, output);
+                }
+            }
+        }
+    }
+}
+
+/// Find a value, looking in the innermost scope first
+fn lookup_value<'a>(scopes: &[&'a Values], name: &str) -> Option<&'a str> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|values| values.fields.get(name))
+        .map(String::as_str)
+}
+
+fn lookup_list<'a>(scopes: &[&'a Values], name: &str) -> Option<&'a [Values]> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|values| values.lists.get(name))
+        .map(Vec::as_slice)
+}
+
+/// Values to render a template with
+#[derive(Debug, Default)]
+pub struct Values {
+    fields: HashMap<&'static str, String>,
+    lists: HashMap<&'static str, Vec<Values>>,
+}
+
+impl Values {
+    /// Set a value
+    pub fn set(&mut self, name: &'static str, value: impl Into<String>) {
+        self.fields.insert(name, value.into());
+    }
+
+    /// Set a value if there is one; otherwise it stays missing
+    pub fn set_optional(&mut self, name: &'static str, value: Option<String>) {
+        if let Some(value) = value {
+            self.set(name, value);
+        }
+    }
+
+    /// Set a list, marking its final entry as `last`
+    pub fn set_list(&mut self, name: &'static str, mut entries: Vec<Values>) {
+        if let Some(entry) = entries.last_mut() {
+            entry.set(LAST, "true");
+        }
+        self.lists.insert(name, entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: Schema = Schema {
+        fields: &["temp", "location", "uv"],
+        lists: &[("hours", &["time", "temp"])],
+    };
+
+    fn values() -> Values {
+        let mut values = Values::default();
+        values.set("temp", "20°C");
+        values.set("location", "Wellington");
+        let hour = |time: &str, temp: &str| {
+            let mut entry = Values::default();
+            entry.set("time", time);
+            entry.set("temp", temp);
+            entry
+        };
+        values.set_list("hours", vec![hour("13:00", "21°C"), hour("14:00", "22°C")]);
+        values
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source, &SCHEMA).unwrap().render(&values())
+    }
+
+    #[test]
+    fn test_values_and_literal_braces() {
+        assert_eq!(render("{temp} in {location}"), "20°C in Wellington");
+        assert_eq!(render("{{temp}} }}"), "{temp} }");
+        assert_eq!(render("UV {uv}."), "UV .");
+    }
+
+    #[test]
+    fn test_sections() {
+        assert_eq!(render("{?uv}UV {uv}{/uv}{!uv}no UV{/uv}"), "no UV");
+        assert_eq!(render("{?temp}{temp}{/temp}"), "20°C");
+        assert_eq!(render("{?hours}Hours:{/hours}"), "Hours:");
+    }
+
+    #[test]
+    fn test_loops() {
+        assert_eq!(
+            render("{#hours}{time} {temp} ({location}){!last}, {/last}{/hours}"),
+            "13:00 21°C (Wellington), 14:00 22°C (Wellington)"
+        );
+
+        let mut values = Values::default();
+        values.set_list("hours", Vec::new());
+        let template = Template::parse("{?hours}x{/hours}{#hours}y{/hours}", &SCHEMA).unwrap();
+        assert_eq!(template.render(&values), "");
+    }
+
+    #[test]
+    fn test_errors_name_the_position() {
+        let error = |source: &str| Template::parse(source, &SCHEMA).unwrap_err().to_string();
+
+        assert_eq!(
+            error("{temp}\n {tmp}"),
+            "Unknown placeholder '{tmp}' at line 2, column 2"
+        );
+        assert_eq!(
+            error("{?uv}UV"),
+            "'{?uv}' at line 1, column 1 is never closed with {/uv}"
+        );
+        assert_eq!(
+            error("{#hours}{?uv}{/hours}"),
+            "'{/hours}' at line 1, column 14 does not match '{?uv}' at line 1, column 9"
+        );
+        assert!(error("{/uv}").contains("closes nothing"));
+        assert!(error("{temp").starts_with("Unclosed '{'"));
+        assert!(error("temp}").starts_with("Unmatched '}'"));
+        assert!(error("{#temp}{/temp}").contains("is not a list"));
+        assert!(error("{hours}").contains("is a list"));
+        assert!(error("{Temp}").starts_with("Invalid placeholder"));
+        // Entry names are only known inside their loop
+        assert!(error("{time}").starts_with("Unknown placeholder"));
+        assert!(error("{last}").starts_with("Unknown placeholder"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Plain-text report of weather data for reading in a terminal (the `show` command).
+
+use super::formatting::{
+    format_air_quality, format_alert, format_alert_details, format_day_label, format_pollutants,
+    format_precipitation, format_precipitation_outlook, format_pressure, format_temperature,
+    format_visibility, format_wind, Clock, ClockZone, Icons,
+};
+use super::waybar::DEFAULT_HOURS;
+use crate::app::WeatherFormatter;
+use crate::domain::models::{DailyForecast, HourlyWeather, LocationWeather, WeatherData};
+use crate::domain::{MoonPhase, UnitSystem};
+
+use anyhow::Result;
+
+/// Formatter producing a multi-line, markup-free report
+pub struct TerminalFormatter {
+    hours: usize,
+    units: UnitSystem,
+    icons: Icons,
+    clock_zone: ClockZone,
+}
+
+impl TerminalFormatter {
+    /// Create a terminal formatter listing the default number of upcoming hours
+    pub fn new() -> Self {
+        Self {
+            hours: DEFAULT_HOURS,
+            units: UnitSystem::default(),
+            icons: Icons::default(),
+            clock_zone: ClockZone::default(),
+        }
+    }
+
+    /// Set how many upcoming hours the report lists
+    pub fn with_hours(mut self, hours: usize) -> Self {
+        self.hours = hours;
+        self
+    }
+
+    /// Set the units values are shown in
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Set the icons for conditions, moon phases and alerts
+    pub fn with_icons(mut self, icons: Icons) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Show times in the location's or the system's time zone
+    pub fn with_clock_zone(mut self, zone: ClockZone) -> Self {
+        self.clock_zone = zone;
+        self
+    }
+
+    /// Format the current conditions block
+    fn format_current(&self, weather_data: &WeatherData) -> String {
+        let current = &weather_data.current;
+        let temperature = |t| format_temperature(t, self.units.temperature);
+        let mut block = format!(
+            "{} {}, {}\n\
+             \n\
+             Temperature  {} (feels like {})\n\
+             Humidity     {} (dew point {})\n\
+             Wind         {} {}\n\
+             Pressure     {}",
+            self.icons.condition(&current.condition),
+            weather_data.location.full_name(),
+            current.condition,
+            temperature(&current.temperature),
+            temperature(&current.feels_like),
+            current.humidity,
+            temperature(&current.humidity.dew_point(&current.temperature)),
+            format_wind(&current.wind_speed, self.units.speed),
+            current.wind_direction,
+            format_pressure(&current.pressure, self.units.pressure)
+        );
+        if let Some(precipitation) = &current.precipitation {
+            block.push_str(&format!(
+                "\nRain         {}",
+                format_precipitation(precipitation, self.units.precipitation)
+            ));
+        }
+        if let Some(uv) = &current.uv_index {
+            block.push_str(&format!("\nUV index     {}", uv));
+        }
+        if let Some(visibility) = &current.visibility {
+            block.push_str(&format!(
+                "\nVisibility   {}",
+                format_visibility(visibility, self.units.distance)
+            ));
+        }
+        if let Some(cover) = &current.cloud_cover {
+            block.push_str(&format!("\nCloud cover  {}", cover));
+        }
+        if let Some(air_quality) = &weather_data.air_quality {
+            if let Some(summary) = format_air_quality(air_quality) {
+                block.push_str(&format!("\nAir quality  {}", summary));
+            }
+            if let Some(pollutants) = format_pollutants(air_quality) {
+                block.push_str(&format!("\n             {}", pollutants));
+            }
+        }
+        block
+    }
+
+    /// Format sunrise and sunset, if known, and the phase of the moon
+    fn format_astronomy(&self, weather_data: &WeatherData, clock: &Clock) -> String {
+        let phase = MoonPhase::on(weather_data.local_time.date());
+        let moon = format!("Moon         {} {}", self.icons.moon(phase), phase);
+        match weather_data
+            .weather_day
+            .as_ref()
+            .and_then(|day| day.astronomy.as_ref())
+        {
+            Some(astronomy) => format!(
+                "Sunrise      {}\n\
+                 Sunset       {} (day length {})\n\
+                 {}",
+                clock.time_of_day(astronomy.sunrise()),
+                clock.time_of_day(astronomy.sunset()),
+                astronomy.day_length(),
+                moon
+            ),
+            None => moon,
+        }
+    }
+
+    /// Format the upcoming hours as a table
+    fn format_hourly(&self, weather_data: &WeatherData, clock: &Clock) -> Option<String> {
+        let day = weather_data.weather_day.as_ref()?;
+        if day.hourly_weather.is_empty() || self.hours == 0 {
+            return None;
+        }
+
+        let rows = day
+            .hourly_weather
+            .iter()
+            .take(self.hours)
+            .map(|hourly| self.format_hourly_row(hourly, clock))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!("Upcoming hours ({})\n{}", clock.label(), rows))
+    }
+
+    /// Format a single row of the upcoming hours table
+    fn format_hourly_row(&self, hourly: &HourlyWeather, clock: &Clock) -> String {
+        let mut row = format!(
+            "  {:>9}  {:>5}  {:<24} {} {}",
+            clock.hour(hourly.time),
+            format_temperature(&hourly.temperature, self.units.temperature),
+            hourly.condition.to_string(),
+            format_wind(&hourly.wind_speed, self.units.speed),
+            hourly.wind_direction
+        );
+        if let Some(outlook) =
+            format_precipitation_outlook(hourly, self.units.precipitation, &self.icons)
+        {
+            row.push_str(&format!("  {}", outlook));
+        }
+        row
+    }
+
+    /// Format the multi-day forecast as a table
+    fn format_daily(&self, weather_data: &WeatherData) -> Option<String> {
+        if weather_data.daily.is_empty() {
+            return None;
+        }
+
+        let today = weather_data.local_time.date();
+        let rows = weather_data
+            .daily
+            .iter()
+            .map(|day| self.format_daily_row(day, today))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!("Next days\n{}", rows))
+    }
+
+    /// Format a single row of the next days table
+    fn format_daily_row(&self, day: &DailyForecast, today: time::Date) -> String {
+        let temperature = |t| format_temperature(t, self.units.temperature);
+        let chance = day
+            .chance_of_rain
+            .map(|chance| chance.to_string())
+            .unwrap_or_default();
+        format!(
+            "  {:<8}  {:>5} / {:<5}  {:<24} {:>4} {:>7}  {}",
+            format_day_label(day.date, today),
+            temperature(&day.max_temperature),
+            temperature(&day.min_temperature),
+            day.condition.to_string(),
+            chance,
+            format_precipitation(&day.precipitation, self.units.precipitation),
+            format_wind(&day.max_wind, self.units.speed)
+        )
+    }
+
+    /// Format every location's current conditions as a table, marking the one reported
+    fn format_summary(&self, summary: &[LocationWeather]) -> String {
+        let rows = summary
+            .iter()
+            .map(|entry| {
+                let marker = if entry.shown { "▸" } else { " " };
+                match &entry.weather {
+                    Some(weather) => {
+                        let current = &weather.current;
+                        let mut row = format!(
+                            "{} {:<24} {:>5}  {}",
+                            marker,
+                            entry.name(),
+                            format_temperature(&current.temperature, self.units.temperature),
+                            current.condition
+                        );
+                        if weather.stale_since.is_some() {
+                            row.push_str(" (stale)");
+                        }
+                        row
+                    }
+                    None => format!("{} {:<24} unavailable", marker, entry.name()),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("Locations\n{}", rows)
+    }
+}
+
+impl WeatherFormatter for TerminalFormatter {
+    type Output = String;
+
+    fn format(&self, data: &WeatherData) -> Result<String> {
+        let clock = Clock::new(self.clock_zone, data);
+        let alerts = data
+            .alerts
+            .iter()
+            .map(|alert| match format_alert_details(alert, &clock) {
+                Some(details) => {
+                    format!("{}\n   {}", format_alert(alert, &self.icons), details)
+                }
+                None => format_alert(alert, &self.icons),
+            })
+            .collect::<Vec<_>>();
+
+        let mut sections = Vec::new();
+        if !alerts.is_empty() {
+            sections.push(alerts.join("\n"));
+        }
+        sections.push(self.format_current(data));
+        sections.push(self.format_astronomy(data, &clock));
+        sections.extend(self.format_hourly(data, &clock));
+        sections.extend(self.format_daily(data));
+
+        let mut footer = format!(
+            "Updated {} from {}",
+            clock.date_time(data.current.last_updated.instant()),
+            data.source
+        );
+        if let Some(since) = data.stale_since {
+            footer.push_str(&format!(
+                "\nStale since {} (latest fetch failed)",
+                clock.hour(since)
+            ));
+        }
+        sections.push(footer);
+
+        Ok(sections.join("\n\n"))
+    }
+
+    fn format_with_summary(
+        &self,
+        data: &WeatherData,
+        summary: &[LocationWeather],
+    ) -> Result<String> {
+        let report = self.format(data)?;
+        Ok(format!("{}\n\n{}", report, self.format_summary(summary)))
+    }
+}
+
+impl Default for TerminalFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
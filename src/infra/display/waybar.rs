@@ -1,150 +1,950 @@
 //! Waybar output formatter for weather data with functional composition.
 
-use super::formatting::{condition_icon, format_wind_colored, format_wind_colored_compact};
+use super::formatting::{
+    format_air_quality_colored, format_alert_details, format_day_label, format_pollutants,
+    format_precipitation, format_precipitation_outlook, format_pressure, format_system_date_time,
+    format_temperature, format_uv_colored, format_uv_colored_compact, format_visibility,
+    format_wind_colored, format_wind_colored_compact, Clock, ClockZone, Icons, LineIcon, WindStyle,
+};
+use super::template::{Schema, Template, Values};
 use crate::app::WeatherFormatter;
-use crate::domain::models::WeatherData;
+use crate::domain::models::{DailyForecast, LocationWeather, WeatherData};
+use crate::domain::{
+    ConditionFamily, MoonPhase, Precipitation, SunEvent, Temperature, UnitSystem, WeatherTime,
+    WindSpeedCategory,
+};
 
 use anyhow::Result;
 
 use serde::Serialize;
+use std::str::FromStr;
 
 /// Waybar JSON output format
 #[derive(Debug, Serialize)]
 pub struct WaybarOutput {
     pub text: String,
     pub tooltip: String,
+    /// CSS classes Waybar adds to the module, e.g. "rain", "night", "stale" or "alert"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub class: Vec<String>,
+    /// Stable condition key (e.g., "partly-cloudy-night") for Waybar's `format-icons`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub alt: String,
+    /// Value from 0 to 100 Waybar uses to pick from `format-icons` lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<u8>,
 }
 
+/// What the bar text shows; cycled with a signal in daemon mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    /// Current conditions
+    #[default]
+    Current,
+    /// Forecast for the coming hour
+    NextHour,
+    /// Highest and lowest temperatures for the rest of today
+    HighLow,
+    /// Time until the next sunrise or sunset
+    SunCountdown,
+}
+
+impl DisplayMode {
+    /// The mode after this one, wrapping around to `Current`
+    pub fn next(self) -> Self {
+        match self {
+            Self::Current => Self::NextHour,
+            Self::NextHour => Self::HighLow,
+            Self::HighLow => Self::SunCountdown,
+            Self::SunCountdown => Self::Current,
+        }
+    }
+}
+
+/// Optional current conditions appended to the bar text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarField {
+    /// UV index, coloured by WHO category
+    Uv,
+    /// Horizontal visibility
+    Visibility,
+    /// Cloud cover percentage
+    CloudCover,
+}
+
+impl FromStr for BarField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "uv" => Ok(Self::Uv),
+            "visibility" => Ok(Self::Visibility),
+            "cloud" | "clouds" | "cloud_cover" => Ok(Self::CloudCover),
+            other => anyhow::bail!(
+                "Unknown bar field '{}'. Expected one of: uv, visibility, cloud",
+                other
+            ),
+        }
+    }
+}
+
+/// What the `percentage` field reports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PercentageSource {
+    /// Relative humidity
+    Humidity,
+    /// Chance of rain in the hour in progress
+    ChanceOfRain,
+    /// Where the temperature sits in a comfort range given in °C: 0 at `min` or below,
+    /// 100 at `max` or above
+    Temperature { min: f64, max: f64 },
+}
+
+/// Comfort range used for the temperature percentage when none is configured, in °C
+pub const DEFAULT_COMFORT_RANGE: (f64, f64) = (0.0, 30.0);
+
+impl PercentageSource {
+    /// The percentage for the given weather, if the provider reported what is needed
+    fn percentage(self, weather_data: &WeatherData) -> Option<u8> {
+        match self {
+            Self::Humidity => Some(weather_data.current.humidity.as_int() as u8),
+            Self::ChanceOfRain => weather_data
+                .weather_day
+                .as_ref()?
+                .hourly_weather
+                .first()?
+                .chance_of_rain
+                .map(|chance| chance.as_percent()),
+            Self::Temperature { min, max } => {
+                let celsius = weather_data.current.temperature.value();
+                let position = (celsius - min) / (max - min) * 100.0;
+                Some(position.round().clamp(0.0, 100.0) as u8)
+            }
+        }
+    }
+}
+
+impl FromStr for PercentageSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "humidity" => Ok(Self::Humidity),
+            "rain" | "chance_of_rain" => Ok(Self::ChanceOfRain),
+            "temperature" => Ok(Self::Temperature {
+                min: DEFAULT_COMFORT_RANGE.0,
+                max: DEFAULT_COMFORT_RANGE.1,
+            }),
+            other => anyhow::bail!(
+                "Unknown percentage '{}'. Expected one of: humidity, rain, temperature",
+                other
+            ),
+        }
+    }
+}
+
+/// Number of upcoming hours listed in the tooltip by default
+pub const DEFAULT_HOURS: usize = 12;
+
+/// Names the bar text and tooltip templates can use
+pub const TEMPLATE_SCHEMA: Schema = Schema {
+    fields: &[
+        "location",
+        "region",
+        "country",
+        "icon",
+        "condition",
+        "temp",
+        "feels_like",
+        "humidity",
+        "dew_point",
+        "wind",
+        "wind_short",
+        "wind_dir",
+        "pressure",
+        "precipitation",
+        "uv",
+        "visibility",
+        "cloud_cover",
+        "air_quality",
+        "pollutants",
+        "sunrise",
+        "solar_noon",
+        "sunset",
+        "day_length",
+        "updated",
+        "source",
+        "stale_since",
+        "zone",
+        "moon",
+        "moon_icon",
+        "location_icon",
+        "temperature_icon",
+        "condition_icon",
+        "feels_like_icon",
+        "humidity_icon",
+        "wind_icon",
+        "pressure_icon",
+        "precipitation_icon",
+        "uv_icon",
+        "visibility_icon",
+        "cloud_cover_icon",
+        "air_quality_icon",
+        "sunrise_icon",
+        "solar_noon_icon",
+        "sunset_icon",
+        "day_length_icon",
+        "hours_icon",
+        "days_icon",
+        "updated_icon",
+        "source_icon",
+        "stale_icon",
+        "alert_icon",
+    ],
+    lists: &[
+        (
+            "alerts",
+            &["event", "severity", "urgency", "headline", "details"],
+        ),
+        (
+            "hours",
+            &[
+                "time",
+                "icon",
+                "temp",
+                "condition",
+                "outlook",
+                "wind",
+                "wind_short",
+                "wind_dir",
+            ],
+        ),
+        (
+            "days",
+            &[
+                "day",
+                "icon",
+                "high",
+                "low",
+                "condition",
+                "rain",
+                "wind_short",
+            ],
+        ),
+    ],
+};
+
+/// Template fields holding the icons that start tooltip lines
+const LINE_ICON_FIELDS: [(LineIcon, &str); 22] = [
+    (LineIcon::Location, "location_icon"),
+    (LineIcon::Temperature, "temperature_icon"),
+    (LineIcon::Condition, "condition_icon"),
+    (LineIcon::FeelsLike, "feels_like_icon"),
+    (LineIcon::Humidity, "humidity_icon"),
+    (LineIcon::Wind, "wind_icon"),
+    (LineIcon::Pressure, "pressure_icon"),
+    (LineIcon::Precipitation, "precipitation_icon"),
+    (LineIcon::Uv, "uv_icon"),
+    (LineIcon::Visibility, "visibility_icon"),
+    (LineIcon::CloudCover, "cloud_cover_icon"),
+    (LineIcon::AirQuality, "air_quality_icon"),
+    (LineIcon::Sunrise, "sunrise_icon"),
+    (LineIcon::SolarNoon, "solar_noon_icon"),
+    (LineIcon::Sunset, "sunset_icon"),
+    (LineIcon::DayLength, "day_length_icon"),
+    (LineIcon::Hours, "hours_icon"),
+    (LineIcon::Days, "days_icon"),
+    (LineIcon::Updated, "updated_icon"),
+    (LineIcon::Source, "source_icon"),
+    (LineIcon::Stale, "stale_icon"),
+    (LineIcon::Alert, "alert_icon"),
+];
+
+/// Bar text shown for current conditions unless configured otherwise
+pub const DEFAULT_TEXT_TEMPLATE: &str = "{icon} {temp}/ {wind_short} {location}";
+
+/// Tooltip shown unless configured otherwise
+pub const DEFAULT_TOOLTIP_TEMPLATE: &str = "\
+{#alerts}{alert_icon}{event} ({severity}): {headline}{?details}
+          {details}{/details}
+{?last}
+{/last}{/alerts}\
+{location_icon}Location: {location}{?region}, {region}{/region}{?country}, {country}{/country}
+{temperature_icon}Temperature: {temp}
+{condition_icon}Condition: {condition}
+{feels_like_icon}Feels like: {feels_like}
+{humidity_icon}Humidity: {humidity} (Dew Point: {dew_point})
+{wind_icon}Wind: {wind} {wind_dir}
+{pressure_icon}Pressure: {pressure}\
+{?precipitation}
+{precipitation_icon}Precipitation: {precipitation}{/precipitation}\
+{?uv}
+{uv_icon}UV Index: {uv}{/uv}\
+{?visibility}
+{visibility_icon}Visibility: {visibility}{/visibility}\
+{?cloud_cover}
+{cloud_cover_icon}Cloud Cover: {cloud_cover}{/cloud_cover}\
+{?air_quality}
+{air_quality_icon}Air Quality: {air_quality}{?pollutants}
+          {pollutants}{/pollutants}{/air_quality}\
+{?sunrise}
+{sunrise_icon}Sunrise: {sunrise}
+{solar_noon_icon}Solar Noon: {solar_noon}
+{sunset_icon}Sunset: {sunset}
+{day_length_icon}Daylength: {day_length}{/sunrise}
+{moon_icon}Moon: {moon}\
+{?hours}
+
+{hours_icon}Upcoming Hours ({zone}):
+{#hours}• {time} - {temp} {condition}{?outlook} {outlook}{/outlook}
+          Wind: {wind} {wind_dir}{!last}
+{/last}{/hours}{/hours}\
+{?days}
+
+{days_icon}Next Days:
+{#days}• {day} {icon} ↑{high} ↓{low} {condition}
+          {precipitation_icon}{rain}, {wind_icon}{wind_short}{!last}
+{/last}{/days}{/days}
+
+{updated_icon}Updated: {updated}
+{source_icon}Source: {source}\
+{?stale_since}
+{stale_icon}Stale since {stale_since} (latest fetch failed){/stale_since}";
+
 /// Formatter for creating Waybar JSON output from weather data
-pub struct WaybarFormatter;
+pub struct WaybarFormatter {
+    mode: DisplayMode,
+    hours: usize,
+    wind_style: WindStyle,
+    units: UnitSystem,
+    umbrella_hours: u32,
+    aqi_threshold: u8,
+    bar_fields: Vec<BarField>,
+    percentage: Option<PercentageSource>,
+    text_template: Template,
+    tooltip_template: Template,
+    icons: Icons,
+    clock_zone: ClockZone,
+}
 
 impl WaybarFormatter {
-    /// Create a new Waybar formatter
+    /// Create a new Waybar formatter showing current conditions
     pub fn new() -> Self {
-        Self
+        Self {
+            mode: DisplayMode::default(),
+            hours: DEFAULT_HOURS,
+            wind_style: WindStyle::default(),
+            units: UnitSystem::default(),
+            umbrella_hours: 0,
+            aqi_threshold: 0,
+            bar_fields: Vec::new(),
+            percentage: None,
+            text_template: Template::parse(DEFAULT_TEXT_TEMPLATE, &TEMPLATE_SCHEMA)
+                .expect("default text template is valid"),
+            tooltip_template: Template::parse(DEFAULT_TOOLTIP_TEMPLATE, &TEMPLATE_SCHEMA)
+                .expect("default tooltip template is valid"),
+            icons: Icons::default(),
+            clock_zone: ClockZone::default(),
+        }
+    }
+
+    /// Set how many upcoming hours the tooltip lists
+    pub fn with_hours(mut self, hours: usize) -> Self {
+        self.hours = hours;
+        self
+    }
+
+    /// Set how wind speeds are categorised and coloured
+    pub fn with_wind_style(mut self, wind_style: WindStyle) -> Self {
+        self.wind_style = wind_style;
+        self
+    }
+
+    /// Set the units values are shown in
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Badge the bar text with an umbrella when rain is likely within this many hours;
+    /// zero disables the badge
+    pub fn with_umbrella_hours(mut self, hours: u32) -> Self {
+        self.umbrella_hours = hours;
+        self
+    }
+
+    /// Badge the bar text when the US EPA air quality index (1 to 6) exceeds this level;
+    /// zero disables the badge
+    pub fn with_aqi_threshold(mut self, level: u8) -> Self {
+        self.aqi_threshold = level;
+        self
+    }
+
+    /// Append these current conditions, when reported, to the bar text
+    pub fn with_bar_fields(mut self, fields: Vec<BarField>) -> Self {
+        self.bar_fields = fields;
+        self
+    }
+
+    /// Set what the `percentage` field reports; `None` leaves it out
+    pub fn with_percentage(mut self, percentage: Option<PercentageSource>) -> Self {
+        self.percentage = percentage;
+        self
+    }
+
+    /// Lay out the bar text for current conditions with this template; `None` keeps
+    /// the default
+    pub fn with_text_template(mut self, template: Option<Template>) -> Self {
+        if let Some(template) = template {
+            self.text_template = template;
+        }
+        self
+    }
+
+    /// Lay out the tooltip with this template; `None` keeps the default
+    pub fn with_tooltip_template(mut self, template: Option<Template>) -> Self {
+        if let Some(template) = template {
+            self.tooltip_template = template;
+        }
+        self
+    }
+
+    /// Set the icons for conditions, moon phases, tooltip lines and badges
+    pub fn with_icons(mut self, icons: Icons) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Show times in the location's or the system's time zone
+    pub fn with_clock_zone(mut self, zone: ClockZone) -> Self {
+        self.clock_zone = zone;
+        self
+    }
+
+    /// Switch the bar text to the next display mode
+    pub fn cycle_mode(&mut self) {
+        self.mode = self.mode.next();
     }
 
     /// Create error output for display when weather data is unavailable
-    pub fn create_error_output(location: &str, error: anyhow::Error) -> WaybarOutput {
+    ///
+    /// `location` is the place whose fetch failed, or `None` when the failure came
+    /// before fetching (e.g., an invalid configuration). `service` names the provider
+    /// (or fallback chain) that was tried or would have been. Without weather there is
+    /// no location zone, so the time of the attempt is on the system's clocks whichever
+    /// zone is configured.
+    pub fn create_error_output(
+        location: Option<&str>,
+        service: &str,
+        error: anyhow::Error,
+    ) -> WaybarOutput {
         let text = "🌤️ -- Weather unavailable".to_string();
+        let heading = match location {
+            Some(location) => format!(
+                "Unable to fetch weather data for {}",
+                escape_markup(location)
+            ),
+            None => "Unable to show the weather".to_string(),
+        };
+        // The alternate form lists every context, e.g. why a profile is invalid. It is
+        // escaped since it can quote a request URL, whose query joins fields with '&'
         let tooltip = format!(
-            "Unable to fetch weather data for {}\n\
+            "{}\n\
              \n\
              Error: {}\n\
-             Service: WeatherAPI.com\n\
+             Service: {}\n\
              \n\
              Last attempt: {}",
-            location,
-            error,
-            time::OffsetDateTime::now_utc()
-                .format(&time::macros::format_description!(
-                    "[year]-[month]-[day] [hour]:[minute]Z"
-                ))
-                .unwrap_or_else(|_| "Unknown".to_string())
+            heading,
+            escape_markup(&format!("{:#}", error)),
+            escape_markup(service),
+            format_system_date_time(time::OffsetDateTime::now_utc())
         );
 
-        WaybarOutput { text, tooltip }
+        WaybarOutput {
+            text,
+            tooltip,
+            class: vec!["error".to_string()],
+            alt: "error".to_string(),
+            percentage: None,
+        }
     }
 
-    /// Format the main display text (icon + temperature + wind speed + location)
-    fn format_display_text(&self, weather_data: &WeatherData) -> String {
-        format!(
-            "{} {}/ {} {}",
-            condition_icon(&weather_data.current.condition),
-            weather_data.current.temperature,
-            format_wind_colored_compact(&weather_data.current.wind_speed),
-            weather_data.location
-        )
+    /// Stable key for the current condition, with "-day" or "-night" appended when
+    /// sunrise and sunset are known (e.g., "clear-night")
+    fn condition_key(weather_data: &WeatherData) -> String {
+        let family = condition_family_key(weather_data.current.condition.family());
+        match weather_data.is_daytime() {
+            Some(true) => format!("{}-day", family),
+            Some(false) => format!("{}-night", family),
+            None => family.to_string(),
+        }
     }
 
-    /// Format the detailed tooltip information
-    fn format_tooltip(&self, weather_data: &WeatherData) -> Result<String> {
-        let dew_point = weather_data
-            .current
-            .humidity
-            .dew_point(&weather_data.current.temperature);
-
-        let basic_info = format!(
-            "📍 Location: {}\n\
-             🌡️ Temperature: {}\n\
-             🌤️ Condition: {}\n\
-             🤚 Feels like: {}\n\
-             💧 Humidity: {} (Dew Point: {})\n\
-             💨 Wind: {} {}\n\
-             📊 Pressure: {}",
-            weather_data.location,
-            weather_data.current.temperature,
-            weather_data.current.condition,
-            weather_data.current.feels_like,
-            weather_data.current.humidity,
-            dew_point,
-            format_wind_colored(&weather_data.current.wind_speed),
-            weather_data.current.wind_direction,
-            weather_data.current.pressure
-        );
-
-        let astronomy_info = weather_data
-            .weather_day
-            .as_ref()
-            .and_then(|day| day.astronomy.as_ref())
-            .map(|ast| {
-                format!(
-                    "\n🌅 Sunrise: {}\n\
-                     🌞 Solar Noon: {}\n\
-                     🌇 Sunset: {}\n\
-                     ⏳ Daylength: {}",
-                    ast.sunrise(),
-                    ast.solar_noon().unwrap_or_else(|_| ast.sunrise()), // fallback to sunrise if solar noon calculation fails
-                    ast.sunset(),
-                    ast.day_length()
-                )
-            })
-            .unwrap_or_default();
+    /// CSS classes describing the weather: condition family, "day" or "night", wind
+    /// category, "stale" for data shown after a failed fetch, and any alert classes
+    fn css_classes(&self, weather_data: &WeatherData) -> Vec<String> {
+        let family = weather_data.current.condition.family();
+        let mut classes = vec![condition_family_key(family).to_string()];
+        match weather_data.is_daytime() {
+            Some(true) => classes.push("day".to_string()),
+            Some(false) => classes.push("night".to_string()),
+            None => {}
+        }
+        let wind = self
+            .wind_style
+            .thresholds
+            .categorize(weather_data.current.wind_speed.sustained_value());
+        classes.push(format!("wind-{}", wind_category_key(wind)));
+        if weather_data.stale_since.is_some() {
+            classes.push("stale".to_string());
+        }
+        classes.extend(Self::alert_classes(weather_data));
+        classes
+    }
 
-        let hourly_forecast = weather_data
-            .weather_day
+    /// CSS classes for the alerts in force: "alert" plus one for the most severe, e.g.
+    /// "alert-severe"
+    fn alert_classes(weather_data: &WeatherData) -> Vec<String> {
+        match weather_data.alerts.iter().map(|alert| alert.severity).max() {
+            Some(severity) => vec![
+                "alert".to_string(),
+                format!("alert-{}", severity.to_string().to_lowercase()),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// Format the main display text for the current display mode.
+    ///
+    /// Modes whose data is missing (e.g., no astronomy) fall back to current conditions.
+    fn format_display_text(&self, weather_data: &WeatherData, values: &Values) -> String {
+        let text = match self.mode {
+            DisplayMode::Current => None,
+            DisplayMode::NextHour => self.format_next_hour_text(weather_data),
+            DisplayMode::HighLow => Some(self.format_high_low_text(weather_data)),
+            DisplayMode::SunCountdown => self.format_sun_countdown_text(weather_data),
+        };
+        let mut text = text.unwrap_or_else(|| self.text_template.render(values));
+        for field in self.format_bar_fields(weather_data) {
+            text.push(' ');
+            text.push_str(&field);
+        }
+
+        if self.umbrella_hours > 0 && weather_data.rain_likely_within(self.umbrella_hours) {
+            text = self.icons.prefix(LineIcon::UmbrellaBadge, &text);
+        }
+        if self.aqi_threshold > 0 && self.air_quality_exceeds_threshold(weather_data) {
+            text = self.icons.prefix(LineIcon::AirQualityBadge, &text);
+        }
+        text
+    }
+
+    /// Whether the US EPA air quality index is above the configured level
+    fn air_quality_exceeds_threshold(&self, weather_data: &WeatherData) -> bool {
+        weather_data
+            .air_quality
             .as_ref()
-            .filter(|day| !day.hourly_weather.is_empty())
-            .map(|day| {
-                let forecast = day
-                    .hourly_weather
-                    .iter()
-                    .map(|hour| self.format_hourly_entry(hour))
-                    .collect::<Vec<_>>()
-                    .join("\n");
+            .and_then(|air_quality| air_quality.us_epa_index)
+            .is_some_and(|index| index.value() > self.aqi_threshold)
+    }
 
-                format!("\n\n⏰ Upcoming Hours:\n{}", forecast)
+    /// Format the configured bar fields the provider reported, in order
+    fn format_bar_fields(&self, weather_data: &WeatherData) -> Vec<String> {
+        let current = &weather_data.current;
+        self.bar_fields
+            .iter()
+            .filter_map(|field| match field {
+                BarField::Uv => current
+                    .uv_index
+                    .map(|uv| format!("UV {}", format_uv_colored_compact(&uv))),
+                BarField::Visibility => current.visibility.map(|visibility| {
+                    let visibility = format_visibility(&visibility, self.units.distance);
+                    self.icons.prefix(LineIcon::Visibility, &visibility)
+                }),
+                BarField::CloudCover => current
+                    .cloud_cover
+                    .map(|cover| self.icons.prefix(LineIcon::CloudCover, &cover.to_string())),
             })
-            .unwrap_or_default();
+            .collect()
+    }
 
-        let update_info = format!("\n\n🕐 Updated: {}", weather_data.current.last_updated);
+    /// Format a temperature in the configured unit
+    fn temperature(&self, temperature: &Temperature) -> String {
+        format_temperature(temperature, self.units.temperature)
+    }
 
-        Ok(format!(
-            "{}{}{}{}",
-            basic_info, astronomy_info, hourly_forecast, update_info
+    /// Format a precipitation amount in the configured unit
+    fn precipitation(&self, precipitation: &Precipitation) -> String {
+        format_precipitation(precipitation, self.units.precipitation)
+    }
+
+    /// Format the first forecast hour after the current one
+    fn format_next_hour_text(&self, weather_data: &WeatherData) -> Option<String> {
+        let now = weather_data.local_time;
+        let next = weather_data
+            .weather_day
+            .as_ref()?
+            .hourly_weather
+            .iter()
+            .find(|hourly| hourly.time > now)?;
+
+        Some(format!(
+            "{} {} {}/ {} {}",
+            self.icons.condition(&next.condition),
+            Clock::new(self.clock_zone, weather_data).hour(next.time),
+            self.temperature(&next.temperature),
+            format_wind_colored_compact(&next.wind_speed, &self.wind_style, self.units.speed),
+            escape_markup(&weather_data.location.to_string())
         ))
     }
 
-    /// Format a single hourly forecast entry
-    fn format_hourly_entry(&self, hourly: &crate::domain::models::HourlyWeather) -> String {
+    /// Format the temperature range over the current conditions and today's remaining hours
+    fn format_high_low_text(&self, weather_data: &WeatherData) -> String {
+        let current = weather_data.current.temperature;
+        let today = weather_data.local_time.date();
+        let temperatures = || {
+            weather_data
+                .weather_day
+                .iter()
+                .flat_map(|day| &day.hourly_weather)
+                .filter(|hourly| hourly.time.date() == today)
+                .map(|hourly| hourly.temperature)
+                .chain(std::iter::once(current))
+        };
+        let high = temperatures()
+            .max_by(|a, b| a.value().total_cmp(&b.value()))
+            .unwrap_or(current);
+        let low = temperatures()
+            .min_by(|a, b| a.value().total_cmp(&b.value()))
+            .unwrap_or(current);
+
+        format!(
+            "{} ↑{} ↓{} {}",
+            self.icons.condition(&weather_data.current.condition),
+            self.temperature(&high),
+            self.temperature(&low),
+            escape_markup(&weather_data.location.to_string())
+        )
+    }
+
+    /// Format the countdown to the next sunrise or sunset
+    fn format_sun_countdown_text(&self, weather_data: &WeatherData) -> Option<String> {
+        let astronomy = weather_data.weather_day.as_ref()?.astronomy.as_ref()?;
+        let now = WeatherTime::from_time(weather_data.local_time.time());
+
+        let (event, remaining) = astronomy.next_event(now);
+        let (icon, label) = match event {
+            SunEvent::Sunrise => (LineIcon::Sunrise, "Sunrise"),
+            SunEvent::Sunset => (LineIcon::Sunset, "Sunset"),
+        };
+
+        let countdown = format!(
+            "{} in {}h {:02}m {}",
+            label,
+            remaining.hours(),
+            remaining.minutes(),
+            escape_markup(&weather_data.location.to_string())
+        );
+        Some(self.icons.prefix(icon, &countdown))
+    }
+
+    /// Format the detailed tooltip information
+    fn format_tooltip(&self, values: &Values) -> String {
+        self.tooltip_template.render(values)
+    }
+
+    /// Format the tooltip block listing every location, the shown one in bold
+    fn format_summary(&self, summary: &[LocationWeather]) -> String {
+        let lines = summary
+            .iter()
+            .map(|entry| {
+                let line = match &entry.weather {
+                    Some(weather) => {
+                        let current = &weather.current;
+                        let mut line = format!(
+                            "{}{}: {}, {}",
+                            icon_field(self.icons.condition(&current.condition)),
+                            escape_markup(&entry.name()),
+                            self.temperature(&current.temperature),
+                            current.condition
+                        );
+                        if weather.stale_since.is_some() {
+                            line.push_str(" (stale)");
+                        }
+                        line
+                    }
+                    None => format!("{}: unavailable", escape_markup(&entry.name())),
+                };
+                if entry.shown {
+                    format!("• <b>{}</b>", line)
+                } else {
+                    format!("• {}", line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
         format!(
-            "• {} - {} {}\n          Wind: {} {}",
-            hourly.time,
-            hourly.temperature,
-            hourly.condition,
-            format_wind_colored(&hourly.wind_speed),
-            hourly.wind_direction
+            "{}Locations:\n{}",
+            icon_field(self.icons.line(LineIcon::Location)),
+            lines
         )
     }
+
+    /// Values the text and tooltip templates can use
+    fn template_values(&self, weather_data: &WeatherData) -> Values {
+        let current = &weather_data.current;
+        let today = weather_data.local_time.date();
+        let clock = Clock::new(self.clock_zone, weather_data);
+        let mut values = Values::default();
+
+        // Names and conditions come from the provider, so they are escaped for Pango
+        values.set(
+            "location",
+            escape_markup(&weather_data.location.to_string()),
+        );
+        // Shown beside the name so a wrong match for an ambiguous name stands out
+        values.set_optional(
+            "region",
+            weather_data.location.distinct_region().map(escape_markup),
+        );
+        values.set_optional(
+            "country",
+            weather_data.location.country().map(escape_markup),
+        );
+        values.set("icon", self.icons.condition(&current.condition));
+        values.set("condition", escape_markup(&current.condition.to_string()));
+        values.set("temp", self.temperature(&current.temperature));
+        values.set("feels_like", self.temperature(&current.feels_like));
+        values.set("humidity", current.humidity.to_string());
+        values.set(
+            "dew_point",
+            self.temperature(&current.humidity.dew_point(&current.temperature)),
+        );
+        values.set(
+            "wind",
+            format_wind_colored(&current.wind_speed, &self.wind_style, self.units.speed),
+        );
+        values.set(
+            "wind_short",
+            format_wind_colored_compact(&current.wind_speed, &self.wind_style, self.units.speed),
+        );
+        values.set("wind_dir", current.wind_direction.to_string());
+        values.set(
+            "pressure",
+            format_pressure(&current.pressure, self.units.pressure),
+        );
+        values.set_optional(
+            "precipitation",
+            current
+                .precipitation
+                .map(|amount| self.precipitation(&amount)),
+        );
+        values.set_optional("uv", current.uv_index.map(|uv| format_uv_colored(&uv)));
+        values.set_optional(
+            "visibility",
+            current
+                .visibility
+                .map(|visibility| format_visibility(&visibility, self.units.distance)),
+        );
+        values.set_optional(
+            "cloud_cover",
+            current.cloud_cover.map(|cover| cover.to_string()),
+        );
+        if let Some(air_quality) = &weather_data.air_quality {
+            values.set_optional("air_quality", format_air_quality_colored(air_quality));
+            values.set_optional("pollutants", format_pollutants(air_quality));
+        }
+
+        let day = weather_data.weather_day.as_ref();
+        if let Some(ast) = day.and_then(|day| day.astronomy.as_ref()) {
+            values.set("sunrise", clock.time_of_day(ast.sunrise()));
+            // fall back to sunrise if solar noon calculation fails
+            values.set(
+                "solar_noon",
+                clock.time_of_day(ast.solar_noon().unwrap_or_else(|_| ast.sunrise())),
+            );
+            values.set("sunset", clock.time_of_day(ast.sunset()));
+            values.set("day_length", ast.day_length().to_string());
+        }
+
+        let phase = MoonPhase::on(today);
+        values.set("moon", phase.to_string());
+        values.set("moon_icon", icon_field(self.icons.moon(phase)));
+
+        values.set("updated", clock.date_time(current.last_updated.instant()));
+        values.set("source", escape_markup(&weather_data.source));
+        values.set_optional(
+            "stale_since",
+            weather_data.stale_since.map(|since| clock.hour(since)),
+        );
+        values.set("zone", clock.label());
+
+        for (line, field) in LINE_ICON_FIELDS {
+            values.set(field, icon_field(self.icons.line(line)));
+        }
+
+        values.set_list(
+            "alerts",
+            weather_data
+                .alerts
+                .iter()
+                .map(|alert| {
+                    let mut entry = Values::default();
+                    entry.set("event", escape_markup(&alert.event));
+                    entry.set("severity", alert.severity.to_string());
+                    entry.set("urgency", alert.urgency.to_string());
+                    entry.set("headline", escape_markup(&alert.headline));
+                    entry.set_optional(
+                        "details",
+                        format_alert_details(alert, &clock).map(|details| escape_markup(&details)),
+                    );
+                    entry
+                })
+                .collect(),
+        );
+        values.set_list(
+            "hours",
+            day.map(|day| {
+                day.hourly_weather
+                    .iter()
+                    .take(self.hours)
+                    .map(|hour| self.hourly_values(hour, &clock))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        );
+        values.set_list(
+            "days",
+            weather_data
+                .daily
+                .iter()
+                .map(|day| self.daily_values(day, today))
+                .collect(),
+        );
+        values
+    }
+
+    /// Values for a single hourly forecast entry; hours after today are labelled with
+    /// their weekday
+    fn hourly_values(
+        &self,
+        hourly: &crate::domain::models::HourlyWeather,
+        clock: &Clock,
+    ) -> Values {
+        let mut values = Values::default();
+        values.set("time", clock.hour(hourly.time));
+        values.set("icon", self.icons.condition(&hourly.condition));
+        values.set("temp", self.temperature(&hourly.temperature));
+        values.set("condition", escape_markup(&hourly.condition.to_string()));
+        values.set_optional(
+            "outlook",
+            format_precipitation_outlook(hourly, self.units.precipitation, &self.icons),
+        );
+        values.set(
+            "wind",
+            format_wind_colored(&hourly.wind_speed, &self.wind_style, self.units.speed),
+        );
+        values.set(
+            "wind_short",
+            format_wind_colored_compact(&hourly.wind_speed, &self.wind_style, self.units.speed),
+        );
+        values.set("wind_dir", hourly.wind_direction.to_string());
+        values
+    }
+
+    /// Values for a single day of the multi-day forecast
+    fn daily_values(&self, day: &DailyForecast, today: time::Date) -> Values {
+        let rain = match day.chance_of_rain {
+            Some(chance) => format!("{} {}", chance, self.precipitation(&day.precipitation)),
+            None => self.precipitation(&day.precipitation),
+        };
+        let mut values = Values::default();
+        values.set("day", format_day_label(day.date, today));
+        values.set("icon", self.icons.condition(&day.condition));
+        values.set("high", self.temperature(&day.max_temperature));
+        values.set("low", self.temperature(&day.min_temperature));
+        values.set("condition", escape_markup(&day.condition.to_string()));
+        values.set("rain", rain);
+        values.set(
+            "wind_short",
+            format_wind_colored_compact(&day.max_wind, &self.wind_style, self.units.speed),
+        );
+        values
+    }
 }
 
 impl WeatherFormatter for WaybarFormatter {
     type Output = WaybarOutput;
 
     fn format(&self, data: &WeatherData) -> Result<WaybarOutput> {
-        let text = self.format_display_text(data);
-        let tooltip = self.format_tooltip(data)?;
-        Ok(WaybarOutput { text, tooltip })
+        let values = self.template_values(data);
+        let text = self.format_display_text(data, &values);
+        let tooltip = self.format_tooltip(&values);
+        Ok(WaybarOutput {
+            text,
+            tooltip,
+            class: self.css_classes(data),
+            alt: Self::condition_key(data),
+            percentage: self
+                .percentage
+                .and_then(|percentage| percentage.percentage(data)),
+        })
+    }
+
+    fn format_with_summary(
+        &self,
+        data: &WeatherData,
+        summary: &[LocationWeather],
+    ) -> Result<WaybarOutput> {
+        let mut output = self.format(data)?;
+        output.tooltip.push_str("\n\n");
+        output.tooltip.push_str(&self.format_summary(summary));
+        Ok(output)
+    }
+}
+
+/// Escape text from the provider or the user so Pango shows it rather than reading it as
+/// markup
+pub fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Template value for a line icon: the icon and a space, or nothing when the icon is empty
+/// so that lines without one start flush
+fn icon_field(icon: &str) -> String {
+    if icon.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", icon)
+    }
+}
+
+/// Key for a condition family, used in CSS classes and `alt`
+fn condition_family_key(family: ConditionFamily) -> &'static str {
+    match family {
+        ConditionFamily::Clear => "clear",
+        ConditionFamily::PartlyCloudy => "partly-cloudy",
+        ConditionFamily::Cloudy => "cloudy",
+        ConditionFamily::Rain => "rain",
+        ConditionFamily::Storm => "storm",
+        ConditionFamily::Snow => "snow",
+        ConditionFamily::Fog => "fog",
+        ConditionFamily::Wind => "wind",
+        ConditionFamily::Other => "other",
+    }
+}
+
+/// Key for a wind speed category, used in the "wind-" CSS class
+fn wind_category_key(category: WindSpeedCategory) -> &'static str {
+    match category {
+        WindSpeedCategory::Calm => "calm",
+        WindSpeedCategory::ModerateBreezes => "moderate",
+        WindSpeedCategory::Gales => "gale",
+        WindSpeedCategory::Storms => "storm",
+        WindSpeedCategory::Hurricane => "hurricane",
     }
 }
 
@@ -0,0 +1,452 @@
+//! The computer's position from GeoClue2 over the system D-Bus.
+//!
+//! `GeoClueLocator` asks the GeoClue service for a city-level fix: enough for weather,
+//! and no more precise than it needs to be. `CachedLocator` wraps any `LocationSource`
+//! and keeps the last fix in a state file. A recent fix is reused without asking again.
+//! When GeoClue cannot answer (disabled, denied, or no network to locate by), the last
+//! known position is used rather than no weather at all.
+
+use crate::app::LocationSource;
+use crate::domain::Coordinates;
+use crate::infra::paths;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Instant;
+use time::{Duration, OffsetDateTime};
+use zbus::blocking::{Connection, Proxy};
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::OwnedObjectPath;
+
+/// Name of the file holding the last fix within the state directory
+pub const FIX_FILE_NAME: &str = "last-location.json";
+
+/// Age below which the last fix is reused without asking GeoClue again
+pub const FIX_TTL: Duration = Duration::minutes(10);
+
+/// How long to wait for GeoClue to find the position
+const FIX_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How often to check whether GeoClue has found the position
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+const SERVICE: &str = "org.freedesktop.GeoClue2";
+const MANAGER_PATH: &str = "/org/freedesktop/GeoClue2/Manager";
+const MANAGER_INTERFACE: &str = "org.freedesktop.GeoClue2.Manager";
+const CLIENT_INTERFACE: &str = "org.freedesktop.GeoClue2.Client";
+const LOCATION_INTERFACE: &str = "org.freedesktop.GeoClue2.Location";
+
+/// Identifies the program to GeoClue, which may allow or deny it by this name
+const DESKTOP_ID: &str = "waybar_weather";
+
+/// GeoClue accuracy level for a city-level fix (`GCLUE_ACCURACY_LEVEL_CITY`)
+const ACCURACY_CITY: u32 = 4;
+
+/// Location source asking the GeoClue2 service (`org.freedesktop.GeoClue2`)
+pub struct GeoClueLocator {
+    /// Bus to connect to instead of the system bus
+    address: Option<String>,
+    timeout: std::time::Duration,
+}
+
+impl GeoClueLocator {
+    /// Create a locator for the system bus
+    pub fn new() -> Self {
+        Self {
+            address: None,
+            timeout: FIX_TIMEOUT,
+        }
+    }
+
+    /// Create a locator for the bus at a D-Bus address, e.g. a private test bus
+    #[cfg(test)]
+    fn with_address(address: &str, timeout: std::time::Duration) -> Self {
+        Self {
+            address: Some(address.to_string()),
+            timeout,
+        }
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        let connection = match &self.address {
+            Some(address) => {
+                zbus::blocking::connection::Builder::address(address.as_str())?.build()?
+            }
+            None => Connection::system()?,
+        };
+        Ok(connection)
+    }
+
+    /// Wait for the started client to report a position
+    fn wait_for_fix(&self, connection: &Connection, client: &Proxy) -> Result<Coordinates> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            // The root path stands for "no position yet"
+            let path: OwnedObjectPath = client.get_property("Location")?;
+            if path.as_str() != "/" {
+                let location = proxy(connection, path.as_str(), LOCATION_INTERFACE)?;
+                let latitude: f64 = location.get_property("Latitude")?;
+                let longitude: f64 = location.get_property("Longitude")?;
+                return Ok(Coordinates::new(latitude, longitude)?);
+            }
+            if Instant::now() >= deadline {
+                anyhow::bail!(
+                    "GeoClue found no position within {} seconds",
+                    self.timeout.as_secs()
+                );
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl LocationSource for GeoClueLocator {
+    fn locate(&self) -> Result<Coordinates> {
+        let connection = self
+            .connect()
+            .context("Failed to connect to the D-Bus system bus")?;
+        let manager = proxy(&connection, MANAGER_PATH, MANAGER_INTERFACE)?;
+        let client_path: OwnedObjectPath = manager
+            .call("GetClient", &())
+            .context("GeoClue is not available")?;
+
+        let client = proxy(&connection, client_path.as_str(), CLIENT_INTERFACE)?;
+        client.set_property("DesktopId", DESKTOP_ID)?;
+        client.set_property("RequestedAccuracyLevel", ACCURACY_CITY)?;
+        client
+            .call::<_, _, ()>("Start", &())
+            .context("GeoClue did not allow finding the position")?;
+
+        let fix = self.wait_for_fix(&connection, &client);
+        // The client stops by itself once the connection closes; stopping is a courtesy
+        let _ = client.call::<_, _, ()>("Stop", &());
+        fix
+    }
+}
+
+impl Default for GeoClueLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Proxy for a GeoClue object, reading properties afresh each time
+fn proxy<'a>(connection: &Connection, path: &'a str, interface: &'a str) -> Result<Proxy<'a>> {
+    Ok(zbus::blocking::proxy::Builder::<Proxy>::new(connection)
+        .destination(SERVICE)?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()?)
+}
+
+/// Persisted position from the last successful fix
+#[derive(Debug, Serialize, Deserialize)]
+struct Fix {
+    #[serde(with = "time::serde::rfc3339")]
+    located_at: OffsetDateTime,
+    coordinates: Coordinates,
+}
+
+/// Decorator that remembers the last fix, reusing it while recent and whenever the
+/// inner source fails
+pub struct CachedLocator<S> {
+    inner: S,
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl<S: LocationSource> CachedLocator<S> {
+    /// Wrap `inner`, storing the last fix in `path` and trusting it for `ttl`
+    pub fn new(inner: S, path: PathBuf, ttl: Duration) -> Self {
+        Self { inner, path, ttl }
+    }
+
+    fn locate_at(&self, now: OffsetDateTime) -> Result<Coordinates> {
+        let last = self.load();
+        if let Some(fix) = &last {
+            if now - fix.located_at < self.ttl {
+                return Ok(fix.coordinates);
+            }
+        }
+
+        match self.inner.locate() {
+            Ok(coordinates) => {
+                let _ = self.store(&Fix {
+                    located_at: now,
+                    coordinates,
+                });
+                Ok(coordinates)
+            }
+            Err(e) => match last {
+                Some(fix) => Ok(fix.coordinates),
+                None => Err(e),
+            },
+        }
+    }
+
+    fn load(&self) -> Option<Fix> {
+        let contents = std::fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn store(&self, fix: &Fix) -> Result<()> {
+        paths::write_state_file(&self.path, serde_json::to_string(fix)?)
+    }
+}
+
+impl<S: LocationSource> LocationSource for CachedLocator<S> {
+    fn locate(&self) -> Result<Coordinates> {
+        self.locate_at(OffsetDateTime::now_utc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestBus;
+    use std::cell::{Cell, RefCell};
+    use std::sync::{Arc, Mutex};
+    use time::macros::datetime;
+    use zbus::zvariant::ObjectPath;
+
+    const NOW: OffsetDateTime = datetime!(2023-01-13 20:30 UTC);
+
+    /// Location source replaying a fixed answer and counting how often it is asked
+    struct StubSource {
+        fix: RefCell<Option<(f64, f64)>>,
+        calls: Cell<u32>,
+    }
+
+    impl StubSource {
+        fn new(fix: Option<(f64, f64)>) -> Self {
+            Self {
+                fix: RefCell::new(fix),
+                calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl LocationSource for StubSource {
+        fn locate(&self) -> Result<Coordinates> {
+            self.calls.set(self.calls.get() + 1);
+            let (latitude, longitude) = self.fix.borrow().context("GeoClue is not available")?;
+            Ok(Coordinates::new(latitude, longitude)?)
+        }
+    }
+
+    fn cached_locator(dir: &tempfile::TempDir, source: StubSource) -> CachedLocator<StubSource> {
+        CachedLocator::new(source, dir.path().join(FIX_FILE_NAME), FIX_TTL)
+    }
+
+    #[test]
+    fn test_recent_fix_reused_without_asking() {
+        let dir = tempfile::tempdir().unwrap();
+        let locator = cached_locator(&dir, StubSource::new(Some((-41.29, 174.78))));
+
+        assert_eq!(locator.locate_at(NOW).unwrap().to_string(), "-41.29,174.78");
+        *locator.inner.fix.borrow_mut() = Some((-36.85, 174.76));
+        let later = NOW + Duration::minutes(5);
+        assert_eq!(
+            locator.locate_at(later).unwrap().to_string(),
+            "-41.29,174.78"
+        );
+        assert_eq!(locator.inner.calls.get(), 1);
+
+        // Once the fix is old, GeoClue is asked again
+        let later = NOW + FIX_TTL;
+        assert_eq!(
+            locator.locate_at(later).unwrap().to_string(),
+            "-36.85,174.76"
+        );
+        assert_eq!(locator.inner.calls.get(), 2);
+    }
+
+    #[test]
+    fn test_last_fix_used_when_source_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        cached_locator(&dir, StubSource::new(Some((-41.29, 174.78))))
+            .locate_at(NOW)
+            .unwrap();
+
+        // A fix from days ago is still the best guess
+        let offline = cached_locator(&dir, StubSource::new(None));
+        let coordinates = offline.locate_at(NOW + Duration::days(3)).unwrap();
+        assert_eq!(coordinates.to_string(), "-41.29,174.78");
+    }
+
+    #[test]
+    fn test_error_without_any_fix() {
+        let dir = tempfile::tempdir().unwrap();
+        let locator = cached_locator(&dir, StubSource::new(None));
+
+        let error = locator.locate_at(NOW).unwrap_err();
+        assert_eq!(error.to_string(), "GeoClue is not available");
+    }
+
+    const CLIENT_PATH: &str = "/org/freedesktop/GeoClue2/Client/1";
+    const FIX_PATH: &str = "/org/freedesktop/GeoClue2/Location/1";
+
+    /// Stand-in GeoClue manager handing out the one client
+    struct MockManager;
+
+    #[zbus::interface(name = "org.freedesktop.GeoClue2.Manager")]
+    impl MockManager {
+        fn get_client(&self) -> OwnedObjectPath {
+            ObjectPath::try_from(CLIENT_PATH).unwrap().into()
+        }
+    }
+
+    /// What the stand-in client was told by the locator
+    #[derive(Debug, Default, PartialEq)]
+    struct ClientState {
+        desktop_id: String,
+        accuracy: u32,
+        started: bool,
+        stopped: bool,
+    }
+
+    /// Stand-in GeoClue client, which has a position once started if `has_fix` is set
+    struct MockClient {
+        state: Arc<Mutex<ClientState>>,
+        has_fix: bool,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.GeoClue2.Client")]
+    impl MockClient {
+        fn start(&self) -> zbus::fdo::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            // GeoClue refuses clients that do not say who they are
+            if state.desktop_id.is_empty() {
+                return Err(zbus::fdo::Error::AccessDenied("no DesktopId".to_string()));
+            }
+            state.started = true;
+            Ok(())
+        }
+
+        fn stop(&self) {
+            self.state.lock().unwrap().stopped = true;
+        }
+
+        #[zbus(property)]
+        fn location(&self) -> OwnedObjectPath {
+            let located = self.has_fix && self.state.lock().unwrap().started;
+            let path = if located { FIX_PATH } else { "/" };
+            ObjectPath::try_from(path).unwrap().into()
+        }
+
+        #[zbus(property)]
+        fn desktop_id(&self) -> String {
+            self.state.lock().unwrap().desktop_id.clone()
+        }
+
+        #[zbus(property)]
+        fn set_desktop_id(&mut self, desktop_id: String) {
+            self.state.lock().unwrap().desktop_id = desktop_id;
+        }
+
+        #[zbus(property)]
+        fn requested_accuracy_level(&self) -> u32 {
+            self.state.lock().unwrap().accuracy
+        }
+
+        #[zbus(property)]
+        fn set_requested_accuracy_level(&mut self, level: u32) {
+            self.state.lock().unwrap().accuracy = level;
+        }
+    }
+
+    /// Stand-in GeoClue location object
+    struct MockFix;
+
+    #[zbus::interface(name = "org.freedesktop.GeoClue2.Location")]
+    impl MockFix {
+        #[zbus(property)]
+        fn latitude(&self) -> f64 {
+            -41.28664
+        }
+
+        #[zbus(property)]
+        fn longitude(&self) -> f64 {
+            174.77557
+        }
+    }
+
+    /// Serve a mock GeoClue on the test bus, returning the connection keeping it alive
+    fn serve_geoclue(bus: &TestBus, client: MockClient) -> Connection {
+        zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(SERVICE)
+            .unwrap()
+            .serve_at(MANAGER_PATH, MockManager)
+            .unwrap()
+            .serve_at(CLIENT_PATH, client)
+            .unwrap()
+            .serve_at(FIX_PATH, MockFix)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_geoclue_fix_over_dbus() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let state = Arc::new(Mutex::new(ClientState::default()));
+        let _server = serve_geoclue(
+            &bus,
+            MockClient {
+                state: state.clone(),
+                has_fix: true,
+            },
+        );
+
+        let locator = GeoClueLocator::with_address(&bus.address, FIX_TIMEOUT);
+        let coordinates = locator.locate().unwrap();
+        assert_eq!(coordinates.to_string(), "-41.28664,174.77557");
+
+        assert_eq!(
+            *state.lock().unwrap(),
+            ClientState {
+                desktop_id: DESKTOP_ID.to_string(),
+                accuracy: ACCURACY_CITY,
+                started: true,
+                stopped: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_geoclue_without_fix_times_out() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let state = Arc::new(Mutex::new(ClientState::default()));
+        let _server = serve_geoclue(
+            &bus,
+            MockClient {
+                state: state.clone(),
+                has_fix: false,
+            },
+        );
+
+        let locator =
+            GeoClueLocator::with_address(&bus.address, std::time::Duration::from_millis(300));
+        let error = locator.locate().unwrap_err();
+        assert!(error.to_string().starts_with("GeoClue found no position"));
+        assert!(state.lock().unwrap().stopped);
+    }
+
+    #[test]
+    fn test_geoclue_missing_from_bus() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+
+        let locator = GeoClueLocator::with_address(&bus.address, FIX_TIMEOUT);
+        let error = locator.locate().unwrap_err();
+        assert_eq!(error.to_string(), "GeoClue is not available");
+    }
+}
@@ -1,3 +1,10 @@
 //! Infrastructure adapters: API clients, display formatters, and other I/O.
 pub mod api;
+pub mod cache;
+pub mod config;
 pub mod display;
+pub mod geoclue;
+pub mod notify;
+pub mod paths;
+pub mod rotation;
+pub mod signals;
@@ -0,0 +1,483 @@
+//! Desktop notifications for weather alerts.
+//!
+//! `NotifyingFetcher` wraps any `WeatherFetcher` and hands each alert it has not seen
+//! before to an `AlertNotifier`. The IDs of alerts already notified are persisted to a
+//! state file, so polling every few minutes (or restarting Waybar) does not repeat a
+//! notification for the same alert. `DesktopNotifier` delivers them through the
+//! freedesktop notification service on the session bus.
+
+use crate::app::{AlertNotifier, WeatherFetcher};
+use crate::domain::{AlertSeverity, Location, LocationQuery, WeatherAlert, WeatherData};
+use crate::infra::display::escape_markup;
+use crate::infra::paths;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use time::{Duration, OffsetDateTime};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::Value;
+
+/// Name of the state file within the state directory
+pub const STATE_FILE_NAME: &str = "notified-alerts.json";
+
+/// How long an alert without an expiry time is remembered after notifying about it
+const FORGET_OPEN_ENDED_AFTER: Duration = Duration::days(7);
+
+/// Application name shown by the notification server
+const APP_NAME: &str = "waybar_weather";
+
+/// Freedesktop icon name for alert notifications
+const APP_ICON: &str = "dialog-warning";
+
+/// An alert the user has already been notified about
+#[derive(Debug, Serialize, Deserialize)]
+struct NotifiedAlert {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    notified_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    expires: Option<OffsetDateTime>,
+}
+
+impl NotifiedAlert {
+    /// Whether the alert could still be reported, so must not be notified again
+    fn is_remembered_at(&self, now: OffsetDateTime) -> bool {
+        match self.expires {
+            Some(expires) => expires > now,
+            None => now - self.notified_at < FORGET_OPEN_ENDED_AFTER,
+        }
+    }
+}
+
+/// Decorator that notifies about each new alert in the fetched data once
+pub struct NotifyingFetcher<F, N> {
+    inner: F,
+    notifier: N,
+    state_path: PathBuf,
+    /// Held while the state file is read and rewritten, since the fetches for several
+    /// locations may finish at once
+    state_lock: Mutex<()>,
+}
+
+impl<F: WeatherFetcher, N: AlertNotifier> NotifyingFetcher<F, N> {
+    /// Wrap `inner`, recording the alerts passed to `notifier` in `state_path`
+    pub fn new(inner: F, notifier: N, state_path: PathBuf) -> Self {
+        Self {
+            inner,
+            notifier,
+            state_path,
+            state_lock: Mutex::new(()),
+        }
+    }
+
+    fn fetch_weather_at(
+        &self,
+        location: &LocationQuery,
+        now: OffsetDateTime,
+    ) -> Result<WeatherData> {
+        let data = self.inner.fetch_weather(location)?;
+        if !data.alerts.is_empty() {
+            self.notify_new(&data, now);
+        }
+        Ok(data)
+    }
+
+    /// Notify about alerts not notified before, and remember them
+    fn notify_new(&self, data: &WeatherData, now: OffsetDateTime) {
+        let _state = self
+            .state_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut notified = self.load();
+        notified.retain(|entry| entry.is_remembered_at(now));
+
+        for alert in &data.alerts {
+            // Alerts cached before IDs were recorded can't be told apart
+            if alert.id.is_empty() || notified.iter().any(|entry| entry.id == alert.id) {
+                continue;
+            }
+            // An alert that could not be shown is tried again after the next fetch
+            if self.notifier.notify(&data.location, alert).is_ok() {
+                notified.push(NotifiedAlert {
+                    id: alert.id.clone(),
+                    notified_at: now,
+                    expires: alert.expires,
+                });
+            }
+        }
+
+        let _ = self.store(&notified);
+    }
+
+    fn load(&self) -> Vec<NotifiedAlert> {
+        std::fs::read_to_string(&self.state_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self, notified: &[NotifiedAlert]) -> Result<()> {
+        paths::write_state_file(&self.state_path, serde_json::to_string(notified)?)
+    }
+}
+
+impl<F: WeatherFetcher, N: AlertNotifier> WeatherFetcher for NotifyingFetcher<F, N> {
+    fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+        self.fetch_weather_at(location, OffsetDateTime::now_utc())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Notifier posting to the freedesktop notification service
+/// (`org.freedesktop.Notifications`) over D-Bus
+pub struct DesktopNotifier {
+    /// Bus to connect to instead of the session bus
+    address: Option<String>,
+}
+
+impl DesktopNotifier {
+    /// Create a notifier for the user's session bus
+    pub fn new() -> Self {
+        Self { address: None }
+    }
+
+    /// Create a notifier for the bus at a D-Bus address, e.g. a private test bus
+    #[cfg(test)]
+    fn with_address(address: &str) -> Self {
+        Self {
+            address: Some(address.to_string()),
+        }
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        let connection = match &self.address {
+            Some(address) => {
+                zbus::blocking::connection::Builder::address(address.as_str())?.build()?
+            }
+            None => Connection::session()?,
+        };
+        Ok(connection)
+    }
+
+    /// Notification urgency hint: 0 low, 1 normal, 2 critical (stays until dismissed)
+    fn urgency(severity: AlertSeverity) -> u8 {
+        match severity {
+            AlertSeverity::Minor => 0,
+            AlertSeverity::Unknown | AlertSeverity::Moderate => 1,
+            AlertSeverity::Severe | AlertSeverity::Extreme => 2,
+        }
+    }
+}
+
+impl AlertNotifier for DesktopNotifier {
+    fn notify(&self, location: &Location, alert: &WeatherAlert) -> Result<()> {
+        let connection = self
+            .connect()
+            .context("Failed to connect to the D-Bus session bus")?;
+        let proxy = Proxy::new(
+            &connection,
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+        )?;
+
+        // The summary is always plain text; the body is read as markup by servers that
+        // advertise it, so provider text is escaped for those
+        let summary = format!("⚠️ {} for {}", alert.event, location);
+        let mut body = alert.headline.clone();
+        if !alert.areas.is_empty() {
+            body.push_str(&format!("\n{}", alert.areas.join(", ")));
+        }
+        let capabilities: Vec<String> = proxy.call("GetCapabilities", &()).unwrap_or_default();
+        if capabilities
+            .iter()
+            .any(|capability| capability == "body-markup")
+        {
+            body = escape_markup(&body);
+        }
+        let hints = HashMap::from([("urgency", Value::from(Self::urgency(alert.severity)))]);
+        let actions: Vec<&str> = Vec::new();
+        // -1 leaves the display time to the notification server
+        let expire_timeout = -1i32;
+
+        let _id: u32 = proxy
+            .call(
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    APP_ICON,
+                    summary,
+                    body,
+                    actions,
+                    hints,
+                    expire_timeout,
+                ),
+            )
+            .context("Failed to send desktop notification")?;
+        Ok(())
+    }
+}
+
+impl Default for DesktopNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::AlertUrgency;
+    use crate::test_support::{weather_data, TestBus};
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex};
+    use time::macros::datetime;
+
+    const NOW: OffsetDateTime = datetime!(2023-01-13 20:30 UTC);
+
+    /// Fetcher returning a fixed set of alerts
+    struct AlertFetcher {
+        alerts: RefCell<Vec<WeatherAlert>>,
+    }
+
+    impl WeatherFetcher for AlertFetcher {
+        fn fetch_weather(&self, location: &LocationQuery) -> Result<WeatherData> {
+            Ok(WeatherData {
+                alerts: self.alerts.borrow().clone(),
+                ..weather_data(&location.to_string())
+            })
+        }
+
+        fn name(&self) -> &str {
+            "Alerting"
+        }
+    }
+
+    /// Notifier recording the IDs of the alerts it was given
+    #[derive(Default)]
+    struct RecordingNotifier {
+        notified: RefCell<Vec<String>>,
+    }
+
+    impl AlertNotifier for RecordingNotifier {
+        fn notify(&self, _location: &Location, alert: &WeatherAlert) -> Result<()> {
+            self.notified.borrow_mut().push(alert.id.clone());
+            Ok(())
+        }
+    }
+
+    /// Notifier for a desktop without a notification server
+    struct UnavailableNotifier;
+
+    impl AlertNotifier for UnavailableNotifier {
+        fn notify(&self, _location: &Location, _alert: &WeatherAlert) -> Result<()> {
+            anyhow::bail!("org.freedesktop.Notifications was not provided by any .service files")
+        }
+    }
+
+    fn alert(id: &str, expires: Option<OffsetDateTime>) -> WeatherAlert {
+        WeatherAlert {
+            id: id.to_string(),
+            event: "Wind Advisory".to_string(),
+            headline: "Wind Advisory until 6PM".to_string(),
+            severity: AlertSeverity::Severe,
+            urgency: AlertUrgency::Expected,
+            areas: vec!["Wellington".to_string(), "Kapiti Coast".to_string()],
+            effective: None,
+            expires,
+        }
+    }
+
+    fn place(name: &str) -> LocationQuery {
+        LocationQuery::Name(name.to_string())
+    }
+
+    fn notifying_fetcher<N: AlertNotifier>(
+        dir: &tempfile::TempDir,
+        notifier: N,
+        alerts: Vec<WeatherAlert>,
+    ) -> NotifyingFetcher<AlertFetcher, N> {
+        NotifyingFetcher::new(
+            AlertFetcher {
+                alerts: RefCell::new(alerts),
+            },
+            notifier,
+            dir.path().join("state").join(STATE_FILE_NAME),
+        )
+    }
+
+    #[test]
+    fn test_each_alert_notified_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let expires = Some(NOW + Duration::hours(6));
+        let fetcher = notifying_fetcher(
+            &dir,
+            RecordingNotifier::default(),
+            vec![alert("wind", expires)],
+        );
+
+        fetcher.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        fetcher
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(10))
+            .unwrap();
+        assert_eq!(*fetcher.notifier.notified.borrow(), vec!["wind"]);
+
+        // A new alert is notified even while an earlier one is still in force
+        fetcher
+            .inner
+            .alerts
+            .borrow_mut()
+            .push(alert("flood", expires));
+        fetcher
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(20))
+            .unwrap();
+        assert_eq!(*fetcher.notifier.notified.borrow(), vec!["wind", "flood"]);
+    }
+
+    #[test]
+    fn test_notified_alerts_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let alerts = vec![alert("wind", Some(NOW + Duration::hours(6)))];
+
+        let first = notifying_fetcher(&dir, RecordingNotifier::default(), alerts.clone());
+        first.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        let second = notifying_fetcher(&dir, RecordingNotifier::default(), alerts);
+        second
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(10))
+            .unwrap();
+
+        assert_eq!(first.notifier.notified.borrow().len(), 1);
+        assert!(second.notifier.notified.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_expired_alerts_forgotten() {
+        let dir = tempfile::tempdir().unwrap();
+        let fetcher = notifying_fetcher(
+            &dir,
+            RecordingNotifier::default(),
+            vec![
+                alert("wind", Some(NOW + Duration::hours(1))),
+                alert("fog", None),
+            ],
+        );
+
+        fetcher.fetch_weather_at(&place("Wellington"), NOW).unwrap();
+        let state = std::fs::read_to_string(&fetcher.state_path).unwrap();
+        assert!(state.contains("\"wind\"") && state.contains("\"fog\""));
+
+        // Once lapsed, an alert no longer takes up room in the state file
+        fetcher.inner.alerts.borrow_mut().clear();
+        fetcher.inner.alerts.borrow_mut().push(alert("rain", None));
+        fetcher
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::days(8))
+            .unwrap();
+        let state = std::fs::read_to_string(&fetcher.state_path).unwrap();
+        assert!(!state.contains("\"wind\"") && !state.contains("\"fog\""));
+        assert!(state.contains("\"rain\""));
+    }
+
+    #[test]
+    fn test_failed_notification_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let alerts = vec![alert("wind", Some(NOW + Duration::hours(6)))];
+
+        let unavailable = notifying_fetcher(&dir, UnavailableNotifier, alerts.clone());
+        // Notification failures never fail the fetch
+        assert_eq!(
+            unavailable
+                .fetch_weather_at(&place("Wellington"), NOW)
+                .unwrap()
+                .alerts
+                .len(),
+            1
+        );
+
+        let available = notifying_fetcher(&dir, RecordingNotifier::default(), alerts);
+        available
+            .fetch_weather_at(&place("Wellington"), NOW + Duration::minutes(10))
+            .unwrap();
+        assert_eq!(*available.notifier.notified.borrow(), vec!["wind"]);
+    }
+
+    /// Stand-in notification server recording summary, body and urgency
+    struct NotificationServer {
+        received: Arc<Mutex<Vec<(String, String, u8)>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl NotificationServer {
+        fn get_capabilities(&self) -> Vec<&str> {
+            vec!["body", "body-markup"]
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            _replaces_id: u32,
+            _app_icon: &str,
+            summary: &str,
+            body: &str,
+            _actions: Vec<&str>,
+            hints: HashMap<&str, Value<'_>>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let urgency = hints
+                .get("urgency")
+                .and_then(|urgency| u8::try_from(urgency).ok())
+                .unwrap_or(1);
+            let mut received = self.received.lock().unwrap();
+            received.push((summary.to_string(), body.to_string(), urgency));
+            received.len() as u32
+        }
+    }
+
+    #[test]
+    fn test_desktop_notification_over_dbus() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let _server = zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name("org.freedesktop.Notifications")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/Notifications",
+                NotificationServer {
+                    received: received.clone(),
+                },
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        DesktopNotifier::with_address(&bus.address)
+            .notify(
+                &Location::new("Wellington".to_string()),
+                &WeatherAlert {
+                    headline: "Gusts > 90 km/h & rain until 6PM".to_string(),
+                    ..alert("wind", None)
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(
+                "⚠️ Wind Advisory for Wellington".to_string(),
+                "Gusts &gt; 90 km/h &amp; rain until 6PM\nWellington, Kapiti Coast".to_string(),
+                2
+            )]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Filesystem locations following the XDG Base Directory specification.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Application subdirectory name used under each XDG base directory
+const APP_DIR: &str = "waybar_weather";
+
+/// Directory for disposable cached data (`$XDG_CACHE_HOME/waybar_weather`)
+pub fn cache_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Directory for user configuration (`$XDG_CONFIG_HOME/waybar_weather`)
+pub fn config_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Directory for state kept between runs (`$XDG_STATE_HOME/waybar_weather`)
+pub fn state_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// [`cache_dir`], or a directory under the system's temporary directory when there is
+/// no home to put it in
+pub fn cache_dir_or_temp() -> PathBuf {
+    cache_dir().unwrap_or_else(|_| temp_dir())
+}
+
+/// [`state_dir`], or a directory under the system's temporary directory when there is
+/// no home to put it in
+pub fn state_dir_or_temp() -> PathBuf {
+    state_dir().unwrap_or_else(|_| temp_dir())
+}
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(APP_DIR)
+}
+
+/// Write a file of state kept between runs, creating its directory first.
+///
+/// State is best effort and callers ignore the error: a read-only or full disk only
+/// means work is repeated on the next run.
+pub fn write_state_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create state directory {}", dir.display()))?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>` when the
+/// variable is unset, empty or not absolute (as the specification requires)
+fn xdg_dir(variable: &str, fallback: &str) -> Result<PathBuf> {
+    let base = match std::env::var_os(variable).map(PathBuf::from) {
+        Some(path) if path.is_absolute() => path,
+        _ => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(fallback))
+            .with_context(|| format!("Neither {} nor HOME is set", variable))?,
+    };
+    Ok(base.join(APP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xdg_dir_uses_absolute_variable() {
+        std::env::set_var("WAYBAR_WEATHER_TEST_XDG_ABS", "/tmp/xdg-cache");
+        let dir = xdg_dir("WAYBAR_WEATHER_TEST_XDG_ABS", ".cache").unwrap();
+        assert_eq!(dir, PathBuf::from("/tmp/xdg-cache/waybar_weather"));
+    }
+
+    #[test]
+    fn test_write_state_file_creates_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/nested/file");
+
+        write_state_file(&path, "1").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1");
+    }
+
+    #[test]
+    fn test_xdg_dir_ignores_relative_variable() {
+        std::env::set_var("WAYBAR_WEATHER_TEST_XDG_REL", "relative/cache");
+        let dir = xdg_dir("WAYBAR_WEATHER_TEST_XDG_REL", ".cache").unwrap();
+        assert!(dir.is_absolute());
+        assert!(dir.ends_with(".cache/waybar_weather"));
+    }
+}
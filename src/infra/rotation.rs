@@ -0,0 +1,52 @@
+//! Which of several locations the bar shows, kept between runs.
+//!
+//! Outside daemon mode every Waybar refresh (and every click, with `exec-on-event`)
+//! starts a new process, so the position in the rotation is kept in a state file.
+
+use crate::infra::paths;
+use std::path::Path;
+
+/// Name of the file holding the position within the state directory
+pub const STATE_FILE_NAME: &str = "shown-location";
+
+/// Move the rotation kept in `path` on by one of `count` locations and return the
+/// index of the location to show now.
+///
+/// Without a readable state file the rotation starts over at the first location.
+pub fn advance(path: &Path, count: usize) -> usize {
+    let next = match std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.trim().parse::<usize>().ok())
+    {
+        Some(last) => (last + 1) % count.max(1),
+        None => 0,
+    };
+    let _ = paths::write_state_file(path, next.to_string());
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_wraps_around() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE_NAME);
+
+        let shown = (0..4).map(|_| advance(&path, 3)).collect::<Vec<_>>();
+        assert_eq!(shown, [0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_advance_stays_in_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE_NAME);
+        std::fs::write(&path, "4").unwrap();
+
+        // Fewer locations than before: the rotation wraps rather than running off the end
+        assert_eq!(advance(&path, 3), 2);
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(advance(&path, 3), 0);
+    }
+}
@@ -0,0 +1,118 @@
+//! Unix signal handling for daemon mode.
+//!
+//! Waybar's `on-click` settings can signal the running process, e.g.
+//! `pkill -USR1 waybar_weather` or `pkill -RTMIN+8 waybar_weather`. Signals are
+//! received on a background thread and forwarded over a channel so the daemon loop
+//! can wait for either the next signal or its next scheduled tick.
+
+use anyhow::{Context, Result};
+use signal_hook::iterator::Signals;
+use std::sync::mpsc::{self, Receiver};
+
+/// What a received signal asks the daemon to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalAction {
+    /// Refetch immediately instead of waiting for the next scheduled refresh
+    Refresh,
+    /// Switch the bar text to the next display mode
+    CycleMode,
+    /// Show the next of several locations in the bar
+    NextLocation,
+}
+
+/// Parse a signal name such as "USR1", "SIGUSR2" or "RTMIN+8"
+pub fn parse_signal(name: &str) -> Result<libc::c_int> {
+    let upper = name.trim().to_uppercase();
+    let upper = upper.strip_prefix("SIG").unwrap_or(&upper);
+
+    match upper {
+        "USR1" => Ok(libc::SIGUSR1),
+        "USR2" => Ok(libc::SIGUSR2),
+        "RTMIN" => Ok(libc::SIGRTMIN()),
+        _ => {
+            // Unsigned, so that a negative offset cannot reach below the real-time range
+            let offset: u8 = upper
+                .strip_prefix("RTMIN+")
+                .and_then(|offset| offset.parse().ok())
+                .with_context(|| {
+                    format!(
+                        "Unknown signal '{}'. Use USR1, USR2 or RTMIN+<n>",
+                        name.trim()
+                    )
+                })?;
+            let signal = libc::SIGRTMIN() + libc::c_int::from(offset);
+            if signal > libc::SIGRTMAX() {
+                anyhow::bail!("Signal out of range: {}", name.trim());
+            }
+            Ok(signal)
+        }
+    }
+}
+
+/// Start listening for the refresh and mode-cycling signals, and for the
+/// location-cycling signal if one is given.
+///
+/// The returned receiver yields an action for each signal delivered.
+pub fn listen(
+    refresh_signal: libc::c_int,
+    mode_signal: libc::c_int,
+    location_signal: Option<libc::c_int>,
+) -> Result<Receiver<SignalAction>> {
+    if refresh_signal == mode_signal
+        || location_signal.is_some_and(|signal| signal == refresh_signal || signal == mode_signal)
+    {
+        anyhow::bail!("The refresh, mode and location signals must differ");
+    }
+
+    let mut signals = Signals::new(
+        [refresh_signal, mode_signal]
+            .into_iter()
+            .chain(location_signal),
+    )
+    .context("Failed to register signal handlers")?;
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            let action = if signal == refresh_signal {
+                SignalAction::Refresh
+            } else if signal == mode_signal {
+                SignalAction::CycleMode
+            } else {
+                SignalAction::NextLocation
+            };
+            if sender.send(action).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signal_names() {
+        assert_eq!(parse_signal("USR1").unwrap(), libc::SIGUSR1);
+        assert_eq!(parse_signal("sigusr2").unwrap(), libc::SIGUSR2);
+        assert_eq!(parse_signal("RTMIN").unwrap(), libc::SIGRTMIN());
+        assert_eq!(parse_signal("SIGRTMIN+8").unwrap(), libc::SIGRTMIN() + 8);
+    }
+
+    #[test]
+    fn test_parse_signal_invalid() {
+        assert!(parse_signal("HUP").is_err());
+        assert!(parse_signal("RTMIN+").is_err());
+        assert!(parse_signal("RTMIN+999").is_err());
+        assert!(parse_signal("RTMIN+-19").is_err());
+    }
+
+    #[test]
+    fn test_listen_rejects_same_signal() {
+        assert!(listen(libc::SIGUSR1, libc::SIGUSR1, None).is_err());
+        assert!(listen(libc::SIGUSR1, libc::SIGUSR2, Some(libc::SIGUSR2)).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 //! Weather application with domain-driven design and type safety.
-//! Fetches weather data from WeatherAPI.com and outputs JSON for Waybar.
+//! Fetches weather data from a chain of weather providers and outputs JSON for Waybar.
 //!
 //! This file is the composition root: it constructs concrete types and
 //! delegates to the application layer.
@@ -7,42 +7,747 @@
 mod app;
 mod domain;
 mod infra;
+#[cfg(test)]
+mod test_support;
 
-use anyhow::Result;
-use infra::api::WeatherClient;
-use infra::display::WaybarFormatter;
+use anyhow::{Context, Result};
+use app::{
+    Daemon, LabelledFetcher, LocatingFetcher, LocationSearcher, LocationSource, WeatherFetcher,
+};
+use domain::{LocationQuery, PlaceMatch, UnitSystem};
+use infra::api::provider::ALL_PROVIDERS;
+use infra::api::{http_agent, Provider};
+use infra::cache::CachedFetcher;
+use infra::config::{self, Config, Settings};
+use infra::display::{TerminalFormatter, WaybarFormatter};
+use infra::geoclue::{self, CachedLocator, GeoClueLocator};
+use infra::notify::{self, DesktopNotifier, NotifyingFetcher};
+use infra::paths;
+use infra::rotation;
+use infra::signals::{self, SignalAction};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use time::OffsetDateTime;
 
-fn main() -> Result<()> {
-    let location = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "Wellington".to_string());
+const USAGE: &str = "\
+Usage: waybar_weather [command] [options] [location...]
+
+Commands:
+  bar       Print weather as Waybar JSON (the default when no command is given)
+  show      Print a weather report for reading in a terminal
+  search    List places matching a name, to tell apart places that share it:
+            waybar_weather search <query> [--save <number>]
+  doctor    Check the configuration, API keys and each provider
+  help      Show this help
+
+Options:
+  --profile <name>            Use a profile from the configuration file
+  --provider <name>[,<name>]  Providers to try in order: weatherapi, openmeteo, metno, nws
+  --units <system>            Units to show: metric, imperial or uk (default metric)
+  --cache-ttl <seconds>       Reuse a cached result for this long (default 600)
+  --daemon                    Keep running, printing a line every minute (bar only)
+  --interval <seconds>        How often daemon mode refetches (default 1800)
+  --refresh-signal <signal>   Signal that makes daemon mode refetch (default USR1)
+  --mode-signal <signal>      Signal that cycles the bar text (default USR2)
+  --rotate                    Show each location in the bar in turn (bar only)
+  --location-signal <signal>  Signal that shows the next location (daemon only)
+  --save <number>             Save that place from the search results to the profile
+  -h, --help                  Show this help
+
+Locations: a place name, lat,lon (e.g. -41.2866,174.7756), a US, UK or Canadian
+postcode, iata:<code> or id:<n> (WeatherAPI.com place id). With several locations
+the first is shown, and all are summarised in the tooltip or report
+";
+
+/// Most places listed by the `search` command
+const SEARCH_RESULTS: usize = 10;
+
+/// What the program was asked to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Command {
+    /// Print Waybar JSON, once or continuously in daemon mode
+    #[default]
+    Bar,
+    /// Print a human-readable report
+    Show,
+    /// List places matching the location argument
+    Search,
+    /// Print configuration and provider diagnostics
+    Doctor,
+    /// Print usage
+    Help,
+}
+
+impl Command {
+    /// Command named by a leading argument, if it names one
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bar" => Some(Self::Bar),
+            "show" => Some(Self::Show),
+            "search" => Some(Self::Search),
+            "doctor" => Some(Self::Doctor),
+            "help" => Some(Self::Help),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed command-line arguments:
+/// `waybar_weather [bar|show|search|doctor|help] [--profile <name>]
+/// [--provider <name>[,<name>...]] [--units <system>] [--cache-ttl <seconds>]
+/// [--daemon [--interval <seconds>] [--refresh-signal <signal>] [--mode-signal <signal>]
+/// [--location-signal <signal>]] [--rotate] [--save <number>] [location...]`
+///
+/// Without a command the arguments are those of `bar`, so existing Waybar
+/// configurations passing only a location keep working. Options left out fall back
+/// to the selected configuration profile.
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    /// Configuration profile to use instead of the default one
+    profile: Option<String>,
+    /// Locations to report on, the primary one first
+    locations: Vec<LocationQuery>,
+    /// What to look for with `search`
+    query: Option<String>,
+    /// Place from the `search` results to save to the profile, counting from 1
+    save: Option<usize>,
+    /// Providers in order of preference; later ones are tried when earlier ones fail
+    providers: Option<Vec<Provider>>,
+    /// Unit system values are shown in
+    units: Option<UnitSystem>,
+    /// How long a cached result is served without refetching
+    cache_ttl: Option<time::Duration>,
+    /// Keep running and print a new line whenever the output changes
+    daemon: bool,
+    /// How often daemon mode refetches
+    interval: Option<time::Duration>,
+    /// Signal that makes daemon mode refetch immediately
+    refresh_signal: libc::c_int,
+    /// Signal that makes daemon mode cycle the bar text's display mode
+    mode_signal: libc::c_int,
+    /// Signal that makes daemon mode show the next location
+    location_signal: Option<libc::c_int>,
+    /// Show each location in the bar in turn
+    rotate: bool,
+}
+
+/// Options that only mean something to daemon mode
+const DAEMON_OPTIONS: [&str; 4] = [
+    "--interval",
+    "--refresh-signal",
+    "--mode-signal",
+    "--location-signal",
+];
+
+impl Args {
+    /// Parse arguments (excluding the program name)
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+        let command = args
+            .next_if(|arg| Command::from_name(arg).is_some())
+            .and_then(|arg| Command::from_name(&arg));
+        let mut command = command.unwrap_or_default();
+        let mut profile = None;
+        let mut positional = Vec::new();
+        let mut providers = None;
+        let mut units = None;
+        let mut cache_ttl = None;
+        let mut daemon = false;
+        let mut interval = None;
+        let mut refresh_signal = libc::SIGUSR1;
+        let mut mode_signal = libc::SIGUSR2;
+        let mut location_signal = None;
+        let mut rotate = false;
+        let mut save = None;
+        // The first daemon option given, to reject outside daemon mode
+        let mut daemon_option = None;
+
+        while let Some(arg) = args.next() {
+            let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+            if daemon_option.is_none() {
+                daemon_option = DAEMON_OPTIONS.into_iter().find(|option| *option == name);
+            }
+
+            if arg == "--help" || arg == "-h" {
+                command = Command::Help;
+            } else if let Some(name) = arg.strip_prefix("--profile=") {
+                profile = Some(name.to_string());
+            } else if arg == "--profile" {
+                profile = Some(args.next().context("--profile requires a value")?);
+            } else if let Some(names) = arg.strip_prefix("--provider=") {
+                providers = Some(Provider::parse_list(names)?);
+            } else if arg == "--provider" {
+                providers = Some(Provider::parse_list(
+                    &args.next().context("--provider requires a value")?,
+                )?);
+            } else if let Some(system) = arg.strip_prefix("--units=") {
+                units = Some(system.parse()?);
+            } else if arg == "--units" {
+                units = Some(args.next().context("--units requires a value")?.parse()?);
+            } else if let Some(seconds) = arg.strip_prefix("--cache-ttl=") {
+                cache_ttl = Some(parse_seconds(seconds)?);
+            } else if arg == "--cache-ttl" {
+                cache_ttl = Some(parse_seconds(
+                    &args.next().context("--cache-ttl requires a value")?,
+                )?);
+            } else if arg == "--daemon" {
+                daemon = true;
+            } else if let Some(seconds) = arg.strip_prefix("--interval=") {
+                interval = Some(parse_interval(seconds)?);
+            } else if arg == "--interval" {
+                interval = Some(parse_interval(
+                    &args.next().context("--interval requires a value")?,
+                )?);
+            } else if let Some(name) = arg.strip_prefix("--refresh-signal=") {
+                refresh_signal = signals::parse_signal(name)?;
+            } else if arg == "--refresh-signal" {
+                refresh_signal = signals::parse_signal(
+                    &args.next().context("--refresh-signal requires a value")?,
+                )?;
+            } else if let Some(name) = arg.strip_prefix("--mode-signal=") {
+                mode_signal = signals::parse_signal(name)?;
+            } else if arg == "--mode-signal" {
+                mode_signal =
+                    signals::parse_signal(&args.next().context("--mode-signal requires a value")?)?;
+            } else if let Some(name) = arg.strip_prefix("--location-signal=") {
+                location_signal = Some(signals::parse_signal(name)?);
+            } else if arg == "--location-signal" {
+                location_signal = Some(signals::parse_signal(
+                    &args.next().context("--location-signal requires a value")?,
+                )?);
+            } else if arg == "--rotate" {
+                rotate = true;
+            } else if let Some(number) = arg.strip_prefix("--save=") {
+                save = Some(parse_match_number(number)?);
+            } else if arg == "--save" {
+                save = Some(parse_match_number(
+                    &args.next().context("--save requires a value")?,
+                )?);
+            } else {
+                positional.push(arg);
+            }
+        }
+
+        // `search` takes free text; every other command takes locations
+        let (locations, query) = match command {
+            Command::Search => {
+                let mut positional = positional.into_iter();
+                let query = positional
+                    .next()
+                    .context("search requires a query, e.g. waybar_weather search Springfield")?;
+                if let Some(arg) = positional.next() {
+                    anyhow::bail!("Unexpected argument: {}", arg);
+                }
+                (Vec::new(), Some(query))
+            }
+            _ => (
+                positional
+                    .iter()
+                    .map(|location| location.parse())
+                    .collect::<Result<_, _>>()?,
+                None,
+            ),
+        };
+        if daemon && !matches!(command, Command::Bar | Command::Help) {
+            anyhow::bail!("--daemon only applies to the bar command");
+        }
+        if let Some(option) = daemon_option {
+            if !daemon && command != Command::Help {
+                anyhow::bail!("{} only applies with --daemon", option);
+            }
+        }
+        if save.is_some() && !matches!(command, Command::Search | Command::Help) {
+            anyhow::bail!("--save only applies to the search command");
+        }
+        if rotate && !matches!(command, Command::Bar | Command::Help) {
+            anyhow::bail!("--rotate only applies to the bar command");
+        }
+
+        Ok(Self {
+            command,
+            profile,
+            locations,
+            query,
+            save,
+            providers,
+            units,
+            cache_ttl,
+            daemon,
+            interval,
+            refresh_signal,
+            mode_signal,
+            location_signal,
+            rotate,
+        })
+    }
+
+    /// Apply the options given on the command line on top of profile settings
+    fn apply(&self, settings: &mut Settings) {
+        if let Some((location, extra_locations)) = self.locations.split_first() {
+            settings.location = location.clone();
+            settings.extra_locations = extra_locations.to_vec();
+            // The profile's name for its own location does not fit another place
+            settings.location_name = None;
+        }
+        if self.rotate {
+            settings.rotate_locations = true;
+        }
+        if let Some(providers) = &self.providers {
+            settings.providers = providers.clone();
+        }
+        if let Some(units) = self.units {
+            settings.units = units;
+        }
+        if let Some(cache_ttl) = self.cache_ttl {
+            settings.cache_ttl = cache_ttl;
+        }
+        if let Some(interval) = self.interval {
+            settings.interval = interval;
+        }
+    }
+}
+
+/// Path of the configuration file, if there is a configuration directory
+fn config_path() -> Option<PathBuf> {
+    paths::config_dir()
+        .ok()
+        .map(|dir| dir.join(config::FILE_NAME))
+}
+
+/// Resolve settings from the configuration profile and the command line
+fn load_settings(args: &Args) -> Result<Settings> {
+    // Without a configuration directory there can be no file, so no profiles
+    let config = match config_path() {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    let mut settings = config.settings(args.profile.as_deref())?;
+    args.apply(&mut settings);
+    Ok(settings)
+}
+
+/// Print the Waybar error output; `location` is the place whose fetch failed, if the
+/// failure was in fetching
+fn print_error(
+    location: Option<&LocationQuery>,
+    service: &str,
+    error: anyhow::Error,
+) -> Result<()> {
+    let location = location.map(ToString::to_string);
+    let error_output = WaybarFormatter::create_error_output(location.as_deref(), service, error);
+    println!("{}", serde_json::to_string(&error_output)?);
+    Ok(())
+}
+
+fn parse_seconds(value: &str) -> Result<time::Duration> {
+    let seconds: u32 = value
+        .parse()
+        .with_context(|| format!("Invalid number of seconds: {}", value))?;
+    Ok(time::Duration::seconds(seconds.into()))
+}
+
+/// Parse the number of a place listed by `search`, which counts from 1
+fn parse_match_number(value: &str) -> Result<usize> {
+    match value.parse() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => anyhow::bail!(
+            "Invalid place number: {}. Use a number listed by search",
+            value
+        ),
+    }
+}
+
+/// Parse the daemon refresh interval, which must be positive
+fn parse_interval(value: &str) -> Result<time::Duration> {
+    let interval = parse_seconds(value)?;
+    if interval.is_zero() {
+        anyhow::bail!("--interval must be at least one second");
+    }
+    Ok(interval)
+}
+
+/// Print a line for every minute, and after every signal, until stdout is closed.
+///
+/// Waybar reads one JSON object per line from a persistent `exec`; ticking on the
+/// minute lets the upcoming hours roll forward between refreshes. With `rotate`, every
+/// minute also moves the bar on to the next location.
+fn run_daemon<F: WeatherFetcher + Sync>(
+    mut daemon: Daemon<F, WaybarFormatter>,
+    actions: Receiver<SignalAction>,
+    location: &LocationQuery,
+    service: &str,
+    rotate: bool,
+) -> Result<()> {
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let now = OffsetDateTime::now_utc();
+        let line = match daemon.tick(now) {
+            Some(Ok(output)) => Some(serde_json::to_string(&output)?),
+            Some(Err(e)) => Some(serde_json::to_string(
+                &WaybarFormatter::create_error_output(Some(&location.to_string()), service, e),
+            )?),
+            None => None,
+        };
+        if let Some(line) = line {
+            // Waybar has gone away once the pipe closes; exit rather than spin
+            writeln!(stdout, "{}", line).context("Failed to write to stdout")?;
+            stdout.flush().context("Failed to write to stdout")?;
+        }
+
+        let until_next_minute = 60 - u64::from(now.second());
+        match actions.recv_timeout(std::time::Duration::from_secs(until_next_minute)) {
+            Ok(SignalAction::Refresh) => daemon.refresh_now(),
+            Ok(SignalAction::CycleMode) => daemon.formatter_mut().cycle_mode(),
+            Ok(SignalAction::NextLocation) => daemon.show_next_location(),
+            Err(RecvTimeoutError::Timeout) if rotate => daemon.show_next_location(),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => anyhow::bail!("Signal listener stopped"),
+        }
+    }
+}
+
+/// Where the computer is, for profiles with `auto_location`: GeoClue, falling back to
+/// the last fix kept in the state directory
+fn current_location_source() -> CachedLocator<GeoClueLocator> {
+    let state_dir = paths::state_dir_or_temp();
+    CachedLocator::new(
+        GeoClueLocator::new(),
+        state_dir.join(geoclue::FIX_FILE_NAME),
+        geoclue::FIX_TTL,
+    )
+}
+
+/// Print a short description of a place found by `search`
+fn format_place(place: &PlaceMatch) -> String {
+    let details = place
+        .location
+        .coordinates()
+        .map(|coordinates| {
+            format!(
+                "{:.4}, {:.4}",
+                coordinates.latitude(),
+                coordinates.longitude()
+            )
+        })
+        .into_iter()
+        .chain(place.id.map(|id| format!("id:{}", id)))
+        .collect::<Vec<_>>();
+    if details.is_empty() {
+        place.location.full_name()
+    } else {
+        format!("{} ({})", place.location.full_name(), details.join(", "))
+    }
+}
 
-    let client = match WeatherClient::new() {
-        Ok(client) => client,
+/// The `bar` command: print Waybar JSON once, or continuously in daemon mode
+fn run_bar(args: &Args, settings: Settings, agent: &ureq::Agent) -> Result<()> {
+    let location = settings.location;
+    let extra_locations = settings.extra_locations;
+    let chain = match Provider::create_chain(&settings.providers, agent) {
+        Ok(chain) => chain,
         Err(e) => {
-            let error_output = WaybarFormatter::create_error_output(&location, e);
-            println!("{}", serde_json::to_string(&error_output)?);
-            return Ok(());
+            return print_error(None, &Provider::chain_name(&settings.providers), e);
         }
     };
-    let formatter = WaybarFormatter::new();
+    let cache_dir = paths::cache_dir_or_temp();
+    // The daemon schedules its own refreshes (and can be told to refresh now), so it
+    // only relies on the cache for stale data when a refresh fails
+    let cache_ttl = if args.daemon {
+        time::Duration::ZERO
+    } else {
+        settings.cache_ttl
+    };
+    let labelled = LabelledFetcher::new(chain, settings.location_name);
+    let cached = CachedFetcher::new(labelled, cache_dir, cache_ttl);
+    let client: Box<dyn WeatherFetcher + Send + Sync> = if settings.notify_alerts {
+        let state_dir = paths::state_dir_or_temp();
+        Box::new(NotifyingFetcher::new(
+            cached,
+            DesktopNotifier::new(),
+            state_dir.join(notify::STATE_FILE_NAME),
+        ))
+    } else {
+        Box::new(cached)
+    };
+    let client = LocatingFetcher::new(client, current_location_source());
+    let formatter = WaybarFormatter::new()
+        .with_hours(settings.hours)
+        .with_units(settings.units)
+        .with_umbrella_hours(settings.umbrella_hours)
+        .with_aqi_threshold(settings.aqi_threshold)
+        .with_bar_fields(settings.bar_fields)
+        .with_percentage(settings.percentage)
+        .with_text_template(settings.text_template)
+        .with_tooltip_template(settings.tooltip_template)
+        .with_icons(settings.icons)
+        .with_clock_zone(settings.time_zone)
+        .with_wind_style(settings.wind_style);
 
-    match app::fetch_and_format(&client, &formatter, &location) {
+    if args.daemon {
+        let service = client.name().to_string();
+        let actions =
+            match signals::listen(args.refresh_signal, args.mode_signal, args.location_signal) {
+                Ok(actions) => actions,
+                Err(e) => return print_error(None, &service, e),
+            };
+        let rotate = settings.rotate_locations && !extra_locations.is_empty();
+        let daemon = Daemon::new(client, formatter, location.clone(), settings.interval)
+            .with_more_locations(extra_locations);
+        return run_daemon(daemon, actions, &location, &service, rotate);
+    }
+
+    let locations = std::iter::once(location)
+        .chain(extra_locations)
+        .collect::<Vec<_>>();
+    // Each run is one Waybar refresh, so the rotation moves on once per run
+    let shown = if settings.rotate_locations && locations.len() > 1 {
+        let state_dir = paths::state_dir_or_temp();
+        rotation::advance(&state_dir.join(rotation::STATE_FILE_NAME), locations.len())
+    } else {
+        0
+    };
+    match app::fetch_and_format(&client, &formatter, &locations, shown) {
         Ok(output) => {
             println!("{}", serde_json::to_string(&output)?);
         }
-        Err(e) => {
-            let error_output = WaybarFormatter::create_error_output(&location, e);
-            println!("{}", serde_json::to_string(&error_output)?);
+        Err(e) => print_error(Some(&locations[shown]), client.name(), e)?,
+    }
+
+    Ok(())
+}
+
+/// The `show` command: print a report for reading in a terminal
+fn run_show(settings: Settings, agent: &ureq::Agent) -> Result<()> {
+    let chain = Provider::create_chain(&settings.providers, agent)?;
+    let cache_dir = paths::cache_dir_or_temp();
+    let labelled = LabelledFetcher::new(chain, settings.location_name);
+    let cached = CachedFetcher::new(labelled, cache_dir, settings.cache_ttl);
+    let client = LocatingFetcher::new(cached, current_location_source());
+    let formatter = TerminalFormatter::new()
+        .with_hours(settings.hours)
+        .with_units(settings.units)
+        .with_icons(settings.icons)
+        .with_clock_zone(settings.time_zone);
+
+    let locations = std::iter::once(settings.location.clone())
+        .chain(settings.extra_locations)
+        .collect::<Vec<_>>();
+    let report = app::fetch_and_format(&client, &formatter, &locations, 0)
+        .with_context(|| format!("Failed to fetch weather for {}", settings.location))?;
+    println!("{}", report);
+    Ok(())
+}
+
+/// The searcher of the first configured provider that can be set up, so the places
+/// listed are those that provider would choose between
+fn create_searcher(settings: &Settings, agent: &ureq::Agent) -> Result<Box<dyn LocationSearcher>> {
+    let mut failures = Vec::new();
+    for provider in &settings.providers {
+        match provider.create_searcher(agent.clone()) {
+            Ok(searcher) => return Ok(searcher),
+            Err(e) => failures.push(format!("• {}: {:#}", provider.name(), e)),
         }
     }
+    anyhow::bail!(
+        "No location search could be set up:\n{}",
+        failures.join("\n")
+    )
+}
+
+/// The `search` command: list places matching a name, or save the chosen one to the
+/// configuration profile
+fn run_search(args: &Args, settings: &Settings, agent: &ureq::Agent) -> Result<()> {
+    let query = args.query.as_deref().unwrap_or_default().trim();
+    let searcher = create_searcher(settings, agent)?;
+    let places = searcher.search_places(query, SEARCH_RESULTS)?;
+    if places.is_empty() {
+        anyhow::bail!("No location found matching '{}'", query);
+    }
+
+    let Some(number) = args.save else {
+        println!("Places matching '{}' ({}):", query, searcher.name());
+        for (index, place) in places.iter().enumerate() {
+            println!("{:>2}. {}", index + 1, format_place(place));
+        }
+        println!();
+        println!("Save one to the configuration with --save <number>");
+        return Ok(());
+    };
 
+    let place = places.get(number - 1).with_context(|| {
+        format!(
+            "No place numbered {}; the search found {}",
+            number,
+            places.len()
+        )
+    })?;
+    let path = config_path().context("No configuration directory to save to")?;
+    let profile = config::save_location(&path, args.profile.as_deref(), place)?;
+    println!(
+        "Saved {} as the location of profile '{}' in {}",
+        format_place(place),
+        profile,
+        path.display()
+    );
     Ok(())
 }
 
+/// The `doctor` command: report the configuration and try every provider.
+///
+/// Providers are fetched directly, bypassing the cache, so each result reflects
+/// the provider's current reachability. Fails if no configured provider works.
+fn run_doctor(settings: &Settings, agent: &ureq::Agent) -> Result<()> {
+    match config_path() {
+        Some(path) if path.exists() => println!("Configuration: {}", path.display()),
+        Some(path) => println!(
+            "Configuration: {} (not found, using defaults)",
+            path.display()
+        ),
+        None => println!("Configuration: no configuration directory, using defaults"),
+    }
+    match paths::cache_dir() {
+        Ok(dir) => println!("Cache: {}", dir.display()),
+        Err(e) => println!("Cache: unavailable ({:#})", e),
+    }
+    let shown_as = match &settings.location_name {
+        Some(name) => format!(" (shown as {})", name),
+        None => String::new(),
+    };
+    let location = match &settings.location {
+        // Asks GeoClue directly, like the providers below, rather than trusting the last fix
+        LocationQuery::Current => {
+            let coordinates = GeoClueLocator::new()
+                .locate()
+                .context("Failed to find the current location")?;
+            println!("Location: {}, found by GeoClue{}", coordinates, shown_as);
+            LocationQuery::Coordinates(coordinates)
+        }
+        location => {
+            println!("Location: {}{}", location, shown_as);
+            location.clone()
+        }
+    };
+    if !settings.extra_locations.is_empty() {
+        // Coordinates contain commas, so the places are separated otherwise
+        let extra_locations = settings
+            .extra_locations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        println!("Extra locations: {}", extra_locations.join("; "));
+    }
+    println!("Providers: {}", Provider::chain_name(&settings.providers));
+    println!();
+
+    let mut working = 0;
+    for &provider in ALL_PROVIDERS {
+        let configured = settings.providers.contains(&provider);
+        let started = std::time::Instant::now();
+        let result = provider
+            .create_fetcher(agent.clone())
+            .and_then(|fetcher| fetcher.fetch_weather(&location));
+        let note = if configured { "" } else { " (not configured)" };
+
+        match result {
+            Ok(data) => {
+                if configured {
+                    working += 1;
+                }
+                println!(
+                    "✓ {}{}: {}, {} ({} ms)",
+                    provider,
+                    note,
+                    data.current.temperature,
+                    data.current.condition,
+                    started.elapsed().as_millis()
+                );
+                // Shows which place the provider resolved the location to
+                let coordinates = data.location.coordinates().map(|coordinates| {
+                    format!(
+                        " ({:.4}, {:.4})",
+                        coordinates.latitude(),
+                        coordinates.longitude()
+                    )
+                });
+                println!(
+                    "    {}{}",
+                    data.location.full_name(),
+                    coordinates.unwrap_or_default()
+                );
+            }
+            Err(e) => println!("✗ {}{}: {:#}", provider, note, e),
+        }
+    }
+
+    if working == 0 {
+        anyhow::bail!("None of the configured providers returned weather data");
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let raw_args: Vec<String> = std::env::args().skip(1).collect();
+    let requested = raw_args
+        .first()
+        .and_then(|arg| Command::from_name(arg))
+        .unwrap_or_default();
+    let defaults = Settings::default();
+
+    // The bar reports errors through Waybar's output; other commands on stderr
+    let args = match Args::parse(raw_args) {
+        Ok(args) => args,
+        Err(e) if requested == Command::Bar => {
+            return print_error(None, &Provider::chain_name(&defaults.providers), e)
+        }
+        Err(e) => return Err(e),
+    };
+    if args.command == Command::Help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let settings = match load_settings(&args) {
+        Ok(settings) => settings,
+        Err(e) if args.command == Command::Bar => {
+            let providers = args.providers.as_ref().unwrap_or(&defaults.providers);
+            return print_error(None, &Provider::chain_name(providers), e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    // One agent for every provider, kept alive across refreshes in daemon mode
+    let agent = http_agent(settings.timeout);
+    match args.command {
+        Command::Bar => run_bar(&args, settings, &agent),
+        Command::Show => run_show(settings, &agent),
+        Command::Search => run_search(&args, &settings, &agent),
+        Command::Doctor => run_doctor(&settings, &agent),
+        Command::Help => unreachable!("usage is printed before loading settings"),
+    }
+}
+
 #[cfg(test)]
 mod integration_tests {
     use super::*;
+    use app::DEFAULT_REFRESH_INTERVAL;
+    use domain::{Coordinates, Location};
+    use infra::api::provider::DEFAULT_PROVIDERS;
+    use infra::api::WeatherClient;
+    use infra::cache::DEFAULT_TTL;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    /// Settings from the built-in defaults overridden by the given arguments
+    fn resolve(values: &[&str]) -> Settings {
+        let mut settings = Settings::default();
+        Args::parse(args(values)).unwrap().apply(&mut settings);
+        settings
+    }
 
     #[test]
     fn test_full_weather_flow() {
@@ -54,7 +759,8 @@ mod integration_tests {
         let client = WeatherClient::new().expect("Failed to create client in test");
         let formatter = WaybarFormatter::new();
 
-        match app::fetch_and_format(&client, &formatter, "Wellington") {
+        let locations = [Settings::default().location];
+        match app::fetch_and_format(&client, &formatter, &locations, 0) {
             Ok(output) => {
                 assert!(!output.text.is_empty());
                 assert!(!output.tooltip.is_empty());
@@ -75,7 +781,8 @@ mod integration_tests {
     fn test_error_handling_flow() {
         let location = "test_location";
         let error = anyhow::anyhow!("Test error");
-        let error_output = WaybarFormatter::create_error_output(location, error);
+        let error_output =
+            WaybarFormatter::create_error_output(Some(location), "WeatherAPI.com", error);
 
         assert!(error_output.text.contains("unavailable"));
         assert!(error_output.tooltip.contains("test_location"));
@@ -101,4 +808,299 @@ mod integration_tests {
         // Test that custom location would be passed through
         assert_eq!(test_location, "Auckland");
     }
+
+    #[test]
+    fn test_args_default() {
+        let parsed = resolve(&[]);
+        assert_eq!(parsed.location.to_string(), "Wellington");
+        assert_eq!(
+            parsed.providers,
+            vec![Provider::WeatherApi, Provider::OpenMeteo]
+        );
+    }
+
+    #[test]
+    fn test_args_bare_location() {
+        let parsed = resolve(&["New York"]);
+        assert_eq!(parsed.location.to_string(), "New York");
+        assert_eq!(parsed.providers, DEFAULT_PROVIDERS);
+    }
+
+    #[test]
+    fn test_args_structured_location() {
+        let parsed = resolve(&["--provider", "openmeteo", "-41.2866,174.7756"]);
+        assert_eq!(parsed.location.to_string(), "-41.2866,174.7756");
+        assert!(matches!(parsed.location, LocationQuery::Coordinates(_)));
+
+        assert_eq!(
+            resolve(&["iata:akl"]).location,
+            LocationQuery::Airport("AKL".to_string())
+        );
+        // The search query is kept as typed
+        let parsed = Args::parse(args(&["search", "id:42"])).unwrap();
+        assert_eq!(parsed.query.as_deref(), Some("id:42"));
+    }
+
+    #[test]
+    fn test_args_provider_flag() {
+        let parsed = resolve(&["--provider", "openmeteo", "Auckland"]);
+        assert_eq!(parsed.location.to_string(), "Auckland");
+        assert_eq!(parsed.providers, vec![Provider::OpenMeteo]);
+
+        let parsed = resolve(&["Auckland", "--provider=open-meteo"]);
+        assert_eq!(parsed.location.to_string(), "Auckland");
+        assert_eq!(parsed.providers, vec![Provider::OpenMeteo]);
+    }
+
+    #[test]
+    fn test_args_provider_chain() {
+        let parsed = resolve(&["--provider", "nws,openmeteo", "Denver"]);
+        assert_eq!(parsed.location.to_string(), "Denver");
+        assert_eq!(parsed.providers, vec![Provider::Nws, Provider::OpenMeteo]);
+    }
+
+    #[test]
+    fn test_args_cache_ttl() {
+        assert_eq!(resolve(&[]).cache_ttl, DEFAULT_TTL);
+
+        let parsed = resolve(&["--cache-ttl", "0", "Auckland"]);
+        assert_eq!(parsed.location.to_string(), "Auckland");
+        assert_eq!(parsed.cache_ttl, time::Duration::ZERO);
+
+        let parsed = resolve(&["--cache-ttl=1800"]);
+        assert_eq!(parsed.cache_ttl, time::Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_args_daemon() {
+        assert!(!Args::parse(args(&["Auckland"])).unwrap().daemon);
+        assert_eq!(resolve(&["Auckland"]).interval, DEFAULT_REFRESH_INTERVAL);
+
+        let parsed = Args::parse(args(&["--daemon", "--interval", "900", "Auckland"])).unwrap();
+        assert!(parsed.daemon);
+        let parsed = resolve(&["--daemon", "--interval", "900", "Auckland"]);
+        assert_eq!(parsed.location.to_string(), "Auckland");
+        assert_eq!(parsed.interval, time::Duration::minutes(15));
+
+        let parsed = resolve(&["--daemon", "--interval=60"]);
+        assert_eq!(parsed.interval, time::Duration::minutes(1));
+    }
+
+    #[test]
+    fn test_args_units() {
+        assert_eq!(resolve(&[]).units, UnitSystem::METRIC);
+        assert_eq!(
+            resolve(&["--units", "imperial"]).units,
+            UnitSystem::IMPERIAL
+        );
+        assert_eq!(resolve(&["--units=uk", "London"]).units, UnitSystem::UK);
+    }
+
+    #[test]
+    fn test_args_override_profile() {
+        let config = Config::parse(
+            r#"
+            [profiles.work]
+            location = "London"
+            provider = "metno"
+            cache_ttl = 300
+            "#,
+        )
+        .unwrap();
+
+        let parsed = Args::parse(args(&["--profile", "work"])).unwrap();
+        assert_eq!(parsed.profile.as_deref(), Some("work"));
+        let mut settings = config.settings(parsed.profile.as_deref()).unwrap();
+        parsed.apply(&mut settings);
+        assert_eq!(settings.location.to_string(), "London");
+        assert_eq!(settings.providers, vec![Provider::MetNo]);
+        assert_eq!(settings.cache_ttl, time::Duration::minutes(5));
+
+        // Command-line options win over the profile
+        let parsed = Args::parse(args(&["--profile=work", "--cache-ttl", "0", "Paris"])).unwrap();
+        let mut settings = config.settings(parsed.profile.as_deref()).unwrap();
+        parsed.apply(&mut settings);
+        assert_eq!(settings.location.to_string(), "Paris");
+        assert_eq!(settings.providers, vec![Provider::MetNo]);
+        assert_eq!(settings.cache_ttl, time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_args_signals() {
+        let parsed = Args::parse(args(&[])).unwrap();
+        assert_eq!(parsed.refresh_signal, libc::SIGUSR1);
+        assert_eq!(parsed.mode_signal, libc::SIGUSR2);
+
+        let parsed = Args::parse(args(&[
+            "--daemon",
+            "--refresh-signal",
+            "RTMIN+8",
+            "--mode-signal=RTMIN+9",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.refresh_signal, libc::SIGRTMIN() + 8);
+        assert_eq!(parsed.mode_signal, libc::SIGRTMIN() + 9);
+    }
+
+    #[test]
+    fn test_args_commands() {
+        // A bare location is the bar command, as before subcommands existed
+        let parsed = Args::parse(args(&["Auckland"])).unwrap();
+        assert_eq!(parsed.command, Command::Bar);
+        assert_eq!(
+            parsed.locations,
+            [LocationQuery::Name("Auckland".to_string())]
+        );
+
+        let parsed = Args::parse(args(&["bar", "--daemon", "Auckland"])).unwrap();
+        assert_eq!(parsed.command, Command::Bar);
+        assert!(parsed.daemon);
+
+        let parsed = Args::parse(args(&["show", "--provider", "metno", "Oslo"])).unwrap();
+        assert_eq!(parsed.command, Command::Show);
+        assert_eq!(parsed.locations, [LocationQuery::Name("Oslo".to_string())]);
+
+        let parsed = Args::parse(args(&["search", "Springfield"])).unwrap();
+        assert_eq!(parsed.command, Command::Search);
+        assert_eq!(parsed.query.as_deref(), Some("Springfield"));
+        assert!(parsed.locations.is_empty());
+
+        assert_eq!(
+            Args::parse(args(&["doctor"])).unwrap().command,
+            Command::Doctor
+        );
+        assert_eq!(Args::parse(args(&["help"])).unwrap().command, Command::Help);
+        assert_eq!(
+            Args::parse(args(&["show", "-h"])).unwrap().command,
+            Command::Help
+        );
+
+        // Only a leading argument names a command
+        let parsed = Args::parse(args(&["--cache-ttl", "0", "show"])).unwrap();
+        assert_eq!(parsed.command, Command::Bar);
+        assert_eq!(parsed.locations, [LocationQuery::Name("show".to_string())]);
+    }
+
+    #[test]
+    fn test_format_place() {
+        let location = Location::new("Springfield".to_string())
+            .with_region(Some("Illinois".to_string()))
+            .with_country(Some("United States".to_string()))
+            .with_coordinates(Coordinates::new(39.80172, -89.64371).ok());
+        let mut place = PlaceMatch {
+            location,
+            id: Some(2637890),
+        };
+        assert_eq!(
+            format_place(&place),
+            "Springfield, Illinois, United States (39.8017, -89.6437, id:2637890)"
+        );
+
+        place.id = None;
+        assert_eq!(
+            format_place(&place),
+            "Springfield, Illinois, United States (39.8017, -89.6437)"
+        );
+
+        place.location =
+            Location::new("Springfield".to_string()).with_region(Some("Springfield".to_string()));
+        assert_eq!(format_place(&place), "Springfield");
+    }
+
+    #[test]
+    fn test_args_save() {
+        let parsed = Args::parse(args(&["search", "Richmond", "--save", "2"])).unwrap();
+        assert_eq!(parsed.query.as_deref(), Some("Richmond"));
+        assert_eq!(parsed.save, Some(2));
+        assert_eq!(
+            Args::parse(args(&["search", "--save=1", "Richmond"]))
+                .unwrap()
+                .save,
+            Some(1)
+        );
+
+        assert!(Args::parse(args(&["search", "Richmond", "--save", "0"])).is_err());
+        assert!(Args::parse(args(&["search", "Richmond", "--save", "first"])).is_err());
+        assert!(Args::parse(args(&["--save", "1", "Richmond"])).is_err());
+    }
+
+    #[test]
+    fn test_args_location_drops_profile_location_name() {
+        let config = Config::parse(
+            r#"
+            [profiles.home]
+            location = "37.55,-77.46"
+            location_name = "Richmond"
+            "#,
+        )
+        .unwrap();
+
+        let mut settings = config.settings(Some("home")).unwrap();
+        Args::parse(args(&[])).unwrap().apply(&mut settings);
+        assert_eq!(settings.location_name.as_deref(), Some("Richmond"));
+
+        Args::parse(args(&["Paris"])).unwrap().apply(&mut settings);
+        assert_eq!(settings.location.to_string(), "Paris");
+        assert_eq!(settings.location_name, None);
+    }
+
+    #[test]
+    fn test_args_several_locations() {
+        let config = Config::parse(
+            r#"
+            [profiles.team]
+            location = "Wellington"
+            location_name = "Office"
+            extra_locations = ["Auckland", "Christchurch"]
+            "#,
+        )
+        .unwrap();
+
+        let mut settings = config.settings(Some("team")).unwrap();
+        Args::parse(args(&[])).unwrap().apply(&mut settings);
+        assert_eq!(settings.extra_locations.len(), 2);
+        assert!(!settings.rotate_locations);
+
+        // Locations on the command line replace the profile's, extra ones included
+        let parsed = Args::parse(args(&["--rotate", "Paris", "-33.87,151.21"])).unwrap();
+        parsed.apply(&mut settings);
+        assert_eq!(settings.location.to_string(), "Paris");
+        assert_eq!(settings.location_name, None);
+        assert_eq!(
+            settings.extra_locations,
+            [LocationQuery::Coordinates(
+                Coordinates::new(-33.87, 151.21).unwrap()
+            )]
+        );
+        assert!(settings.rotate_locations);
+
+        let parsed = Args::parse(args(&["--daemon", "--location-signal", "RTMIN+3"])).unwrap();
+        assert_eq!(parsed.location_signal, Some(libc::SIGRTMIN() + 3));
+    }
+
+    #[test]
+    fn test_args_errors() {
+        assert!(Args::parse(args(&["--provider"])).is_err());
+        assert!(Args::parse(args(&["--provider", "nope"])).is_err());
+        assert!(Args::parse(args(&["--provider", ","])).is_err());
+        assert!(Args::parse(args(&["search", "Richmond", "Virginia"])).is_err());
+        assert!(Args::parse(args(&["--cache-ttl", "soon"])).is_err());
+        assert!(Args::parse(args(&["--cache-ttl", "-5"])).is_err());
+        assert!(Args::parse(args(&["--interval"])).is_err());
+        assert!(Args::parse(args(&["--interval", "0"])).is_err());
+        assert!(Args::parse(args(&["--refresh-signal", "HUP"])).is_err());
+        assert!(Args::parse(args(&["--mode-signal"])).is_err());
+        assert!(Args::parse(args(&["--profile"])).is_err());
+        assert!(Args::parse(args(&["--units", "nautical"])).is_err());
+        assert!(Args::parse(args(&["search"])).is_err());
+        assert!(Args::parse(args(&["-41.3,200"])).is_err());
+        assert!(Args::parse(args(&["iata:"])).is_err());
+        assert!(Args::parse(args(&["show", "--daemon"])).is_err());
+        assert!(Args::parse(args(&["show", "--rotate", "Auckland", "London"])).is_err());
+        assert!(Args::parse(args(&["--interval", "900"])).is_err());
+        assert!(Args::parse(args(&["--refresh-signal=RTMIN+8"])).is_err());
+        assert!(Args::parse(args(&["show", "--mode-signal", "USR1"])).is_err());
+        assert!(Args::parse(args(&["doctor", "--location-signal", "RTMIN+3"])).is_err());
+        assert!(Args::parse(args(&["search", "Richmond", "--interval=60"])).is_err());
+    }
 }
@@ -0,0 +1,86 @@
+//! Helpers shared by the tests of several modules.
+
+use crate::domain::{
+    CurrentWeather, Humidity, LastUpdated, Location, Pressure, Temperature, WeatherCondition,
+    WeatherData, WeatherDay, WindDirection, WindSpeed,
+};
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use time::macros::datetime;
+
+/// Weather for a place fetched at 14:30 on 13 January 2023 (NZDT), with no forecast.
+///
+/// Tests change the fields they care about, e.g.
+/// `WeatherData { alerts, ..weather_data("Wellington") }`.
+pub fn weather_data(location: &str) -> WeatherData {
+    WeatherData {
+        current: CurrentWeather {
+            last_updated: LastUpdated::from_epoch(1673620200).unwrap(),
+            temperature: Temperature::new(18.0).unwrap(),
+            feels_like: Temperature::new(16.0).unwrap(),
+            condition: WeatherCondition::new("Partly cloudy".to_string()),
+            humidity: Humidity::new(72.0).unwrap(),
+            wind_speed: WindSpeed::new(25).unwrap(),
+            wind_direction: WindDirection::SW,
+            pressure: Pressure::new(1010).unwrap(),
+            precipitation: None,
+            uv_index: None,
+            visibility: None,
+            cloud_cover: None,
+        },
+        location: Location::new(location.to_string()),
+        weather_day: Some(WeatherDay {
+            astronomy: None,
+            hourly_weather: vec![],
+        }),
+        daily: vec![],
+        alerts: vec![],
+        air_quality: None,
+        source: "Stub".to_string(),
+        local_time: datetime!(2023-01-13 14:30 +13),
+        time_zone: None,
+        stale_since: None,
+    }
+}
+
+/// Private bus daemon standing in for the session or system bus, stopped when dropped
+pub struct TestBus {
+    daemon: Child,
+    pub address: String,
+}
+
+impl TestBus {
+    /// Start a private bus, or `None` (noting that the test is skipped) when
+    /// `dbus-daemon` is not installed
+    pub fn start() -> Option<Self> {
+        let bus = Self::spawn();
+        if bus.is_none() {
+            eprintln!("skipping D-Bus test: dbus-daemon could not be started");
+        }
+        bus
+    }
+
+    fn spawn() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for TestBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}